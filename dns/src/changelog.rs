@@ -0,0 +1,317 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// This file is part of Neatcoin.
+//
+// Copyright (c) 2021 Wei Tang.
+//
+// Neatcoin is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Neatcoin is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Neatcoin. If not, see <http://www.gnu.org/licenses/>.
+
+//! History of zone changes at finalized blocks, kept on disk to serve IXFR.
+//!
+//! The changelog is an append-only file of SCALE-encoded entries. It starts
+//! with the block history begins at, and each finalized block appends its
+//! changes followed by a marker that the block was processed.
+
+use crate::Error;
+use codec::{Decode, Encode};
+use neatcoin_nomo::{DnsKey, Ds, RawRrsig, Records};
+use np_domain::Name;
+use np_opaque::BlockNumber;
+use pallet_zone::{RawIpv4, RawIpv6};
+use std::{
+	fs::{self, File, OpenOptions},
+	io::{ErrorKind, Write},
+	path::{Path, PathBuf},
+};
+
+const FILE_NAME: &str = "changelog";
+/// Number of changes kept. Once exceeded, the oldest blocks are dropped
+/// until half of them are left.
+pub const MAX_CHANGES: usize = 65536;
+
+/// Zone records of a name, without the ICANN, OpenNIC and Handshake flags
+/// and the DNSSEC mode, which are not served as records.
+#[derive(Clone, Debug, Default, Encode, Decode, Eq, PartialEq)]
+pub struct Snapshot {
+	pub a: Vec<RawIpv4>,
+	pub aaaa: Vec<RawIpv6>,
+	pub ns: Vec<Name>,
+	pub cname: Option<Name>,
+	pub mx: Option<(u16, Name)>,
+	pub dnskey: Vec<DnsKey>,
+	pub ds: Vec<Ds>,
+	pub rrsig: Vec<RawRrsig>,
+}
+
+impl From<&Records> for Snapshot {
+	fn from(records: &Records) -> Self {
+		Self {
+			a: records.a.iter().cloned().map(RawIpv4::from).collect(),
+			aaaa: records.aaaa.iter().cloned().map(RawIpv6::from).collect(),
+			ns: records.ns.clone(),
+			cname: records.cname.clone(),
+			mx: records.mx.clone(),
+			dnskey: records.dnskey.clone(),
+			ds: records.ds.clone(),
+			rrsig: records.rrsig.clone(),
+		}
+	}
+}
+
+impl From<&Snapshot> for Records {
+	fn from(snapshot: &Snapshot) -> Self {
+		Self {
+			a: snapshot.a.iter().cloned().map(Into::into).collect(),
+			aaaa: snapshot.aaaa.iter().cloned().map(Into::into).collect(),
+			ns: snapshot.ns.clone(),
+			cname: snapshot.cname.clone(),
+			mx: snapshot.mx.clone(),
+			dnskey: snapshot.dnskey.clone(),
+			ds: snapshot.ds.clone(),
+			rrsig: snapshot.rrsig.clone(),
+			..Default::default()
+		}
+	}
+}
+
+/// Records of a name before and after a finalized block.
+#[derive(Clone, Debug, Encode, Decode, Eq, PartialEq)]
+pub struct Change {
+	pub number: BlockNumber,
+	pub name: Name,
+	pub old: Snapshot,
+	pub new: Snapshot,
+}
+
+#[derive(Encode, Decode)]
+enum Entry {
+	Start(BlockNumber),
+	Change(Box<Change>),
+	Processed(BlockNumber),
+}
+
+/// Zone changes from block `start` up to block `processed`.
+pub struct Changelog {
+	path: Option<PathBuf>,
+	file: Option<File>,
+	start: BlockNumber,
+	processed: BlockNumber,
+	changes: Vec<Change>,
+}
+
+impl Changelog {
+	/// Open the changelog in `dir`, or keep it in memory if there is none. A
+	/// missing or unreadable changelog starts over at block `finalized`.
+	pub fn open(dir: Option<&Path>, finalized: BlockNumber) -> Result<Self, Error> {
+		let mut changelog = Self {
+			path: dir.map(|dir| dir.join(FILE_NAME)),
+			file: None,
+			start: finalized,
+			processed: finalized,
+			changes: Vec::new(),
+		};
+
+		let path = match (dir, &changelog.path) {
+			(Some(dir), Some(path)) => {
+				fs::create_dir_all(dir)?;
+				path.clone()
+			}
+			_ => return Ok(changelog),
+		};
+
+		match fs::read(&path) {
+			Ok(raw) => match Self::decode(&raw) {
+				Ok((start, processed, changes)) => {
+					changelog.start = start;
+					changelog.processed = processed;
+					changelog.changes = changes;
+					// Drops the tail of an interrupted write, if any.
+					changelog.rewrite()?;
+				}
+				Err(e) => {
+					log::warn!(target: "dns", "Starting over the changelog at #{}: {}", finalized, e);
+					changelog.rewrite()?;
+				}
+			},
+			Err(e) if e.kind() == ErrorKind::NotFound => changelog.rewrite()?,
+			Err(e) => return Err(e.into()),
+		}
+
+		Ok(changelog)
+	}
+
+	fn decode(mut raw: &[u8]) -> Result<(BlockNumber, BlockNumber, Vec<Change>), Error> {
+		let start = match Entry::decode(&mut raw).map_err(Error::Changelog)? {
+			Entry::Start(start) => start,
+			_ => return Err(Error::Changelog("missing start".into())),
+		};
+
+		let mut processed = start;
+		let mut changes = Vec::new();
+		let mut pending = Vec::new();
+		while !raw.is_empty() {
+			match Entry::decode(&mut raw) {
+				Ok(Entry::Change(change)) => pending.push(*change),
+				Ok(Entry::Processed(number)) => {
+					processed = number;
+					changes.append(&mut pending);
+				}
+				Ok(Entry::Start(_)) => return Err(Error::Changelog("duplicate start".into())),
+				// A write interrupted by a crash. Changes of the block are
+				// recorded again, as it is not marked processed.
+				Err(_) => break,
+			}
+		}
+
+		Ok((start, processed, changes))
+	}
+
+	/// First block of the history. Changes after it are known.
+	pub fn start(&self) -> BlockNumber {
+		self.start
+	}
+
+	/// Last block whose changes were recorded.
+	pub fn processed(&self) -> BlockNumber {
+		self.processed
+	}
+
+	/// Changes after block `from`, up to the last processed one. Returns
+	/// `None` if the history does not reach back to `from`.
+	pub fn since(&self, from: BlockNumber) -> Option<&[Change]> {
+		if from < self.start || from > self.processed {
+			return None
+		}
+
+		let first = self.changes.partition_point(|change| change.number <= from);
+		Some(&self.changes[first..])
+	}
+
+	/// Record the changes of finalized block `number`, which must follow the
+	/// last processed one.
+	pub fn record(&mut self, number: BlockNumber, changes: Vec<Change>) -> Result<(), Error> {
+		debug_assert_eq!(number, self.processed + 1);
+
+		if let Some(file) = &mut self.file {
+			let mut raw = Vec::new();
+			for change in &changes {
+				Entry::Change(Box::new(change.clone())).encode_to(&mut raw);
+			}
+			Entry::Processed(number).encode_to(&mut raw);
+			file.write_all(&raw)?;
+			file.sync_data()?;
+		}
+
+		self.processed = number;
+		self.changes.extend(changes);
+
+		if self.changes.len() > MAX_CHANGES {
+			let dropped = self.changes.len() - MAX_CHANGES / 2;
+			let start = self.changes[dropped - 1].number;
+			self.changes.retain(|change| change.number > start);
+			self.start = start;
+			self.rewrite()?;
+		}
+
+		Ok(())
+	}
+
+	/// Start the history over at block `number`, for when the blocks in
+	/// between can no longer be read.
+	pub fn reset(&mut self, number: BlockNumber) -> Result<(), Error> {
+		self.start = number;
+		self.processed = number;
+		self.changes.clear();
+		self.rewrite()
+	}
+
+	/// Replace the file with the entries kept in memory.
+	fn rewrite(&mut self) -> Result<(), Error> {
+		let path = match &self.path {
+			Some(path) => path,
+			None => return Ok(()),
+		};
+
+		let mut raw = Entry::Start(self.start).encode();
+		let mut number = self.start;
+		for change in &self.changes {
+			if change.number != number {
+				if number != self.start {
+					Entry::Processed(number).encode_to(&mut raw);
+				}
+				number = change.number;
+			}
+			Entry::Change(Box::new(change.clone())).encode_to(&mut raw);
+		}
+		if self.processed != self.start {
+			Entry::Processed(self.processed).encode_to(&mut raw);
+		}
+
+		let tmp = path.with_extension("tmp");
+		fs::write(&tmp, &raw)?;
+		fs::rename(&tmp, path)?;
+		self.file = Some(OpenOptions::new().append(true).open(path)?);
+
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn change(number: BlockNumber, name: &str, a: Vec<RawIpv4>) -> Change {
+		Change {
+			number,
+			name: Name::from_dotted(name.as_bytes()).unwrap(),
+			old: Snapshot::default(),
+			new: Snapshot {
+				a,
+				..Default::default()
+			},
+		}
+	}
+
+	#[test]
+	fn reopens_recorded_changes() {
+		let dir =
+			std::env::temp_dir().join(format!("neatcoin-dns-changelog-{}", std::process::id()));
+		let _ = fs::remove_dir_all(&dir);
+
+		let mut changelog = Changelog::open(Some(&dir), 10).unwrap();
+		changelog
+			.record(11, vec![change(11, "a.neatuser", vec![1])])
+			.unwrap();
+		changelog.record(12, vec![]).unwrap();
+		changelog
+			.record(
+				13,
+				vec![
+					change(13, "a.neatuser", vec![2]),
+					change(13, "b.neatuser", vec![3]),
+				],
+			)
+			.unwrap();
+
+		let reopened = Changelog::open(Some(&dir), 20).unwrap();
+		assert_eq!(reopened.start(), 10);
+		assert_eq!(reopened.processed(), 13);
+		assert_eq!(reopened.since(10).unwrap(), &changelog.changes[..]);
+		assert_eq!(reopened.since(11).unwrap(), &changelog.changes[1..]);
+		assert_eq!(reopened.since(13).unwrap(), &[]);
+		assert!(reopened.since(9).is_none());
+		assert!(reopened.since(14).is_none());
+
+		let _ = fs::remove_dir_all(&dir);
+	}
+}
@@ -0,0 +1,354 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// This file is part of Neatcoin.
+//
+// Copyright (c) 2021 Wei Tang.
+//
+// Neatcoin is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Neatcoin is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Neatcoin. If not, see <http://www.gnu.org/licenses/>.
+
+//! DNSSEC signatures and denials of answers.
+//!
+//! Zones in keystore mode are signed on the fly with the Ed25519 keys
+//! (algorithm 15) of key type `dnss` in the node keystore, for every DNSKEY
+//! of the apex the keystore holds. Zones in presigned mode are served with
+//! the RRSIG records their owner has stored. As missing names in keystore
+//! mode get an empty answer rather than NXDOMAIN, a denial only needs an
+//! NSEC record at the query name listing the types it has, whose next name
+//! is its immediate successor.
+
+use crate::Error;
+use neatcoin_nomo::{zonefile::SOA_TIMERS, DnsKey, Ds, NomoReader, Records, StorageRead};
+use np_domain::Name;
+use pallet_zone::Dnssec;
+use sp_core::{
+	crypto::{CryptoTypePublicPair, KeyTypeId},
+	ed25519,
+};
+use sp_keystore::{SyncCryptoStore, SyncCryptoStorePtr};
+use std::{
+	convert::TryFrom,
+	time::{SystemTime, UNIX_EPOCH},
+};
+use trust_dns_proto::{
+	rr::{
+		self,
+		dnssec::{
+			rdata::{DNSSECRData, DNSSECRecordType, DNSKEY, DS, NSEC, SIG},
+			tbs::rrset_tbs,
+			Algorithm, DigestType,
+		},
+		RData, Record, RecordType,
+	},
+	serialize::binary::{BinDecoder, Restrict},
+};
+
+/// Key type of zone signing keys in the node keystore.
+pub const KEY_TYPE: KeyTypeId = KeyTypeId(*b"dnss");
+/// Signatures are valid from an hour before the current hour, for a week.
+const VALIDITY: u32 = 7 * 24 * 3600;
+const HOUR: u32 = 3600;
+
+/// Record type of RRSIG records.
+pub const RRSIG: RecordType = RecordType::DNSSEC(DNSSECRecordType::RRSIG);
+
+/// DNSKEY record data of a stored key.
+pub fn dnskey(key: &DnsKey) -> DNSKEY {
+	DNSKEY::new(
+		key.flags & 0x0100 != 0,
+		key.flags & 0x0001 != 0,
+		key.flags & 0x0080 != 0,
+		Algorithm::from_u8(key.algorithm),
+		key.public_key.to_vec(),
+	)
+}
+
+/// DS record data of a stored digest. Returns `None` for unknown digest
+/// types.
+pub fn ds(ds: &Ds) -> Option<DS> {
+	Some(DS::new(
+		ds.key_tag,
+		Algorithm::from_u8(ds.algorithm),
+		DigestType::from_u8(ds.digest_type).ok()?,
+		ds.digest.to_vec(),
+	))
+}
+
+/// RRSIG record of `owner` from stored wire data. Returns `None` if the
+/// data does not parse, which the zone pallet rules out for records it
+/// stored.
+pub fn rrsig(owner: &rr::Name, raw: &[u8]) -> Option<Record> {
+	let parsed = u16::try_from(raw.len()).ok().and_then(|length| {
+		RData::read(&mut BinDecoder::new(raw), RRSIG, Restrict::new(length)).ok()
+	});
+	let ttl = match &parsed {
+		Some(RData::DNSSEC(DNSSECRData::SIG(sig))) => sig.original_ttl(),
+		_ => {
+			log::warn!(target: "dns", "Skipping unparsable RRSIG record of {}", owner);
+			return None
+		}
+	};
+	let rdata = parsed?;
+
+	let mut record = Record::from_rdata(owner.clone(), ttl, rdata);
+	record.set_rr_type(RRSIG);
+	Some(record)
+}
+
+/// NSEC record at `name` denying every type but `types`.
+pub fn denial(name: &rr::Name, mut types: Vec<RecordType>) -> Result<Record, Error> {
+	let next = rr::Name::root().append_label(&[0u8][..])?.append_name(name);
+	types.push(RRSIG);
+	types.sort();
+	types.dedup();
+
+	Ok(Record::from_rdata(
+		name.clone(),
+		SOA_TIMERS.3,
+		RData::DNSSEC(DNSSECRData::NSEC(NSEC::new_cover_self(next, types))),
+	))
+}
+
+/// Signing of answers from the zone of an apex.
+pub struct Signer<'a> {
+	zone: rr::Name,
+	mode: Mode<'a>,
+}
+
+enum Mode<'a> {
+	Keystore {
+		keystore: &'a SyncCryptoStorePtr,
+		keys: Vec<(u16, ed25519::Public)>,
+		inception: u32,
+		expiration: u32,
+	},
+	Presigned,
+}
+
+impl<'a> Signer<'a> {
+	/// Signer of the zone of `apex`, given the records of the apex. Returns
+	/// `None` if the zone is unsigned, or is in keystore mode and the node
+	/// holds none of its keys.
+	pub fn new(
+		zone: rr::Name,
+		records: &Records,
+		keystore: Option<&'a SyncCryptoStorePtr>,
+	) -> Result<Option<Self>, Error> {
+		let mode = match (records.dnssec, keystore) {
+			(Some(Dnssec::Presigned), _) => Mode::Presigned,
+			(Some(Dnssec::Keystore), Some(keystore)) => {
+				let held = SyncCryptoStore::ed25519_public_keys(&**keystore, KEY_TYPE);
+				let mut keys = Vec::new();
+				for key in &records.dnskey {
+					let rdata = dnskey(key);
+					let public = held
+						.iter()
+						.find(|public| public.as_ref() == &key.public_key[..]);
+					if let (true, Algorithm::ED25519, Some(public)) =
+						(rdata.zone_key(), rdata.algorithm(), public)
+					{
+						keys.push((rdata.calculate_key_tag()?, *public));
+					}
+				}
+				if keys.is_empty() {
+					return Ok(None)
+				}
+
+				let now = SystemTime::now()
+					.duration_since(UNIX_EPOCH)
+					.map(|now| now.as_secs() as u32)
+					.unwrap_or_default();
+				let inception = (now - now % HOUR).saturating_sub(HOUR);
+				Mode::Keystore {
+					keystore,
+					keys,
+					inception,
+					expiration: inception + VALIDITY,
+				}
+			}
+			_ => return Ok(None),
+		};
+
+		Ok(Some(Self { zone, mode }))
+	}
+
+	/// Whether the signer can sign records made by the server, as denials
+	/// are.
+	pub fn signs_denials(&self) -> bool {
+		matches!(self.mode, Mode::Keystore { .. })
+	}
+
+	/// Signatures of every RRset in `records`. RRSIG records are skipped.
+	/// In presigned mode, the stored RRSIG records covering each RRset are
+	/// read with `reader`.
+	pub fn sign<S: StorageRead>(
+		&self,
+		reader: &NomoReader<S>,
+		records: &[Record],
+	) -> Result<Vec<Record>, Error> {
+		let mut signatures = Vec::new();
+		for rrset in rrsets(records) {
+			let first = &rrset[0];
+			match &self.mode {
+				Mode::Keystore {
+					keystore,
+					keys,
+					inception,
+					expiration,
+				} => {
+					for (key_tag, public) in keys {
+						let tbs = rrset_tbs(
+							first.name(),
+							first.dns_class(),
+							first.name().num_labels(),
+							first.record_type(),
+							Algorithm::ED25519,
+							first.ttl(),
+							*expiration,
+							*inception,
+							*key_tag,
+							&self.zone,
+							&rrset,
+						)?;
+						let signature = SyncCryptoStore::sign_with(
+							&***keystore,
+							KEY_TYPE,
+							&CryptoTypePublicPair(ed25519::CRYPTO_ID, public.as_ref().to_vec()),
+							tbs.as_ref(),
+						)
+						.map_err(|e| Error::Keystore(e.to_string()))?;
+
+						if let Some(signature) = signature {
+							let mut record = Record::from_rdata(
+								first.name().clone(),
+								first.ttl(),
+								RData::DNSSEC(DNSSECRData::SIG(SIG::new(
+									first.record_type(),
+									Algorithm::ED25519,
+									first.name().num_labels(),
+									first.ttl(),
+									*expiration,
+									*inception,
+									*key_tag,
+									self.zone.clone(),
+									signature,
+								))),
+							);
+							record.set_rr_type(RRSIG).set_dns_class(first.dns_class());
+							signatures.push(record);
+						}
+					}
+				}
+				Mode::Presigned => {
+					let name = match crate::resolver::from_dns(first.name()) {
+						Some(name) => name,
+						None => continue,
+					};
+					signatures.extend(stored(reader, &name, first.name())?.into_iter().filter(
+						|record| match record.rdata() {
+							RData::DNSSEC(DNSSECRData::SIG(sig)) => {
+								sig.type_covered() == first.record_type()
+							}
+							_ => false,
+						},
+					));
+				}
+			}
+		}
+
+		Ok(signatures)
+	}
+}
+
+/// Stored RRSIG records of a name.
+fn stored<S: StorageRead>(
+	reader: &NomoReader<S>,
+	name: &Name,
+	owner: &rr::Name,
+) -> Result<Vec<Record>, Error> {
+	Ok(reader
+		.records(name)?
+		.rrsig
+		.iter()
+		.filter_map(|raw| rrsig(owner, raw))
+		.collect())
+}
+
+/// Records grouped into RRsets by owner and type, in order of first
+/// appearance.
+fn rrsets(records: &[Record]) -> Vec<Vec<Record>> {
+	let mut rrsets = Vec::<Vec<Record>>::new();
+	for record in records
+		.iter()
+		.filter(|record| record.record_type() != RRSIG)
+	{
+		match rrsets.iter_mut().find(|rrset| {
+			rrset[0].name() == record.name() && rrset[0].record_type() == record.record_type()
+		}) {
+			Some(rrset) => rrset.push(record.clone()),
+			None => rrsets.push(vec![record.clone()]),
+		}
+	}
+	rrsets
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use trust_dns_proto::serialize::binary::BinEncoder;
+
+	#[test]
+	fn stored_signatures_and_denials_are_records() {
+		let owner = rr::Name::from_ascii("www.example.neatuser.").unwrap();
+		let sig = RData::DNSSEC(DNSSECRData::SIG(SIG::new(
+			RecordType::A,
+			Algorithm::ED25519,
+			3,
+			600,
+			1_700_604_800,
+			1_700_000_000,
+			4242,
+			rr::Name::from_ascii("example.neatuser.").unwrap(),
+			vec![7; 64],
+		)));
+		let mut raw = Vec::new();
+		sig.emit(&mut BinEncoder::new(&mut raw)).unwrap();
+
+		let record = rrsig(&owner, &raw).unwrap();
+		assert_eq!(record.record_type(), RRSIG);
+		assert_eq!(record.ttl(), 600);
+		assert_eq!(record.rdata(), &sig);
+		assert!(rrsig(&owner, &raw[..10]).is_none());
+
+		let nsec = denial(&owner, vec![RecordType::AAAA, RecordType::A]).unwrap();
+		match nsec.rdata() {
+			RData::DNSSEC(DNSSECRData::NSEC(nsec)) => {
+				assert_eq!(
+					nsec.next_domain_name().to_ascii(),
+					"\\000.www.example.neatuser."
+				);
+				let mut types = nsec.type_bit_maps().to_vec();
+				types.sort();
+				assert_eq!(
+					types,
+					vec![
+						RecordType::A,
+						RecordType::AAAA,
+						RRSIG,
+						RecordType::DNSSEC(DNSSECRecordType::NSEC)
+					]
+				);
+			}
+			_ => panic!("denial is not an NSEC record"),
+		}
+	}
+}
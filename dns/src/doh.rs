@@ -0,0 +1,370 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// This file is part of Neatcoin.
+//
+// Copyright (c) 2021 Wei Tang.
+//
+// Neatcoin is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Neatcoin is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Neatcoin. If not, see <http://www.gnu.org/licenses/>.
+
+//! DNS over HTTPS (RFC 8484), and the JSON API of public resolvers.
+//!
+//! The endpoint speaks plain HTTP on `/dns-query`, and must sit behind a
+//! reverse proxy terminating TLS, like the RPC endpoints of the node:
+//! browsers only use DoH over HTTPS, and answers served in the clear can be
+//! read and altered on the way.
+//!
+//! Responses may be cached for the lowest TTL of their records, capped by a
+//! configured lifetime, as zones change with every finalized block while
+//! records are served with a fixed TTL.
+
+use hyper::{
+	body::HttpBody,
+	header::{
+		ACCESS_CONTROL_ALLOW_HEADERS, ACCESS_CONTROL_ALLOW_METHODS, ACCESS_CONTROL_ALLOW_ORIGIN,
+		CACHE_CONTROL, CONTENT_TYPE,
+	},
+	service::{make_service_fn, service_fn},
+	Body, Method, Request, Response, Server, StatusCode,
+};
+use serde_json::{json, Map, Value};
+use std::{collections::HashMap, convert::Infallible, net::SocketAddr, sync::Arc};
+use tokio::task::spawn_blocking;
+use trust_dns_proto::{
+	op::{Message, MessageType, OpCode, Query},
+	rr::{self, RData, Record, RecordType},
+};
+
+const PATH: &str = "/dns-query";
+const DNS_MESSAGE: &str = "application/dns-message";
+const DNS_JSON: &str = "application/dns-json";
+/// Largest DNS message accepted in a request body.
+const MAX_MESSAGE: usize = 65535;
+
+/// Answer to a standard query from finalized state.
+pub(crate) type Answer = Arc<dyn Fn(&Message) -> Message + Send + Sync>;
+
+type Failure = (StatusCode, &'static str);
+
+/// Serve DoH on `addr` until the node shuts down. Responses are cached for
+/// at most `cache_limit` seconds.
+pub(crate) async fn serve(addr: SocketAddr, cache_limit: u32, answer: Answer) {
+	let make_service = make_service_fn(move |_| {
+		let answer = answer.clone();
+		async move {
+			Ok::<_, Infallible>(service_fn(move |request| {
+				handle(answer.clone(), cache_limit, request)
+			}))
+		}
+	});
+
+	let server = match Server::try_bind(&addr) {
+		Ok(builder) => builder.serve(make_service),
+		Err(e) => {
+			log::error!(target: "dns", "Failed to listen for DNS over HTTPS on {}: {}", addr, e);
+			return
+		}
+	};
+	log::info!(target: "dns", "Serving Nomo zones over DNS over HTTPS on http://{}{}", addr, PATH);
+
+	if let Err(e) = server.await {
+		log::error!(target: "dns", "DNS over HTTPS server failed: {}", e);
+	}
+}
+
+async fn handle(
+	answer: Answer,
+	cache_limit: u32,
+	request: Request<Body>,
+) -> Result<Response<Body>, Infallible> {
+	Ok(respond(answer, cache_limit, request)
+		.await
+		.unwrap_or_else(|(status, message)| {
+			response(status, "text/plain", None)
+				.body(Body::from(message))
+				.expect(BUILDER)
+		}))
+}
+
+const BUILDER: &str = "status and headers are valid; qed";
+
+async fn respond(
+	answer: Answer,
+	cache_limit: u32,
+	request: Request<Body>,
+) -> Result<Response<Body>, Failure> {
+	if request.uri().path() != PATH {
+		return Err((StatusCode::NOT_FOUND, "not found"))
+	}
+	let params = request
+		.uri()
+		.query()
+		.map(|query| {
+			form_urlencoded::parse(query.as_bytes())
+				.into_owned()
+				.collect::<HashMap<_, _>>()
+		})
+		.unwrap_or_default();
+
+	match *request.method() {
+		Method::GET => {
+			if let Some(dns) = params.get("dns") {
+				let raw = base64::decode_config(dns.trim_end_matches('='), base64::URL_SAFE_NO_PAD)
+					.map_err(|_| (StatusCode::BAD_REQUEST, "invalid dns parameter"))?;
+				wire(answer, cache_limit, &raw).await
+			} else if let Some(name) = params.get("name") {
+				json_api(answer, cache_limit, name, params.get("type")).await
+			} else {
+				Err((StatusCode::BAD_REQUEST, "missing dns or name parameter"))
+			}
+		}
+		Method::POST => {
+			let content_type = request
+				.headers()
+				.get(CONTENT_TYPE)
+				.and_then(|value| value.to_str().ok())
+				.unwrap_or_default();
+			if !content_type.starts_with(DNS_MESSAGE) {
+				return Err((
+					StatusCode::UNSUPPORTED_MEDIA_TYPE,
+					"expected application/dns-message",
+				))
+			}
+
+			let mut body = request.into_body();
+			let mut raw = Vec::new();
+			while let Some(chunk) = body.data().await {
+				let chunk =
+					chunk.map_err(|_| (StatusCode::BAD_REQUEST, "failed to read the body"))?;
+				if raw.len() + chunk.len() > MAX_MESSAGE {
+					return Err((StatusCode::PAYLOAD_TOO_LARGE, "message too large"))
+				}
+				raw.extend_from_slice(&chunk);
+			}
+			wire(answer, cache_limit, &raw).await
+		}
+		Method::OPTIONS => Ok(response(StatusCode::NO_CONTENT, "text/plain", None)
+			.header(ACCESS_CONTROL_ALLOW_METHODS, "GET, POST, OPTIONS")
+			.header(ACCESS_CONTROL_ALLOW_HEADERS, "content-type, accept")
+			.body(Body::empty())
+			.expect(BUILDER)),
+		_ => Err((StatusCode::METHOD_NOT_ALLOWED, "method not allowed")),
+	}
+}
+
+/// Answer a query in DNS wire format.
+async fn wire(answer: Answer, cache_limit: u32, raw: &[u8]) -> Result<Response<Body>, Failure> {
+	let request = Message::from_vec(raw)
+		.ok()
+		.filter(|request| request.message_type() == MessageType::Query)
+		.ok_or((StatusCode::BAD_REQUEST, "invalid DNS query"))?;
+
+	let response = resolve(answer, request).await?;
+	let body = response.to_vec().map_err(|_| {
+		(
+			StatusCode::INTERNAL_SERVER_ERROR,
+			"failed to encode the response",
+		)
+	})?;
+
+	Ok(response_for(&response, DNS_MESSAGE, cache_limit)
+		.body(Body::from(body))
+		.expect(BUILDER))
+}
+
+/// Answer a query of the JSON API, given the name and the type as a number
+/// or mnemonic. The type defaults to A.
+async fn json_api(
+	answer: Answer,
+	cache_limit: u32,
+	name: &str,
+	rtype: Option<&String>,
+) -> Result<Response<Body>, Failure> {
+	let name = rr::Name::from_ascii(format!("{}.", name.trim_end_matches('.')))
+		.map_err(|_| (StatusCode::BAD_REQUEST, "invalid name"))?;
+	let rtype = match rtype {
+		Some(rtype) => rtype
+			.parse::<u16>()
+			.map(RecordType::from)
+			.or_else(|_| rtype.to_ascii_uppercase().parse::<RecordType>())
+			.map_err(|_| (StatusCode::BAD_REQUEST, "invalid type"))?,
+		None => RecordType::A,
+	};
+
+	let mut request = Message::new();
+	request
+		.set_message_type(MessageType::Query)
+		.set_op_code(OpCode::Query)
+		.set_recursion_desired(true)
+		.add_query(Query::query(name, rtype));
+
+	let response = resolve(answer, request).await?;
+	Ok(response_for(&response, DNS_JSON, cache_limit)
+		.body(Body::from(to_json(&response).to_string()))
+		.expect(BUILDER))
+}
+
+async fn resolve(answer: Answer, request: Message) -> Result<Message, Failure> {
+	spawn_blocking(move || answer(&request))
+		.await
+		.map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "resolver failed"))
+}
+
+fn response(
+	status: StatusCode,
+	content_type: &str,
+	max_age: Option<u32>,
+) -> hyper::http::response::Builder {
+	Response::builder()
+		.status(status)
+		.header(CONTENT_TYPE, content_type)
+		.header(ACCESS_CONTROL_ALLOW_ORIGIN, "*")
+		.header(
+			CACHE_CONTROL,
+			match max_age {
+				Some(max_age) => format!("max-age={}", max_age),
+				None => "no-cache".to_string(),
+			},
+		)
+}
+
+fn response_for(
+	message: &Message,
+	content_type: &str,
+	cache_limit: u32,
+) -> hyper::http::response::Builder {
+	response(
+		StatusCode::OK,
+		content_type,
+		max_age(message).map(|max_age| max_age.min(cache_limit)),
+	)
+}
+
+/// Freshness lifetime of a response: the lowest TTL of its answers or, for
+/// negative answers, the lower of the SOA TTL and minimum field (RFC 2308).
+pub fn max_age(response: &Message) -> Option<u32> {
+	if !response.answers().is_empty() {
+		return response.answers().iter().map(Record::ttl).min()
+	}
+
+	response
+		.name_servers()
+		.iter()
+		.find_map(|record| match record.rdata() {
+			RData::SOA(soa) => Some(record.ttl().min(soa.minimum())),
+			_ => None,
+		})
+}
+
+/// JSON API form of a response, as served by public DoH resolvers.
+pub fn to_json(response: &Message) -> Value {
+	let records = |records: &[Record]| {
+		records
+			.iter()
+			.map(|record| {
+				json!({
+					"name": record.name().to_ascii(),
+					"type": u16::from(record.record_type()),
+					"TTL": record.ttl(),
+					"data": record.rdata().to_string(),
+				})
+			})
+			.collect::<Vec<_>>()
+	};
+
+	let mut json = Map::new();
+	json.insert("Status".into(), json!(u16::from(response.response_code())));
+	json.insert("TC".into(), json!(response.truncated()));
+	json.insert("RD".into(), json!(response.recursion_desired()));
+	json.insert("RA".into(), json!(response.recursion_available()));
+	json.insert("AD".into(), json!(response.authentic_data()));
+	json.insert("CD".into(), json!(response.checking_disabled()));
+	json.insert(
+		"Question".into(),
+		response
+			.queries()
+			.iter()
+			.map(|query| {
+				json!({
+					"name": query.name().to_ascii(),
+					"type": u16::from(query.query_type()),
+				})
+			})
+			.collect(),
+	);
+	if !response.answers().is_empty() {
+		json.insert("Answer".into(), records(response.answers()).into());
+	}
+	if !response.name_servers().is_empty() {
+		json.insert("Authority".into(), records(response.name_servers()).into());
+	}
+
+	Value::Object(json)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::net::Ipv4Addr;
+	use trust_dns_proto::rr::rdata::SOA;
+
+	#[test]
+	fn json_and_freshness_follow_records() {
+		let name = rr::Name::from_ascii("www.example.neatuser.").unwrap();
+		let apex = rr::Name::from_ascii("example.neatuser.").unwrap();
+
+		let mut response = Message::new();
+		response
+			.set_message_type(MessageType::Response)
+			.add_query(Query::query(name.clone(), RecordType::A))
+			.add_answer(Record::from_rdata(
+				name.clone(),
+				3600,
+				RData::A(Ipv4Addr::new(10, 0, 0, 1)),
+			))
+			.add_answer(Record::from_rdata(
+				name,
+				600,
+				RData::A(Ipv4Addr::new(10, 0, 0, 2)),
+			));
+		assert_eq!(max_age(&response), Some(600));
+		let cached = response_for(&response, DNS_JSON, 60)
+			.body(Body::empty())
+			.unwrap();
+		assert_eq!(cached.headers()[CACHE_CONTROL], "max-age=60");
+		assert_eq!(
+			to_json(&response),
+			json!({
+				"Status": 0,
+				"TC": false,
+				"RD": false,
+				"RA": false,
+				"AD": false,
+				"CD": false,
+				"Question": [{ "name": "www.example.neatuser.", "type": 1 }],
+				"Answer": [
+					{ "name": "www.example.neatuser.", "type": 1, "TTL": 3600, "data": "10.0.0.1" },
+					{ "name": "www.example.neatuser.", "type": 1, "TTL": 600, "data": "10.0.0.2" },
+				],
+			})
+		);
+
+		let mut negative = Message::new();
+		negative.add_name_server(Record::from_rdata(
+			apex.clone(),
+			3600,
+			RData::SOA(SOA::new(apex.clone(), apex, 1, 3600, 900, 604800, 300)),
+		));
+		assert_eq!(max_age(&negative), Some(300));
+		assert_eq!(max_age(&Message::new()), None);
+	}
+}
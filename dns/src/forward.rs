@@ -0,0 +1,238 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// This file is part of Neatcoin.
+//
+// Copyright (c) 2021 Wei Tang.
+//
+// Neatcoin is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Neatcoin is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Neatcoin. If not, see <http://www.gnu.org/licenses/>.
+
+//! Forwarding of names in external namespaces to upstream resolvers.
+//!
+//! Root can flag names it owns as also resolved through ICANN, OpenNIC or
+//! Handshake. A query whose zone apex carries such a flag is forwarded to
+//! the upstream resolvers configured for that namespace, so that names
+//! owned on chain below a flagged name are still answered from chain. Names
+//! whose apex is not flagged, or has no upstreams configured, are answered
+//! from chain as usual.
+
+use crate::{resolver::response_to, Error};
+use neatcoin_nomo::Records;
+use std::{
+	io::{self, ErrorKind, Read, Write},
+	net::{Ipv4Addr, Ipv6Addr, SocketAddr, TcpStream, UdpSocket},
+	time::{Duration, Instant},
+};
+use trust_dns_proto::op::{Edns, Message, MessageType, OpCode};
+
+/// Time to wait for each upstream before trying the next one.
+const TIMEOUT: Duration = Duration::from_secs(2);
+/// Largest UDP response accepted from upstreams.
+const UDP_PAYLOAD: u16 = 1232;
+
+/// Upstream resolvers of the external namespaces.
+#[derive(Clone, Debug, Default)]
+pub struct Upstreams {
+	pub icann: Vec<SocketAddr>,
+	pub opennic: Vec<SocketAddr>,
+	pub handshake: Vec<SocketAddr>,
+}
+
+impl Upstreams {
+	/// Upstreams to forward queries to for a zone whose apex has `records`.
+	/// A name flagged in several namespaces goes to ICANN upstreams first,
+	/// then OpenNIC, then Handshake, skipping namespaces without upstreams.
+	pub fn of(&self, records: &Records) -> Option<&[SocketAddr]> {
+		[
+			(records.icann, &self.icann),
+			(records.opennic, &self.opennic),
+			(records.handshake, &self.handshake),
+		]
+		.iter()
+		.find(|(flagged, upstreams)| *flagged && !upstreams.is_empty())
+		.map(|(_, upstreams)| &upstreams[..])
+	}
+}
+
+/// Forward a query to each upstream in turn, returning the answer of the
+/// first that responds. Queries go over UDP, and over TCP when the answer
+/// is truncated. The answer carries the id of `request` and is not
+/// authoritative.
+pub fn forward(upstreams: &[SocketAddr], request: &Message) -> Result<Message, Error> {
+	let mut query = Message::new();
+	query
+		.set_id(rand::random())
+		.set_message_type(MessageType::Query)
+		.set_op_code(OpCode::Query)
+		.set_recursion_desired(true)
+		.add_queries(request.queries().to_vec());
+	let mut edns = Edns::new();
+	edns.set_max_payload(UDP_PAYLOAD);
+	edns.set_dnssec_ok(request.edns().map_or(false, Edns::dnssec_ok));
+	query.set_edns(edns);
+	let encoded = query.to_vec()?;
+
+	let mut last = Error::Io(io::Error::new(ErrorKind::NotFound, "no upstream resolver"));
+	for upstream in upstreams {
+		let answer = exchange_udp(*upstream, &encoded, &query).and_then(|answer| {
+			if answer.truncated() {
+				exchange_tcp(*upstream, &encoded, &query)
+			} else {
+				Ok(answer)
+			}
+		});
+		match answer {
+			Ok(answer) => {
+				let mut response = response_to(request);
+				response
+					.set_recursion_available(true)
+					.set_response_code(answer.response_code())
+					.add_queries(request.queries().to_vec())
+					.add_answers(answer.answers().to_vec())
+					.add_name_servers(answer.name_servers().to_vec());
+				for record in answer.additionals() {
+					response.add_additional(record.clone());
+				}
+				return Ok(response)
+			}
+			Err(e) => {
+				log::debug!(target: "dns", "Upstream {} failed: {}", upstream, e);
+				last = e;
+			}
+		}
+	}
+
+	Err(last)
+}
+
+fn exchange_udp(upstream: SocketAddr, encoded: &[u8], query: &Message) -> Result<Message, Error> {
+	let local: SocketAddr = if upstream.is_ipv4() {
+		(Ipv4Addr::UNSPECIFIED, 0).into()
+	} else {
+		(Ipv6Addr::UNSPECIFIED, 0).into()
+	};
+	let socket = UdpSocket::bind(local)?;
+	socket.connect(upstream)?;
+	socket.send(encoded)?;
+
+	// Stray datagrams must not extend the wait for the answer.
+	let deadline = Instant::now() + TIMEOUT;
+	let mut buf = vec![0u8; UDP_PAYLOAD as usize];
+	loop {
+		let remaining = deadline.saturating_duration_since(Instant::now());
+		if remaining == Duration::ZERO {
+			return Err(io::Error::new(ErrorKind::TimedOut, "upstream timed out").into())
+		}
+		socket.set_read_timeout(Some(remaining))?;
+
+		let len = socket.recv(&mut buf)?;
+		match Message::from_vec(&buf[..len]) {
+			Ok(answer) if is_answer(&answer, query) => return Ok(answer),
+			_ => continue,
+		}
+	}
+}
+
+fn exchange_tcp(upstream: SocketAddr, encoded: &[u8], query: &Message) -> Result<Message, Error> {
+	let mut stream = TcpStream::connect_timeout(&upstream, TIMEOUT)?;
+	stream.set_read_timeout(Some(TIMEOUT))?;
+	stream.write_all(&(encoded.len() as u16).to_be_bytes())?;
+	stream.write_all(encoded)?;
+
+	let mut len = [0u8; 2];
+	stream.read_exact(&mut len)?;
+	let mut buf = vec![0u8; u16::from_be_bytes(len) as usize];
+	stream.read_exact(&mut buf)?;
+
+	let answer = Message::from_vec(&buf)?;
+	if !is_answer(&answer, query) {
+		return Err(io::Error::new(ErrorKind::InvalidData, "mismatched answer").into())
+	}
+	Ok(answer)
+}
+
+fn is_answer(answer: &Message, query: &Message) -> bool {
+	answer.id() == query.id()
+		&& answer.message_type() == MessageType::Response
+		&& answer.queries() == query.queries()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::{net::Ipv4Addr, str::FromStr, thread};
+	use trust_dns_proto::{
+		op::{Query, ResponseCode},
+		rr::{Name, RData, Record, RecordType},
+	};
+
+	#[test]
+	fn forwards_to_upstream_of_flagged_namespace() {
+		let upstreams = Upstreams {
+			opennic: vec!["127.0.0.1:1".parse().unwrap()],
+			..Default::default()
+		};
+		let flagged = Records {
+			icann: true,
+			opennic: true,
+			..Default::default()
+		};
+		assert_eq!(upstreams.of(&flagged), Some(&upstreams.opennic[..]));
+		assert_eq!(
+			upstreams.of(&Records {
+				icann: true,
+				..Default::default()
+			}),
+			None
+		);
+
+		let mock = UdpSocket::bind("127.0.0.1:0").unwrap();
+		let upstream = mock.local_addr().unwrap();
+		let server = thread::spawn(move || {
+			let mut buf = [0u8; 512];
+			let (len, peer) = mock.recv_from(&mut buf).unwrap();
+			let query = Message::from_vec(&buf[..len]).unwrap();
+
+			let mut answer = Message::new();
+			answer
+				.set_id(query.id())
+				.set_message_type(MessageType::Response)
+				.set_authoritative(true)
+				.add_queries(query.queries().to_vec())
+				.add_answer(Record::from_rdata(
+					query.queries()[0].name().clone(),
+					300,
+					RData::A(Ipv4Addr::new(192, 0, 2, 1)),
+				));
+			mock.send_to(&answer.to_vec().unwrap(), peer).unwrap();
+			query
+		});
+
+		let mut request = Message::new();
+		request.set_id(7).add_query(Query::query(
+			Name::from_str("example.com.").unwrap(),
+			RecordType::A,
+		));
+		let response = forward(&["127.0.0.1:1".parse().unwrap(), upstream], &request).unwrap();
+		let query = server.join().unwrap();
+
+		assert!(query.recursion_desired());
+		assert_eq!(response.id(), 7);
+		assert_eq!(response.response_code(), ResponseCode::NoError);
+		assert!(!response.authoritative());
+		assert!(response.recursion_available());
+		assert_eq!(
+			response.answers()[0].rdata(),
+			&RData::A(Ipv4Addr::new(192, 0, 2, 1))
+		);
+	}
+}
@@ -0,0 +1,107 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// This file is part of Neatcoin.
+//
+// Copyright (c) 2021 Wei Tang.
+//
+// Neatcoin is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Neatcoin is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Neatcoin. If not, see <http://www.gnu.org/licenses/>.
+
+//! Authoritative DNS frontend serving Nomo zones from finalized chain state.
+//!
+//! Standard queries are answered over UDP and TCP from the zone of the
+//! nearest owned name. Secondaries on the transfer allow-list can copy zones
+//! with AXFR, or with IXFR from the changelog kept of finalized blocks, and
+//! are sent NOTIFY when a finalized block changes a zone. The same answers
+//! are served over HTTP, as DNS over HTTPS and its JSON API. Answers from
+//! zones with a DNSSEC mode are signed for queries setting the DO bit.
+//! Names root flagged as ICANN, OpenNIC or Handshake are forwarded to the
+//! upstream resolvers of that namespace.
+
+pub mod changelog;
+pub mod dnssec;
+pub mod doh;
+pub mod forward;
+mod notify;
+pub mod resolver;
+mod server;
+pub mod transfer;
+
+pub use crate::{forward::Upstreams, server::run};
+pub use ipnet::IpNet;
+
+use np_domain::Name;
+use std::{
+	net::{IpAddr, SocketAddr},
+	path::PathBuf,
+};
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+	#[error(transparent)]
+	Io(#[from] std::io::Error),
+
+	#[error(transparent)]
+	Proto(#[from] trust_dns_proto::error::ProtoError),
+
+	#[error(transparent)]
+	Nomo(#[from] neatcoin_nomo::Error),
+
+	#[error(transparent)]
+	Blockchain(#[from] sp_blockchain::Error),
+
+	#[error("invalid changelog: {0}")]
+	Changelog(codec::Error),
+
+	#[error("invalid events at block #{0}: {1}")]
+	Events(np_opaque::BlockNumber, codec::Error),
+
+	#[error("keystore error: {0}")]
+	Keystore(String),
+
+	#[error("block #{0} not found")]
+	UnknownBlock(np_opaque::BlockNumber),
+}
+
+/// Configuration of the DNS frontend.
+#[derive(Clone, Debug)]
+pub struct Config {
+	/// Address to serve DNS on, over both UDP and TCP.
+	pub listen: Option<SocketAddr>,
+	/// Address to serve DNS over HTTPS on, behind TLS termination.
+	pub doh: Option<SocketAddr>,
+	/// Longest time in seconds DNS over HTTPS responses may be cached for.
+	pub doh_cache_limit: u32,
+	/// Networks allowed to request zone transfers.
+	pub allow_transfer: Vec<IpNet>,
+	/// Secondaries sent NOTIFY when a finalized block changes their zones.
+	pub notify: Vec<SocketAddr>,
+	/// Resolvers answering names in external namespaces.
+	pub upstreams: Upstreams,
+	/// Directory of the changelog serving IXFR. Without it, the changelog is
+	/// kept in memory and starts over on restart.
+	pub changelog: Option<PathBuf>,
+}
+
+impl Config {
+	/// Whether a peer may request zone transfers.
+	pub fn may_transfer(&self, peer: IpAddr) -> bool {
+		self.allow_transfer.iter().any(|net| net.contains(&peer))
+	}
+}
+
+/// Zone changes of a block, decoded from the events of a runtime.
+pub trait ZoneEvents: Send + Sync + 'static {
+	/// Names whose zone records were set, from the raw `System::Events`
+	/// storage value of a block.
+	fn changed_names(events: &[u8]) -> Result<Vec<Name>, codec::Error>;
+}
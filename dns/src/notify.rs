@@ -0,0 +1,88 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// This file is part of Neatcoin.
+//
+// Copyright (c) 2021 Wei Tang.
+//
+// Neatcoin is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Neatcoin is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Neatcoin. If not, see <http://www.gnu.org/licenses/>.
+
+//! DNS NOTIFY, telling secondaries that a zone changed.
+
+use futures::future::join_all;
+use std::{
+	net::{Ipv4Addr, Ipv6Addr, SocketAddr},
+	sync::atomic::{AtomicU16, Ordering},
+	time::Duration,
+};
+use tokio::{net::UdpSocket, time::timeout};
+use trust_dns_proto::{
+	op::{Message, MessageType, OpCode, Query},
+	rr::{Record, RecordType},
+};
+
+/// Attempts per secondary before giving up. Secondaries still pick up the
+/// change at the SOA refresh interval.
+const ATTEMPTS: usize = 3;
+const RETRY: Duration = Duration::from_secs(2);
+
+static NEXT_ID: AtomicU16 = AtomicU16::new(0);
+
+/// Notify every target of the zones with the given SOA records, retrying
+/// until each target acknowledges.
+pub async fn notify(targets: &[SocketAddr], zones: &[Record]) {
+	join_all(zones.iter().flat_map(|soa| {
+		targets.iter().map(move |target| async move {
+			if let Err(e) = notify_one(*target, soa).await {
+				log::debug!(target: "dns", "NOTIFY of {} to {} failed: {}", soa.name(), target, e);
+			}
+		})
+	}))
+	.await;
+}
+
+async fn notify_one(target: SocketAddr, soa: &Record) -> Result<(), crate::Error> {
+	let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+	let mut message = Message::new();
+	message
+		.set_id(id)
+		.set_message_type(MessageType::Query)
+		.set_op_code(OpCode::Notify)
+		.set_authoritative(true)
+		.add_query(Query::query(soa.name().clone(), RecordType::SOA))
+		.add_answer(soa.clone());
+	let request = message.to_vec()?;
+
+	let local: SocketAddr = if target.is_ipv4() {
+		(Ipv4Addr::UNSPECIFIED, 0).into()
+	} else {
+		(Ipv6Addr::UNSPECIFIED, 0).into()
+	};
+	let socket = UdpSocket::bind(local).await?;
+	socket.connect(target).await?;
+
+	let mut buf = [0u8; 512];
+	for _ in 0..ATTEMPTS {
+		socket.send(&request).await?;
+		while let Ok(len) = timeout(RETRY, socket.recv(&mut buf)).await {
+			match Message::from_vec(&buf[..len?]) {
+				Ok(response) if response.id() == id => {
+					log::debug!(target: "dns", "{} acknowledged NOTIFY of {}", target, soa.name());
+					return Ok(())
+				}
+				_ => continue,
+			}
+		}
+	}
+
+	Err(std::io::Error::new(std::io::ErrorKind::TimedOut, "no acknowledgement").into())
+}
@@ -0,0 +1,353 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// This file is part of Neatcoin.
+//
+// Copyright (c) 2021 Wei Tang.
+//
+// Neatcoin is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Neatcoin is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Neatcoin. If not, see <http://www.gnu.org/licenses/>.
+
+//! Answers to standard queries from Nomo zone state.
+
+use crate::{
+	dnssec::{self, Signer, RRSIG},
+	forward::{forward, Upstreams},
+	Error,
+};
+use neatcoin_nomo::{
+	is_under,
+	zonefile::{absolute, hostmaster, DEFAULT_TTL, SOA_TIMERS},
+	NomoReader, Records, StorageRead,
+};
+use np_domain::Name;
+use np_opaque::BlockNumber;
+use sp_keystore::SyncCryptoStorePtr;
+use trust_dns_proto::{
+	op::{Edns, Message, MessageType, OpCode, ResponseCode},
+	rr::{
+		self,
+		dnssec::rdata::{DNSSECRData, DNSSECRecordType},
+		rdata::{MX, SOA},
+		DNSClass, RData, Record, RecordType,
+	},
+};
+
+const DNSKEY: RecordType = RecordType::DNSSEC(DNSSECRecordType::DNSKEY);
+const DS: RecordType = RecordType::DNSSEC(DNSSECRecordType::DS);
+
+/// Maximum UDP payload advertised in responses to EDNS queries.
+pub const EDNS_PAYLOAD: u16 = 1232;
+
+/// Nomo name of a DNS name. Returns `None` for names with labels Nomo does
+/// not accept.
+pub fn from_dns(name: &rr::Name) -> Option<Name> {
+	Name::from_dotted(name.to_lowercase().to_ascii().as_bytes())
+}
+
+/// Absolute DNS name of a Nomo name.
+pub fn to_dns(name: &Name) -> Result<rr::Name, Error> {
+	Ok(rr::Name::from_ascii(absolute(name))?)
+}
+
+/// Apex of the zone serving a name, which is the nearest owned name at or
+/// above it.
+pub fn apex<S: StorageRead>(reader: &NomoReader<S>, name: &Name) -> Result<Option<Name>, Error> {
+	Ok(reader.effective_owner(name)?.map(|(apex, _)| apex))
+}
+
+/// SOA record of the zone of `apex`, with the block number as serial. The
+/// primary server is the first name server of the apex, or the apex itself.
+pub fn soa_record<S: StorageRead>(
+	reader: &NomoReader<S>,
+	apex: &Name,
+	serial: BlockNumber,
+) -> Result<Record, Error> {
+	let records = reader.records(apex)?;
+	let primary = match records.ns.first() {
+		Some(server) => to_dns(server)?,
+		None => to_dns(apex)?,
+	};
+	let (refresh, retry, expire, minimum) = SOA_TIMERS;
+
+	Ok(Record::from_rdata(
+		to_dns(apex)?,
+		DEFAULT_TTL,
+		RData::SOA(SOA::new(
+			primary,
+			rr::Name::from_ascii(hostmaster(apex))?,
+			serial,
+			refresh as i32,
+			retry as i32,
+			expire as i32,
+			minimum,
+		)),
+	))
+}
+
+/// DNS records of a name of the given type. `RecordType::ANY` converts all
+/// of them.
+pub fn to_records(name: &Name, records: &Records, rtype: RecordType) -> Result<Vec<Record>, Error> {
+	let owner = to_dns(name)?;
+	let wanted = |other: RecordType| rtype == other || rtype == RecordType::ANY;
+	let mut out = Vec::new();
+	let mut push = |rdata: RData| out.push(Record::from_rdata(owner.clone(), DEFAULT_TTL, rdata));
+
+	if wanted(RecordType::NS) {
+		for server in &records.ns {
+			push(RData::NS(to_dns(server)?));
+		}
+	}
+	if wanted(RecordType::A) {
+		for addr in &records.a {
+			push(RData::A(*addr));
+		}
+	}
+	if wanted(RecordType::AAAA) {
+		for addr in &records.aaaa {
+			push(RData::AAAA(*addr));
+		}
+	}
+	if wanted(RecordType::CNAME) {
+		if let Some(target) = &records.cname {
+			push(RData::CNAME(to_dns(target)?));
+		}
+	}
+	if wanted(RecordType::MX) {
+		if let Some((preference, exchange)) = &records.mx {
+			push(RData::MX(MX::new(*preference, to_dns(exchange)?)));
+		}
+	}
+	if wanted(DNSKEY) {
+		for key in &records.dnskey {
+			push(RData::DNSSEC(DNSSECRData::DNSKEY(dnssec::dnskey(key))));
+		}
+	}
+	if wanted(DS) {
+		for ds in records.ds.iter().filter_map(dnssec::ds) {
+			push(RData::DNSSEC(DNSSECRData::DS(ds)));
+		}
+	}
+	if wanted(RRSIG) {
+		out.extend(
+			records
+				.rrsig
+				.iter()
+				.filter_map(|raw| dnssec::rrsig(&owner, raw)),
+		);
+	}
+
+	Ok(out)
+}
+
+/// DNS records of a name of the given type, as served from the zone of
+/// `apex`. The DS records of the apex are left out, as they belong to the
+/// zone above it.
+pub fn zone_records(
+	apex: &Name,
+	name: &Name,
+	records: &Records,
+	rtype: RecordType,
+) -> Result<Vec<Record>, Error> {
+	let mut out = to_records(name, records, rtype)?;
+	if name == apex {
+		out.retain(|record| record.record_type() != DS);
+	}
+	Ok(out)
+}
+
+/// Whether `name` with `records` exists in its zone: it has records or an
+/// owner, or owned names are indexed under it.
+fn exists<S: StorageRead>(
+	reader: &NomoReader<S>,
+	name: &Name,
+	records: &Records,
+) -> Result<bool, Error> {
+	Ok(!records.is_empty() || reader.owner(name)?.is_some() || reader.has_children(name)?)
+}
+
+/// Empty response to a request, carrying its id and opcode. Requests with
+/// EDNS get EDNS back, with the same DO bit.
+pub fn response_to(request: &Message) -> Message {
+	let mut response = Message::new();
+	response
+		.set_id(request.id())
+		.set_message_type(MessageType::Response)
+		.set_op_code(request.op_code())
+		.set_recursion_desired(request.recursion_desired());
+	if let Some(request) = request.edns() {
+		let mut edns = Edns::new();
+		edns.set_max_payload(EDNS_PAYLOAD);
+		edns.set_dnssec_ok(request.dnssec_ok());
+		response.set_edns(edns);
+	}
+	response
+}
+
+/// Answer a standard query from state at block `serial`. Zone transfer
+/// requests are refused, as they are served by the `transfer` module.
+///
+/// Names under no owned name are refused. Names under a name server record
+/// below the apex get a referral, with glue for servers under the cut.
+/// Names with no records and no registry entry get NXDOMAIN, unless owned
+/// names are indexed under them as empty non-terminals. DS records of an
+/// apex are answered from the zone above it.
+///
+/// Names whose apex is flagged as resolved through an external namespace
+/// with configured `upstreams` are forwarded there instead.
+///
+/// If the query sets the DO bit and the zone has a DNSSEC mode, RRsets are
+/// signed with the zone keys in `keystore` or with the stored signatures,
+/// and referrals and empty answers carry DS or NSEC records. Missing names
+/// in zones signed from the keystore get an empty answer instead of
+/// NXDOMAIN, as their NSEC record only denies the types at the name.
+pub fn resolve<S: StorageRead>(
+	reader: &NomoReader<S>,
+	serial: BlockNumber,
+	keystore: Option<&SyncCryptoStorePtr>,
+	upstreams: &Upstreams,
+	request: &Message,
+) -> Result<Message, Error> {
+	let mut response = response_to(request);
+	if request.op_code() != OpCode::Query {
+		response.set_response_code(ResponseCode::NotImp);
+		return Ok(response)
+	}
+	let query = match request.queries() {
+		[query] => query.clone(),
+		_ => {
+			response.set_response_code(ResponseCode::FormErr);
+			return Ok(response)
+		}
+	};
+	response.add_query(query.clone());
+
+	let qtype = query.query_type();
+	if matches!(qtype, RecordType::AXFR | RecordType::IXFR) {
+		response.set_response_code(ResponseCode::Refused);
+		return Ok(response)
+	}
+	let name = from_dns(query.name());
+	let apex = match &name {
+		Some(name) if matches!(query.query_class(), DNSClass::IN | DNSClass::ANY) => {
+			match apex(reader, name)? {
+				Some(apex) if qtype == DS && &apex == name => match name.parent() {
+					Some(parent) => self::apex(reader, &parent)?,
+					None => None,
+				},
+				apex => apex,
+			}
+		}
+		_ => None,
+	};
+	let (name, apex) = match (name, apex) {
+		(Some(name), Some(apex)) => (name, apex),
+		_ => {
+			response.set_response_code(ResponseCode::Refused);
+			return Ok(response)
+		}
+	};
+
+	let apex_records = reader.records(&apex)?;
+	if let Some(upstreams) = upstreams.of(&apex_records) {
+		return forward(upstreams, request)
+	}
+
+	let signer = match request.edns() {
+		Some(edns) if edns.dnssec_ok() => Signer::new(to_dns(&apex)?, &apex_records, keystore)?,
+		_ => None,
+	};
+	let sign = |records: &[Record]| match &signer {
+		Some(signer) => signer.sign(reader, records),
+		None => Ok(Vec::new()),
+	};
+
+	// DS records of a cut are served from above it.
+	let last = if qtype == DS {
+		name.0.len().saturating_sub(1)
+	} else {
+		name.0.len()
+	};
+	for depth in apex.0.len() + 1..=last {
+		let cut = Name(name.0[..depth].to_vec());
+		let records = reader.records(&cut)?;
+		if records.ns.is_empty() {
+			continue
+		}
+
+		response.add_name_servers(to_records(&cut, &records, RecordType::NS)?);
+		let mut ds = to_records(&cut, &records, DS)?;
+		match &signer {
+			Some(signer) if ds.is_empty() && signer.signs_denials() => {
+				ds.push(dnssec::denial(&to_dns(&cut)?, vec![RecordType::NS])?)
+			}
+			Some(_) => (),
+			None => ds.clear(),
+		}
+		let signatures = sign(&ds)?;
+		response.add_name_servers(ds).add_name_servers(signatures);
+
+		for server in records.ns.iter().filter(|server| is_under(server, &cut)) {
+			let glue = reader.records(server)?;
+			for record in to_records(server, &glue, RecordType::A)?
+				.into_iter()
+				.chain(to_records(server, &glue, RecordType::AAAA)?)
+			{
+				response.add_additional(record);
+			}
+		}
+		return Ok(response)
+	}
+
+	response.set_authoritative(true);
+	let records = reader.records(&name)?;
+	let soa = soa_record(reader, &apex, serial)?;
+
+	let mut answers = match (&records.cname, qtype) {
+		(Some(_), RecordType::CNAME) | (None, _) => zone_records(&apex, &name, &records, qtype)?,
+		(Some(_), _) => to_records(&name, &records, RecordType::CNAME)?,
+	};
+	if name == apex && matches!(qtype, RecordType::SOA | RecordType::ANY) {
+		answers.insert(0, soa.clone());
+	}
+
+	if answers.is_empty() {
+		let signs_denials = signer.as_ref().map_or(false, Signer::signs_denials);
+		if !signs_denials && name != apex && !exists(reader, &name, &records)? {
+			response.set_response_code(ResponseCode::NXDomain);
+		}
+
+		let mut authority = vec![soa];
+		if signs_denials {
+			let mut types = zone_records(&apex, &name, &records, RecordType::ANY)?
+				.iter()
+				.map(Record::record_type)
+				.collect::<Vec<_>>();
+			if name == apex {
+				types.push(RecordType::SOA);
+			}
+			authority.push(dnssec::denial(&to_dns(&name)?, types)?);
+		}
+		let signatures = sign(&authority)?;
+		response
+			.add_name_servers(authority)
+			.add_name_servers(signatures);
+	} else {
+		let signatures = sign(&answers)?;
+		for signature in signatures {
+			if !answers.contains(&signature) {
+				answers.push(signature);
+			}
+		}
+		response.add_answers(answers);
+	}
+	Ok(response)
+}
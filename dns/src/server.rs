@@ -0,0 +1,506 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// This file is part of Neatcoin.
+//
+// Copyright (c) 2021 Wei Tang.
+//
+// Neatcoin is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Neatcoin is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Neatcoin. If not, see <http://www.gnu.org/licenses/>.
+
+//! UDP and TCP listeners, and the task following finalized blocks.
+
+use crate::{
+	changelog::{Change, Changelog, Snapshot},
+	doh,
+	notify::notify,
+	resolver::{self, from_dns, response_to, soa_record, EDNS_PAYLOAD},
+	transfer, Config, Error, ZoneEvents,
+};
+use futures::StreamExt;
+use neatcoin_nomo::{name_order, ClientStorage, NomoReader, StorageRead};
+use np_opaque::{Block, BlockNumber, Hash};
+use parking_lot::RwLock;
+use sc_client_api::{Backend, BlockchainEvents, StorageProvider};
+use sp_blockchain::HeaderBackend;
+use sp_core::hashing::twox_128;
+use sp_keystore::SyncCryptoStorePtr;
+use sp_runtime::{generic::BlockId, traits::Header};
+use sp_storage::StorageKey;
+use std::{
+	collections::BTreeMap,
+	io::{self, ErrorKind},
+	marker::PhantomData,
+	net::SocketAddr,
+	sync::Arc,
+	time::Duration,
+};
+use tokio::{
+	io::{AsyncReadExt, AsyncWriteExt},
+	net::{TcpListener, TcpStream, UdpSocket},
+	task::spawn_blocking,
+	time::timeout,
+};
+use trust_dns_proto::{
+	op::{Message, MessageType, OpCode, ResponseCode},
+	rr::{RData, Record, RecordType},
+};
+
+/// Records per message of a zone transfer.
+const TRANSFER_CHUNK: usize = 100;
+/// Largest UDP response to requests without EDNS.
+const UDP_PAYLOAD: usize = 512;
+/// Idle time after which a TCP connection is closed.
+const TCP_IDLE: Duration = Duration::from_secs(30);
+
+/// Serve DNS, and DNS over HTTPS, on the configured addresses until the
+/// node shuts down. Events of finalized blocks are decoded with `E` to keep
+/// the changelog. Zones in keystore DNSSEC mode are signed with the keys in
+/// `keystore`.
+pub async fn run<E, C, B>(client: Arc<C>, keystore: Option<SyncCryptoStorePtr>, config: Config)
+where
+	E: ZoneEvents,
+	C: StorageProvider<Block, B> + HeaderBackend<Block> + BlockchainEvents<Block>,
+	C: Send + Sync + 'static,
+	B: Backend<Block> + 'static,
+{
+	let finalized = client.info().finalized_number;
+	let dir = config.listen.and(config.changelog.as_deref());
+	let changelog = match Changelog::open(dir, finalized) {
+		Ok(changelog) => changelog,
+		Err(e) => {
+			log::error!(target: "dns", "Failed to open the DNS changelog: {}", e);
+			return
+		}
+	};
+
+	let server = Arc::new(Server::<C, B> {
+		client,
+		keystore,
+		config,
+		changelog: RwLock::new(changelog),
+		_marker: PhantomData,
+	});
+	let doh = server.config.doh.map(|addr| {
+		let server = server.clone();
+		doh::serve(
+			addr,
+			server.config.doh_cache_limit,
+			Arc::new(move |request: &Message| server.answer(request)),
+		)
+	});
+
+	futures::join!(server.serve_dns::<E>(), async {
+		if let Some(doh) = doh {
+			doh.await
+		}
+	});
+}
+
+struct Server<C, B> {
+	client: Arc<C>,
+	keystore: Option<SyncCryptoStorePtr>,
+	config: Config,
+	changelog: RwLock<Changelog>,
+	_marker: PhantomData<fn() -> B>,
+}
+
+impl<C, B> Server<C, B>
+where
+	C: StorageProvider<Block, B> + HeaderBackend<Block> + BlockchainEvents<Block>,
+	C: Send + Sync + 'static,
+	B: Backend<Block> + 'static,
+{
+	fn reader(&self, hash: Hash) -> NomoReader<ClientStorage<'_, C, B>> {
+		NomoReader::new(ClientStorage::new(&*self.client, BlockId::Hash(hash)))
+	}
+
+	fn block_hash(&self, number: BlockNumber) -> Result<Hash, Error> {
+		self.client.hash(number)?.ok_or(Error::UnknownBlock(number))
+	}
+
+	async fn serve_dns<E: ZoneEvents>(self: Arc<Self>) {
+		let listen = match self.config.listen {
+			Some(listen) => listen,
+			None => return,
+		};
+		let (udp, tcp) =
+			match futures::try_join!(UdpSocket::bind(listen), TcpListener::bind(listen)) {
+				Ok(sockets) => sockets,
+				Err(e) => {
+					log::error!(target: "dns", "Failed to listen for DNS on {}: {}", listen, e);
+					return
+				}
+			};
+		log::info!(target: "dns", "Serving Nomo zones over DNS on {}", listen);
+
+		futures::join!(
+			self.clone().serve_udp(udp),
+			self.clone().serve_tcp(tcp),
+			self.follow_finality::<E>(),
+		);
+	}
+
+	async fn serve_udp(self: Arc<Self>, socket: UdpSocket) {
+		let socket = Arc::new(socket);
+		let mut buf = vec![0u8; u16::MAX as usize];
+		loop {
+			let (len, peer) = match socket.recv_from(&mut buf).await {
+				Ok(received) => received,
+				Err(e) => {
+					log::debug!(target: "dns", "Failed to receive over UDP: {}", e);
+					continue
+				}
+			};
+			let request = buf[..len].to_vec();
+			let (server, socket) = (self.clone(), socket.clone());
+
+			tokio::spawn(async move {
+				let responses = spawn_blocking(move || server.handle(&request, peer, false))
+					.await
+					.unwrap_or_default();
+				for response in responses {
+					let _ = socket.send_to(&response, peer).await;
+				}
+			});
+		}
+	}
+
+	async fn serve_tcp(self: Arc<Self>, listener: TcpListener) {
+		loop {
+			let (stream, peer) = match listener.accept().await {
+				Ok(accepted) => accepted,
+				Err(e) => {
+					log::debug!(target: "dns", "Failed to accept over TCP: {}", e);
+					continue
+				}
+			};
+			let server = self.clone();
+
+			tokio::spawn(async move {
+				if let Err(e) = server.serve_connection(stream, peer).await {
+					log::debug!(target: "dns", "TCP connection from {} failed: {}", peer, e);
+				}
+			});
+		}
+	}
+
+	async fn serve_connection(
+		self: Arc<Self>,
+		mut stream: TcpStream,
+		peer: SocketAddr,
+	) -> io::Result<()> {
+		loop {
+			let len = match timeout(TCP_IDLE, stream.read_u16()).await {
+				Ok(Ok(len)) => len,
+				Ok(Err(e)) if e.kind() == ErrorKind::UnexpectedEof => return Ok(()),
+				Ok(Err(e)) => return Err(e),
+				Err(_) => return Ok(()),
+			};
+			let mut request = vec![0u8; len as usize];
+			stream.read_exact(&mut request).await?;
+
+			let server = self.clone();
+			let responses = spawn_blocking(move || server.handle(&request, peer, true))
+				.await
+				.map_err(|e| io::Error::new(ErrorKind::Other, e))?;
+			for response in responses {
+				stream.write_u16(response.len() as u16).await?;
+				stream.write_all(&response).await?;
+			}
+		}
+	}
+
+	/// Encoded responses to a raw request. Anything but a query is dropped.
+	/// UDP responses larger than the client payload size, or than the EDNS
+	/// payload size advertised to it, are sent truncated.
+	fn handle(&self, request: &[u8], peer: SocketAddr, tcp: bool) -> Vec<Vec<u8>> {
+		let request = match Message::from_vec(request) {
+			Ok(request) if request.message_type() == MessageType::Query => request,
+			_ => return Vec::new(),
+		};
+
+		let responses = self
+			.respond(&request, peer, tcp)
+			.unwrap_or_else(|e| vec![server_failure(&request, e)]);
+
+		let limit = match (tcp, request.edns()) {
+			(true, _) => u16::MAX as usize,
+			(false, Some(edns)) => {
+				(edns.max_payload() as usize).clamp(UDP_PAYLOAD, EDNS_PAYLOAD as usize)
+			}
+			(false, None) => UDP_PAYLOAD,
+		};
+		responses
+			.into_iter()
+			.filter_map(|response| {
+				let encoded = match response.to_vec() {
+					Ok(encoded) if encoded.len() <= limit => Ok(encoded),
+					Ok(_) => {
+						let mut truncated = response_to(&request);
+						truncated
+							.add_queries(request.queries().to_vec())
+							.set_authoritative(response.authoritative())
+							.set_truncated(true);
+						truncated.to_vec()
+					}
+					Err(e) => Err(e),
+				};
+				encoded
+					.map_err(|e| log::warn!(target: "dns", "Failed to encode a response: {}", e))
+					.ok()
+			})
+			.collect()
+	}
+
+	/// Answer to a standard query from finalized state.
+	fn answer(&self, request: &Message) -> Message {
+		let info = self.client.info();
+		resolver::resolve(
+			&self.reader(info.finalized_hash),
+			info.finalized_number,
+			self.keystore.as_ref(),
+			&self.config.upstreams,
+			request,
+		)
+		.unwrap_or_else(|e| server_failure(request, e))
+	}
+
+	fn respond(
+		&self,
+		request: &Message,
+		peer: SocketAddr,
+		tcp: bool,
+	) -> Result<Vec<Message>, Error> {
+		let info = self.client.info();
+		let reader = self.reader(info.finalized_hash);
+
+		match request.queries().first().map(|query| query.query_type()) {
+			Some(RecordType::AXFR | RecordType::IXFR) if request.op_code() == OpCode::Query => {
+				self.transfer(&reader, info.finalized_number, request, peer, tcp)
+			}
+			_ => Ok(vec![resolver::resolve(
+				&reader,
+				info.finalized_number,
+				self.keystore.as_ref(),
+				&self.config.upstreams,
+				request,
+			)?]),
+		}
+	}
+
+	/// Responses to an AXFR or IXFR request. Transfers are refused to peers
+	/// outside the allow-list, and for names that are not owned. AXFR is only
+	/// served over TCP. IXFR over UDP only tells the client whether it is up
+	/// to date, and falls back to a full transfer when the changelog does not
+	/// reach back to the client serial.
+	fn transfer<S: StorageRead>(
+		&self,
+		reader: &NomoReader<S>,
+		serial: BlockNumber,
+		request: &Message,
+		peer: SocketAddr,
+		tcp: bool,
+	) -> Result<Vec<Message>, Error> {
+		let query = &request.queries()[0];
+		let mut response = response_to(request);
+		response.add_query(query.clone());
+
+		let apex = match from_dns(query.name()) {
+			Some(apex) if reader.owner(&apex)?.is_some() => Some(apex),
+			_ => None,
+		};
+		let apex = match apex {
+			Some(apex) if request.queries().len() == 1 && self.config.may_transfer(peer.ip()) => {
+				apex
+			}
+			_ => {
+				response.set_response_code(ResponseCode::Refused);
+				return Ok(vec![response])
+			}
+		};
+		response.set_authoritative(true);
+		let soa = soa_record(reader, &apex, serial)?;
+
+		let records = match query.query_type() {
+			RecordType::IXFR => {
+				let from = request
+					.name_servers()
+					.iter()
+					.find_map(|record| match record.rdata() {
+						RData::SOA(soa) => Some(soa.serial()),
+						_ => None,
+					});
+				let from = match from {
+					Some(from) => from,
+					None => {
+						response.set_response_code(ResponseCode::FormErr);
+						return Ok(vec![response])
+					}
+				};
+
+				if from >= serial || !tcp {
+					vec![soa]
+				} else {
+					let incremental = {
+						let changelog = self.changelog.read();
+						match changelog.since(from) {
+							Some(changes) if changelog.processed() >= serial => {
+								Some(transfer::ixfr(reader, &soa, &apex, from, changes)?)
+							}
+							_ => None,
+						}
+					};
+					match incremental {
+						Some(records) => records,
+						None => transfer::axfr(reader, &apex, serial)?,
+					}
+				}
+			}
+			_ if tcp => transfer::axfr(reader, &apex, serial)?,
+			_ => {
+				response.set_response_code(ResponseCode::Refused);
+				return Ok(vec![response])
+			}
+		};
+
+		Ok(records
+			.chunks(TRANSFER_CHUNK)
+			.map(|chunk| {
+				let mut message = response.clone();
+				message.add_answers(chunk.to_vec());
+				message
+			})
+			.collect())
+	}
+
+	async fn follow_finality<E: ZoneEvents>(self: Arc<Self>) {
+		let mut finality = self.client.finality_notification_stream();
+		let mut next = Some(self.client.info().finalized_number);
+
+		while let Some(number) = next {
+			let server = self.clone();
+			match spawn_blocking(move || server.catch_up::<E>(number)).await {
+				Ok(Ok(zones)) if !zones.is_empty() => {
+					let targets = self.config.notify.clone();
+					tokio::spawn(async move { notify(&targets, &zones).await });
+				}
+				Ok(Ok(_)) => (),
+				Ok(Err(e)) => {
+					log::warn!(target: "dns", "Failed to follow block #{}: {}", number, e)
+				}
+				Err(_) => return,
+			}
+
+			next = finality
+				.next()
+				.await
+				.map(|notification| *notification.header.number());
+		}
+	}
+
+	/// Record the changes of finalized blocks up to `finalized`, returning
+	/// the SOA records of the zones to notify. The changelog starts over if
+	/// a block can no longer be read.
+	fn catch_up<E: ZoneEvents>(&self, finalized: BlockNumber) -> Result<Vec<Record>, Error> {
+		let processed = self.changelog.read().processed();
+		if processed > finalized {
+			self.changelog.write().reset(finalized)?;
+			return Ok(Vec::new())
+		}
+
+		let mut names = BTreeMap::new();
+		for number in processed + 1..=finalized {
+			let changes = match self.block_changes::<E>(number) {
+				Ok(changes) => changes,
+				Err(e) => {
+					log::warn!(
+						target: "dns",
+						"Starting over the changelog at #{}: {}",
+						finalized,
+						e,
+					);
+					self.changelog.write().reset(finalized)?;
+					break
+				}
+			};
+			for change in &changes {
+				names.insert(change.name.hash(), change.name.clone());
+			}
+			self.changelog.write().record(number, changes)?;
+		}
+
+		if self.config.notify.is_empty() || names.is_empty() {
+			return Ok(Vec::new())
+		}
+
+		let reader = self.reader(self.block_hash(finalized)?);
+		let mut apexes = BTreeMap::new();
+		for name in names.into_values() {
+			let mut current = Some(name);
+			while let Some(check) = current {
+				if reader.owner(&check)?.is_some() {
+					apexes.insert(check.hash(), check.clone());
+				}
+				current = check.parent();
+			}
+		}
+
+		apexes
+			.values()
+			.map(|apex| soa_record(&reader, apex, finalized))
+			.collect()
+	}
+
+	/// Changed zone records of a block, compared with its parent.
+	fn block_changes<E: ZoneEvents>(&self, number: BlockNumber) -> Result<Vec<Change>, Error> {
+		let hash = self.block_hash(number)?;
+		let parent = self.block_hash(number.saturating_sub(1))?;
+
+		let mut key = twox_128(b"System").to_vec();
+		key.extend(twox_128(b"Events"));
+		let mut names = match self
+			.client
+			.storage(&BlockId::Hash(hash), &StorageKey(key))?
+		{
+			Some(events) => E::changed_names(&events.0).map_err(|e| Error::Events(number, e))?,
+			None => Vec::new(),
+		};
+		names.sort_by(name_order);
+		names.dedup();
+
+		let (before, after) = (self.reader(parent), self.reader(hash));
+		let mut changes = Vec::new();
+		for name in names {
+			let old = Snapshot::from(&before.records(&name)?);
+			let new = Snapshot::from(&after.records(&name)?);
+			if old != new {
+				changes.push(Change {
+					number,
+					name,
+					old,
+					new,
+				});
+			}
+		}
+
+		Ok(changes)
+	}
+}
+
+fn server_failure(request: &Message, error: Error) -> Message {
+	log::warn!(target: "dns", "Failed to answer {:?}: {}", request.queries(), error);
+	let mut response = response_to(request);
+	response
+		.add_queries(request.queries().to_vec())
+		.set_response_code(ResponseCode::ServFail);
+	response
+}
@@ -0,0 +1,320 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// This file is part of Neatcoin.
+//
+// Copyright (c) 2021 Wei Tang.
+//
+// Neatcoin is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Neatcoin is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Neatcoin. If not, see <http://www.gnu.org/licenses/>.
+
+//! Full and incremental zone transfers.
+
+use crate::{
+	changelog::Change,
+	resolver::{soa_record, zone_records},
+	Error,
+};
+use neatcoin_nomo::{NomoReader, Records, StorageRead};
+use np_domain::Name;
+use np_opaque::BlockNumber;
+use trust_dns_proto::rr::{rdata::SOA, RData, Record, RecordType};
+
+/// Records of a full transfer of the zone of `apex` at block `serial`: its
+/// SOA, the records held by the zone, and the SOA again. Below cuts and
+/// owned names, only their delegation and glue are held. Stored DNSSEC
+/// records are transferred, but signatures made from the keystore are not.
+pub fn axfr<S: StorageRead>(
+	reader: &NomoReader<S>,
+	apex: &Name,
+	serial: BlockNumber,
+) -> Result<Vec<Record>, Error> {
+	let soa = soa_record(reader, apex, serial)?;
+
+	let mut records = vec![soa.clone()];
+	for (name, entry) in reader.zone_entries(apex)? {
+		records.extend(zone_records(apex, &name, &entry, RecordType::ANY)?);
+	}
+	records.push(soa);
+
+	Ok(records)
+}
+
+/// Records of an incremental transfer of the zone of `apex`, from serial
+/// `from` to the serial of `soa`, given the changes after `from`.
+///
+/// Changes are limited to the records held by the zone, as of the current
+/// state of its cuts and owned names.
+///
+/// Each block changing the zone is one difference sequence. The last one
+/// ends at the current serial, so the sequence is complete even if the zone
+/// did not change in the latest blocks.
+pub fn ixfr<S: StorageRead>(
+	reader: &NomoReader<S>,
+	soa: &Record,
+	apex: &Name,
+	from: BlockNumber,
+	changes: &[Change],
+) -> Result<Vec<Record>, Error> {
+	let serial = match soa.rdata() {
+		RData::SOA(rdata) => rdata.serial(),
+		_ => unreachable!("soa_record only builds SOA records; qed"),
+	};
+	if from >= serial {
+		return Ok(vec![soa.clone()])
+	}
+
+	let mut held = Vec::new();
+	for change in changes.iter().filter(|change| change.number <= serial) {
+		if let Some(part) = reader.zone_part(apex, &change.name)? {
+			held.push((change, part));
+		}
+	}
+	let changes = held;
+	let mut blocks = changes
+		.iter()
+		.map(|(change, _)| change.number)
+		.collect::<Vec<_>>();
+	blocks.dedup();
+	match blocks.last_mut() {
+		Some(last) => *last = serial,
+		None => blocks.push(serial),
+	}
+
+	let mut records = vec![soa.clone()];
+	let mut previous = from;
+	for version in &blocks {
+		let mut deleted = Vec::new();
+		let mut added = Vec::new();
+		for (change, part) in changes
+			.iter()
+			.filter(|(change, _)| change.number > previous && change.number <= *version)
+		{
+			let old = zone_records(
+				apex,
+				&change.name,
+				&part.of(&Records::from(&change.old)),
+				RecordType::ANY,
+			)?;
+			let new = zone_records(
+				apex,
+				&change.name,
+				&part.of(&Records::from(&change.new)),
+				RecordType::ANY,
+			)?;
+			deleted.extend(old.iter().filter(|record| !new.contains(record)).cloned());
+			added.extend(new.iter().filter(|record| !old.contains(record)).cloned());
+		}
+
+		records.push(with_serial(soa, previous));
+		records.extend(deleted);
+		records.push(with_serial(soa, *version));
+		records.extend(added);
+		previous = *version;
+	}
+	records.push(soa.clone());
+
+	Ok(records)
+}
+
+fn with_serial(soa: &Record, serial: BlockNumber) -> Record {
+	let mut record = soa.clone();
+	if let RData::SOA(rdata) = soa.rdata() {
+		record.set_rdata(RData::SOA(SOA::new(
+			rdata.mname().clone(),
+			rdata.rname().clone(),
+			serial,
+			rdata.refresh(),
+			rdata.retry(),
+			rdata.expire(),
+			rdata.minimum(),
+		)));
+	}
+	record
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::{changelog::Snapshot, resolver::to_dns};
+	use codec::Encode;
+	use np_domain::NameValue;
+	use sp_core::hashing::twox_128;
+	use std::{collections::BTreeMap, net::Ipv4Addr};
+
+	#[derive(Default)]
+	struct MemoryStorage(BTreeMap<Vec<u8>, Vec<u8>>);
+
+	impl MemoryStorage {
+		fn insert<T: Encode>(&mut self, pallet: &str, item: &str, name: &str, value: T) {
+			let name = self::name(name);
+			let mut key = twox_128(pallet.as_bytes()).to_vec();
+			key.extend_from_slice(&twox_128(item.as_bytes()));
+			key.extend_from_slice(name.hash().as_bytes());
+			self.0.insert(key, NameValue::some(name, value).encode());
+		}
+	}
+
+	impl StorageRead for MemoryStorage {
+		fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>, neatcoin_nomo::Error> {
+			Ok(self.0.get(key).cloned())
+		}
+
+		fn pairs(&self, prefix: &[u8]) -> Result<Vec<(Vec<u8>, Vec<u8>)>, neatcoin_nomo::Error> {
+			Ok(self
+				.0
+				.iter()
+				.filter(|(key, _)| key.starts_with(prefix))
+				.map(|(key, value)| (key.clone(), value.clone()))
+				.collect())
+		}
+	}
+
+	fn name(dotted: &str) -> Name {
+		Name::from_dotted(dotted.as_bytes()).unwrap()
+	}
+
+	fn soa(apex: &Name, serial: u32) -> Record {
+		Record::from_rdata(
+			to_dns(apex).unwrap(),
+			3600,
+			RData::SOA(SOA::new(
+				to_dns(apex).unwrap(),
+				to_dns(&name("hostmaster.example.neatuser")).unwrap(),
+				serial,
+				3600,
+				900,
+				604800,
+				300,
+			)),
+		)
+	}
+
+	fn snapshot(addr: u32) -> Snapshot {
+		Snapshot {
+			a: vec![addr],
+			..Default::default()
+		}
+	}
+
+	fn a(owner: &str, addr: Ipv4Addr) -> Record {
+		Record::from_rdata(to_dns(&name(owner)).unwrap(), 3600, RData::A(addr))
+	}
+
+	fn serial(record: &Record) -> Option<u32> {
+		match record.rdata() {
+			RData::SOA(rdata) => Some(rdata.serial()),
+			_ => None,
+		}
+	}
+
+	#[test]
+	fn ixfr_has_one_sequence_per_block() {
+		let reader = NomoReader::new(MemoryStorage::default());
+		let apex = name("example.neatuser");
+		let soa = soa(&apex, 30);
+		let changes = vec![
+			Change {
+				number: 12,
+				name: name("www.example.neatuser"),
+				old: snapshot(0x0a000001),
+				new: snapshot(0x0a000002),
+			},
+			Change {
+				number: 12,
+				name: name("other.neatuser"),
+				old: Snapshot::default(),
+				new: snapshot(0x0a000009),
+			},
+			Change {
+				number: 20,
+				name: name("mail.example.neatuser"),
+				old: Snapshot::default(),
+				new: snapshot(0x0a000003),
+			},
+		];
+
+		let records = ixfr(&reader, &soa, &apex, 10, &changes).unwrap();
+		assert_eq!(
+			records.iter().map(serial).collect::<Vec<_>>(),
+			vec![
+				Some(30),
+				Some(10),
+				None,
+				Some(12),
+				None,
+				Some(12),
+				Some(30),
+				None,
+				Some(30)
+			]
+		);
+		assert_eq!(
+			records[2],
+			a("www.example.neatuser", Ipv4Addr::new(10, 0, 0, 1))
+		);
+		assert_eq!(
+			records[4],
+			a("www.example.neatuser", Ipv4Addr::new(10, 0, 0, 2))
+		);
+		assert_eq!(
+			records[7],
+			a("mail.example.neatuser", Ipv4Addr::new(10, 0, 0, 3))
+		);
+
+		assert_eq!(
+			ixfr(&reader, &soa, &apex, 30, &[]).unwrap(),
+			vec![soa.clone()]
+		);
+	}
+	#[test]
+	fn ixfr_keeps_delegations_and_glue_below_cuts() {
+		let mut storage = MemoryStorage::default();
+		storage.insert(
+			"Zone",
+			"NSs",
+			"sub.example.neatuser",
+			vec![name("ns.sub.example.neatuser")],
+		);
+		storage.insert(
+			"Registry",
+			"Ownerships",
+			"owned.example.neatuser",
+			neatcoin_nomo::Ownership::Root,
+		);
+		let reader = NomoReader::new(storage);
+		let apex = name("example.neatuser");
+		let soa = soa(&apex, 30);
+
+		let change = |owner: &str| Change {
+			number: 20,
+			name: name(owner),
+			old: Snapshot::default(),
+			new: snapshot(0x0a000001),
+		};
+		let changes = vec![
+			change("ns.sub.example.neatuser"),
+			change("www.sub.example.neatuser"),
+			change("owned.example.neatuser"),
+			change("www.owned.example.neatuser"),
+			change("www.example.neatuser"),
+		];
+
+		let records = ixfr(&reader, &soa, &apex, 10, &changes).unwrap();
+		assert_eq!(
+			records[3..records.len() - 1],
+			[
+				a("ns.sub.example.neatuser", Ipv4Addr::new(10, 0, 0, 1)),
+				a("www.example.neatuser", Ipv4Addr::new(10, 0, 0, 1)),
+			]
+		);
+	}
+}
@@ -16,31 +16,92 @@
 // You should have received a copy of the GNU General Public License
 // along with Neatcoin. If not, see <http://www.gnu.org/licenses/>.
 
-//! Era information recording.
+//! Network bootstrap utilities.
 
 #![cfg_attr(not(feature = "std"), no_std)]
 
-use frame_support::{decl_module, decl_storage};
+pub mod migrations;
+
+use frame_support::traits::StorageVersion;
 use sp_std::prelude::*;
 
-pub trait Config: frame_system::Config {}
+pub use pallet::*;
+
+/// Current storage version of the pallet.
+pub const STORAGE_VERSION: StorageVersion = StorageVersion::new(2);
+
+#[frame_support::pallet]
+pub mod pallet {
+	use super::*;
+	use frame_support::pallet_prelude::*;
 
-decl_storage! {
-	trait Store for Module<T: Config> as Bootstrap {
-		/// Bootstrapping endowed accounts.
-		pub Endoweds get(fn endoweds): Vec<T::AccountId>;
+	#[pallet::config]
+	pub trait Config: frame_system::Config {
+		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
 	}
-	add_extra_genesis {
-		config(endoweds): Vec<T::AccountId>;
-		build(|config: &GenesisConfig<T>| {
-			Endoweds::<T>::set(config.endoweds.clone());
-			for account in &config.endoweds {
+
+	#[pallet::pallet]
+	#[pallet::generate_store(pub(super) trait Store)]
+	#[pallet::storage_version(STORAGE_VERSION)]
+	pub struct Pallet<T>(_);
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {}
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// The provider reference added at genesis for the endowed account was
+		/// released.
+		EndowedReleased(T::AccountId),
+	}
+
+	/// Bootstrapping endowed accounts.
+	#[pallet::storage]
+	#[pallet::getter(fn endoweds)]
+	pub type Endoweds<T: Config> = StorageValue<_, Vec<T::AccountId>, ValueQuery>;
+
+	#[pallet::genesis_config]
+	pub struct GenesisConfig<T: Config> {
+		pub endoweds: Vec<T::AccountId>,
+	}
+
+	#[cfg(feature = "std")]
+	impl<T: Config> Default for GenesisConfig<T> {
+		fn default() -> Self {
+			Self { endoweds: Vec::new() }
+		}
+	}
+
+	#[pallet::genesis_build]
+	impl<T: Config> GenesisBuild<T> for GenesisConfig<T> {
+		fn build(&self) {
+			Endoweds::<T>::put(self.endoweds.clone());
+			for account in &self.endoweds {
 				frame_system::Pallet::<T>::inc_providers(account);
 			}
-		})
+		}
 	}
 }
 
-decl_module! {
-	pub struct Module<T: Config> for enum Call where origin: T::Origin { }
+impl<T: Config> Pallet<T> {
+	/// Release the provider references added at genesis for all endowed
+	/// accounts, and clear `Endoweds`. Returns the number of accounts.
+	pub fn release_endoweds() -> u32 {
+		let endoweds = Endoweds::<T>::take();
+
+		for account in &endoweds {
+			match frame_system::Pallet::<T>::dec_providers(account) {
+				Ok(_) => Self::deposit_event(Event::<T>::EndowedReleased(account.clone())),
+				Err(e) => log::warn!(
+					target: "runtime::bootstrap",
+					"Failed to release endowed account {:?}: {:?}",
+					account,
+					e,
+				),
+			}
+		}
+
+		endoweds.len() as u32
+	}
 }
@@ -0,0 +1,103 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// This file is part of Neatcoin.
+//
+// Copyright (c) 2021 Wei Tang.
+//
+// Neatcoin is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Neatcoin is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Neatcoin. If not, see <http://www.gnu.org/licenses/>.
+
+//! Storage migrations for the bootstrap pallet.
+
+use crate::{Config, Endoweds, Pallet};
+use frame_support::{
+	traits::{Get, OnRuntimeUpgrade, StorageVersion},
+	weights::Weight,
+};
+use sp_std::marker::PhantomData;
+#[cfg(feature = "try-runtime")]
+use {
+	codec::{Decode, Encode},
+	frame_support::traits::OnRuntimeUpgradeHelpersExt,
+	sp_std::prelude::*,
+};
+
+/// Release the provider references of the bootstrap endowed accounts, once
+/// staking has taken over, moving to storage version 2. Does nothing once
+/// `Endoweds` is empty. The release is keyed on `Endoweds` rather than on the
+/// storage version, because chains built from genesis start at version 2 but
+/// still hold the references added by `GenesisConfig::build`.
+pub struct ReleaseEndoweds<T>(PhantomData<T>);
+
+impl<T: Config> OnRuntimeUpgrade for ReleaseEndoweds<T> {
+	fn on_runtime_upgrade() -> Weight {
+		if !Endoweds::<T>::exists() {
+			return T::DbWeight::get().reads(1)
+		}
+
+		let count = Pallet::<T>::release_endoweds() as Weight;
+		StorageVersion::new(2).put::<Pallet<T>>();
+
+		T::DbWeight::get().reads_writes(2 + count, 2 + count)
+	}
+
+	#[cfg(feature = "try-runtime")]
+	fn pre_upgrade() -> Result<(), &'static str> {
+		if !Endoweds::<T>::exists() {
+			log::info!(target: "runtime::bootstrap", "Endowed accounts already released");
+			return Ok(())
+		}
+
+		let accounts = Endoweds::<T>::get()
+			.into_iter()
+			.map(|account| {
+				let data = frame_system::Account::<T>::get(&account).data;
+				(account, data)
+			})
+			.collect::<Vec<_>>();
+
+		log::info!(
+			target: "runtime::bootstrap",
+			"Releasing {} endowed accounts",
+			accounts.len(),
+		);
+		Self::set_temp_storage(accounts.encode(), "endowed_accounts");
+
+		Ok(())
+	}
+
+	#[cfg(feature = "try-runtime")]
+	fn post_upgrade() -> Result<(), &'static str> {
+		frame_support::ensure!(
+			StorageVersion::get::<Pallet<T>>() >= StorageVersion::new(2),
+			"storage version not updated",
+		);
+
+		// Nothing was recorded if the accounts were already released.
+		let accounts = match Self::get_temp_storage::<Vec<u8>>("endowed_accounts") {
+			Some(accounts) => accounts,
+			None => return Ok(()),
+		};
+		let accounts = Vec::<(T::AccountId, T::AccountData)>::decode(&mut &accounts[..])
+			.map_err(|_| "failed to decode endowed accounts recorded in pre_upgrade")?;
+
+		frame_support::ensure!(Endoweds::<T>::get().is_empty(), "endowed accounts not cleared");
+		for (account, data) in accounts {
+			frame_support::ensure!(
+				frame_system::Account::<T>::get(&account).data == data,
+				"endowed account balance changed",
+			);
+		}
+
+		Ok(())
+	}
+}
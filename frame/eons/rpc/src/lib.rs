@@ -0,0 +1,78 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// This file is part of Neatcoin.
+//
+// Copyright (c) 2021 Wei Tang.
+//
+// Neatcoin is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Neatcoin is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Neatcoin. If not, see <http://www.gnu.org/licenses/>.
+
+//! RPC interface for the eons pallet.
+
+use std::sync::Arc;
+
+use codec::Codec;
+use jsonrpc_core::{Error as RpcError, ErrorCode, Result};
+use jsonrpc_derive::rpc;
+use pallet_eons::Eon;
+use serde::Serialize;
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_runtime::{generic::BlockId, traits::Block as BlockT};
+
+pub use pallet_eons_rpc_runtime_api::EonsApi as EonsRuntimeApi;
+
+/// Eons RPC methods.
+#[rpc]
+pub trait EonsApi<BlockHash, Hash> {
+	/// Past eons, with their indexes.
+	#[rpc(name = "eons_pastEons")]
+	fn past_eons(&self, at: Option<BlockHash>) -> Result<Vec<(u32, Eon<Hash>)>>;
+}
+
+/// An implementation of eons specific RPC methods.
+pub struct Eons<C, B> {
+	client: Arc<C>,
+	_marker: std::marker::PhantomData<B>,
+}
+
+impl<C, B> Eons<C, B> {
+	/// Create new `Eons` with the given reference to the client.
+	pub fn new(client: Arc<C>) -> Self {
+		Eons {
+			client,
+			_marker: Default::default(),
+		}
+	}
+}
+
+/// Error code of a failed runtime call.
+const RUNTIME_ERROR: i64 = 1;
+
+impl<C, Block, Hash> EonsApi<<Block as BlockT>::Hash, Hash> for Eons<C, Block>
+where
+	Block: BlockT,
+	C: Send + Sync + 'static + ProvideRuntimeApi<Block> + HeaderBackend<Block>,
+	C::Api: EonsRuntimeApi<Block, Hash>,
+	Hash: Codec + Serialize + Send + Sync + 'static,
+{
+	fn past_eons(&self, at: Option<<Block as BlockT>::Hash>) -> Result<Vec<(u32, Eon<Hash>)>> {
+		let api = self.client.runtime_api();
+		let at = BlockId::hash(at.unwrap_or_else(|| self.client.info().best_hash));
+
+		api.past_eons(&at).map_err(|e| RpcError {
+			code: ErrorCode::ServerError(RUNTIME_ERROR),
+			message: "Unable to query past eons.".into(),
+			data: Some(format!("{:?}", e).into()),
+		})
+	}
+}
@@ -0,0 +1,92 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// This file is part of Neatcoin.
+//
+// Copyright (c) 2021 Wei Tang.
+//
+// Neatcoin is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Neatcoin is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Neatcoin. If not, see <http://www.gnu.org/licenses/>.
+
+#![cfg(feature = "runtime-benchmarks")]
+
+use super::*;
+use frame_benchmarking::{benchmarks, whitelisted_caller};
+use frame_system::RawOrigin;
+use np_domain::Label;
+use sp_runtime::traits::{Hash, UniqueSaturatedFrom};
+use sp_std::convert::TryFrom;
+
+/// Push an eon whose final state holds `entries`, returning its index and a
+/// proof of the entries padded with an unused node of `b` bytes.
+fn push_proven_eon<T: Config>(entries: &[(Vec<u8>, Vec<u8>)], b: u32) -> (u32, Vec<Vec<u8>>) {
+	let (final_state_root, mut proof) = Pallet::<T>::trie_proof(entries);
+	proof.push(vec![0u8; b as usize]);
+
+	let mut past_eons = PastEons::<T>::get();
+	let index = past_eons.len() as u32;
+	past_eons.push(Eon {
+		genesis_block_hash: T::Hashing::hash(b"genesis"),
+		final_block_hash: T::Hashing::hash(b"final"),
+		final_state_root,
+	});
+	PastEons::<T>::put(past_eons);
+
+	(index, proof)
+}
+
+benchmarks! {
+	push_eon {
+		let origin = T::EonOrigin::successful_origin();
+		let eon = Eon {
+			genesis_block_hash: T::Hashing::hash(b"genesis"),
+			final_block_hash: T::Hashing::hash(b"final"),
+			final_state_root: T::Hashing::hash(b"state"),
+		};
+	}: _<T::Origin>(origin, eon.clone())
+	verify {
+		assert_eq!(PastEons::<T>::get(), vec![eon]);
+	}
+
+	claim_balance {
+		let b in 0 .. MAX_PROOF_SIZE / 2;
+
+		let caller: T::AccountId = whitelisted_caller();
+		let mut info = AccountInfo::<T::Index, AccountData<BalanceOf<T>>>::default();
+		info.data.free = UniqueSaturatedFrom::unique_saturated_from(2000_000_000_000_000u128);
+		let key = frame_system::Account::<T>::hashed_key_for(&caller);
+		let (eon, proof) = push_proven_eon::<T>(&[(key.clone(), info.encode())], b);
+	}: _(RawOrigin::Signed(caller.clone()), eon, caller.clone(), proof)
+	verify {
+		assert!(Claimed::<T>::get(eon, key));
+	}
+
+	claim_name {
+		let b in 0 .. MAX_PROOF_SIZE / 2;
+
+		let caller: T::AccountId = whitelisted_caller();
+		let name = Name(vec![
+			Label::try_from(b"neatuser".to_vec()).unwrap(),
+			Label::try_from(b"testname".to_vec()).unwrap(),
+		]);
+		let fcfs_name = Name(vec![
+			Label::try_from(b"neatuser".to_vec()).unwrap(),
+		]);
+		T::Registry::set_ownership_unchecked(fcfs_name, Some(T::Registrar::ownership()));
+
+		let key = T::Registry::ownership_key(&name);
+		let value = NameValue::some(name.clone(), T::Ownership::account(caller.clone()));
+		let (eon, proof) = push_proven_eon::<T>(&[(key, value.encode())], b);
+	}: _(RawOrigin::Signed(caller.clone()), eon, name.clone(), proof)
+	verify {
+		assert_eq!(T::Registry::owner(&name), Some(T::Ownership::account(caller)));
+	}
+}
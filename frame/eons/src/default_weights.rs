@@ -0,0 +1,44 @@
+//! Weights for `pallet_eons`
+//!
+//! NOT YET MEASURED. THESE ARE PLACEHOLDERS FOLLOWING THE STORAGE FOOTPRINT
+//! OF EACH CALL, UNTIL REPLACED BY THE OUTPUT OF `scripts/benchmark.sh`.
+
+
+#![cfg_attr(rustfmt, rustfmt_skip)]
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+
+use frame_support::{traits::Get, weights::{Weight, constants::RocksDbWeight as DbWeight}};
+use sp_std::marker::PhantomData;
+
+impl crate::WeightInfo for () {
+	// Storage: Eons PastEons (r:1 w:1)
+	fn push_eon() -> Weight {
+		(15_000_000 as Weight)
+			.saturating_add(DbWeight::get().reads(1 as Weight))
+			.saturating_add(DbWeight::get().writes(1 as Weight))
+	}
+	// Storage: Eons PastEons (r:1 w:0)
+	// Storage: Eons Claimed (r:1 w:1)
+	// Storage: System Account (r:1 w:1)
+	fn claim_balance(b: u32, ) -> Weight {
+		(60_000_000 as Weight)
+			// Standard Error: 0
+			.saturating_add((3_000 as Weight).saturating_mul(b as Weight))
+			.saturating_add(DbWeight::get().reads(3 as Weight))
+			.saturating_add(DbWeight::get().writes(2 as Weight))
+	}
+	// Storage: Registry Ownerships (r:3 w:1)
+	// Storage: Eons PastEons (r:1 w:0)
+	// Storage: Eons Claimed (r:1 w:1)
+	// Storage: FCFS Fee (r:1 w:0)
+	// Storage: Registry Children (r:0 w:1)
+	// Storage: FCFS Renewals (r:0 w:1)
+	fn claim_name(b: u32, ) -> Weight {
+		(75_000_000 as Weight)
+			// Standard Error: 0
+			.saturating_add((3_000 as Weight).saturating_mul(b as Weight))
+			.saturating_add(DbWeight::get().reads(6 as Weight))
+			.saturating_add(DbWeight::get().writes(4 as Weight))
+	}
+}
@@ -17,18 +17,59 @@
 // along with Neatcoin. If not, see <http://www.gnu.org/licenses/>.
 
 //! Era information recording.
+//!
+//! Balances and names of a past eon can be claimed by submitting a storage
+//! proof against the eon's final state root.
 
 #![cfg_attr(not(feature = "std"), no_std)]
 
+mod benchmarking;
+mod default_weights;
+#[cfg(test)]
+mod mock;
+#[cfg(test)]
+mod tests;
+
 use codec::{Decode, Encode};
-use frame_support::{decl_module, decl_storage};
+use frame_support::{
+	ensure,
+	traits::{Currency, EnsureOrigin, Imbalance, StorageVersion},
+	weights::Weight,
+};
+use frame_system::{ensure_signed, AccountInfo};
+use np_domain::{Name, NameValue};
+use pallet_balances::AccountData;
+use pallet_fcfs::Registrar;
+use pallet_registry::{Ownership, Registry};
 use scale_info::TypeInfo;
 #[cfg(feature = "std")]
 use serde::{Deserialize, Serialize};
+use sp_runtime::{
+	traits::{Saturating, Zero},
+	RuntimeDebug,
+};
 use sp_std::prelude::*;
+use sp_trie::{read_trie_value, Layout, StorageProof};
+
+pub use pallet::*;
+
+/// Current storage version of the pallet.
+pub const STORAGE_VERSION: StorageVersion = StorageVersion::new(1);
+
+/// Maximum total size of the nodes of a claim proof, in bytes.
+pub const MAX_PROOF_SIZE: u32 = 64 * 1024;
+
+pub trait WeightInfo {
+	fn push_eon() -> Weight;
+	fn claim_balance(b: u32) -> Weight;
+	fn claim_name(b: u32) -> Weight;
+}
+
+pub type BalanceOf<T> =
+	<<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
 
 #[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
-#[derive(Encode, Decode, TypeInfo)]
+#[derive(Encode, Decode, Clone, PartialEq, Eq, RuntimeDebug, TypeInfo)]
 pub struct Eon<H> {
 	/// Genesis block hash of the eon.
 	pub genesis_block_hash: H,
@@ -38,15 +79,281 @@ pub struct Eon<H> {
 	pub final_state_root: H,
 }
 
-pub trait Config: frame_system::Config {}
+#[frame_support::pallet]
+pub mod pallet {
+	use super::*;
+	use frame_support::pallet_prelude::*;
+	use frame_system::pallet_prelude::*;
+
+	#[pallet::config]
+	pub trait Config: frame_system::Config {
+		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+		/// Origin allowed to append eons.
+		type EonOrigin: EnsureOrigin<Self::Origin>;
+		/// Currency credited by balance claims.
+		type Currency: Currency<Self::AccountId>;
+		type Ownership: Ownership<AccountId = Self::AccountId>;
+		/// Registry names are re-assigned in by name claims.
+		type Registry: Registry<Ownership = Self::Ownership>;
+		/// Registrar whose names expire, restarting the period of claimed
+		/// names registered under it.
+		type Registrar: Registrar<Self::Ownership>;
+		type WeightInfo: WeightInfo;
+	}
+
+	#[pallet::pallet]
+	#[pallet::generate_store(pub(super) trait Store)]
+	#[pallet::storage_version(STORAGE_VERSION)]
+	pub struct Pallet<T>(_);
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		/// Append an eon to the list of past eons.
+		#[pallet::weight(T::WeightInfo::push_eon())]
+		pub fn push_eon(origin: OriginFor<T>, eon: Eon<T::Hash>) -> DispatchResult {
+			T::EonOrigin::ensure_origin(origin)?;
+
+			let zero = T::Hash::default();
+			ensure!(
+				eon.genesis_block_hash != zero &&
+					eon.final_block_hash != zero &&
+					eon.final_state_root != zero,
+				Error::<T>::ZeroHash,
+			);
+
+			let mut past_eons = PastEons::<T>::get();
+			ensure!(
+				past_eons.iter().all(|past| {
+					past.genesis_block_hash != eon.genesis_block_hash &&
+						past.final_block_hash != eon.final_block_hash &&
+						past.final_state_root != eon.final_state_root
+				}),
+				Error::<T>::DuplicateEon,
+			);
+
+			let index = past_eons.len() as u32;
+			let genesis_block_hash = eon.genesis_block_hash.clone();
+			past_eons.push(eon);
+			PastEons::<T>::put(past_eons);
+
+			Self::deposit_event(Event::<T>::EonPushed(index, genesis_block_hash));
+			Ok(())
+		}
+
+		/// Claim the balance `who` had at the end of a past eon. Free and
+		/// reserved balances are both credited as free balance, which must
+		/// be enough for the account to exist.
+		#[pallet::weight(T::WeightInfo::claim_balance(proof_size(proof)))]
+		pub fn claim_balance(
+			origin: OriginFor<T>,
+			eon: u32,
+			who: T::AccountId,
+			proof: Vec<Vec<u8>>,
+		) -> DispatchResult {
+			ensure_signed(origin)?;
+
+			let key = frame_system::Account::<T>::hashed_key_for(&who);
+			let raw = Self::prove(eon, &key, proof)?;
+			let info = AccountInfo::<T::Index, AccountData<BalanceOf<T>>>::decode(&mut &raw[..])
+				.map_err(|_| Error::<T>::InvalidValue)?;
+
+			let value = info.data.free.saturating_add(info.data.reserved);
+			ensure!(value != Default::default(), Error::<T>::NothingToClaim);
+
+			// A deposit below the existential deposit of a new account is
+			// dropped, so the balance stays unclaimed.
+			let imbalance = T::Currency::deposit_creating(&who, value);
+			ensure!(!imbalance.peek().is_zero(), Error::<T>::BelowMinimumBalance);
+			drop(imbalance);
+			Claimed::<T>::insert(eon, key, true);
+
+			Self::deposit_event(Event::<T>::BalanceClaimed(eon, who, value));
+			Ok(())
+		}
+
+		/// Re-assign a name that was owned by an account at the end of a past
+		/// eon to the same account. The name must not be owned in the current
+		/// eon, and its closest owned ancestor must be the same account or
+		/// the registrar. Names registered under the registrar start a new
+		/// registration period.
+		#[pallet::weight(T::WeightInfo::claim_name(proof_size(proof)))]
+		pub fn claim_name(
+			origin: OriginFor<T>,
+			eon: u32,
+			name: Name,
+			proof: Vec<Vec<u8>>,
+		) -> DispatchResult {
+			ensure_signed(origin)?;
+			ensure!(T::Registry::owner(&name).is_none(), Error::<T>::NameOccupied);
+
+			let key = T::Registry::ownership_key(&name);
+			let raw = Self::prove(eon, &key, proof)?;
+			let (proven_name, ownership) = NameValue::<T::Ownership>::decode(&mut &raw[..])
+				.map_err(|_| Error::<T>::InvalidValue)?
+				.into_inner()
+				.ok_or(Error::<T>::NothingToClaim)?;
+			ensure!(proven_name == name, Error::<T>::InvalidValue);
+			let who = ownership.as_account().cloned().ok_or(Error::<T>::NotAccountOwned)?;
+			let registered = Self::check_ancestors(&name, &ownership)?;
+
+			Claimed::<T>::insert(eon, key, true);
+			T::Registry::set_ownership_unchecked(name.clone(), Some(ownership));
+			if registered {
+				T::Registrar::start_period(name.clone());
+			}
+
+			Self::deposit_event(Event::<T>::NameClaimed(eon, name, who));
+			Ok(())
+		}
+	}
 
-decl_storage! {
-	trait Store for Module<T: Config> as Eons {
-		/// Past eons.
-		pub PastEons get(fn past_eons) config(past_eons): Vec<Eon<T::Hash>>;
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// A past eon was appended, with its index and genesis block hash.
+		EonPushed(u32, T::Hash),
+		/// Balance of an account in a past eon was claimed.
+		BalanceClaimed(u32, T::AccountId, BalanceOf<T>),
+		/// Name owned by an account in a past eon was claimed.
+		NameClaimed(u32, Name, T::AccountId),
 	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		/// One of the eon hashes is zero.
+		ZeroHash,
+		/// One of the eon hashes is already recorded by another eon.
+		DuplicateEon,
+		/// The eon index does not exist.
+		UnknownEon,
+		/// The storage proof is invalid for the eon's final state root.
+		InvalidProof,
+		/// The proven value could not be decoded.
+		InvalidValue,
+		/// The item was already claimed.
+		AlreadyClaimed,
+		/// There is nothing to claim.
+		NothingToClaim,
+		/// The name was not owned by an account in the past eon.
+		NotAccountOwned,
+		/// The name is already owned in the current eon.
+		NameOccupied,
+		/// An ancestor of the name is owned by someone else in the current
+		/// eon.
+		AncestorOwned,
+		/// The claimed balance is below the existential deposit.
+		BelowMinimumBalance,
+		/// The storage proof is larger than `MAX_PROOF_SIZE`.
+		ProofTooLarge,
+	}
+
+	/// Past eons.
+	#[pallet::storage]
+	#[pallet::getter(fn past_eons)]
+	pub type PastEons<T: Config> = StorageValue<_, Vec<Eon<T::Hash>>, ValueQuery>;
+
+	/// Storage keys of past eons that have already been claimed.
+	#[pallet::storage]
+	#[pallet::getter(fn claimed)]
+	pub type Claimed<T: Config> =
+		StorageDoubleMap<_, Twox64Concat, u32, Blake2_128Concat, Vec<u8>, bool, ValueQuery>;
+
+	#[pallet::genesis_config]
+	pub struct GenesisConfig<T: Config> {
+		pub past_eons: Vec<Eon<T::Hash>>,
+	}
+
+	#[cfg(feature = "std")]
+	impl<T: Config> Default for GenesisConfig<T> {
+		fn default() -> Self {
+			Self { past_eons: Vec::new() }
+		}
+	}
+
+	#[pallet::genesis_build]
+	impl<T: Config> GenesisBuild<T> for GenesisConfig<T> {
+		fn build(&self) {
+			PastEons::<T>::put(self.past_eons.clone());
+		}
+	}
+}
+
+/// Total size of the nodes of a proof, in bytes.
+fn proof_size(proof: &[Vec<u8>]) -> u32 {
+	proof
+		.iter()
+		.fold(0u32, |size, node| size.saturating_add(node.len() as u32))
 }
 
-decl_module! {
-	pub struct Module<T: Config> for enum Call where origin: T::Origin { }
+impl<T: Config> Pallet<T> {
+	/// Check that `key` is unclaimed and verify its value against the final
+	/// state root of the eon.
+	fn prove(eon: u32, key: &[u8], proof: Vec<Vec<u8>>) -> Result<Vec<u8>, Error<T>> {
+		ensure!(proof_size(&proof) <= MAX_PROOF_SIZE, Error::<T>::ProofTooLarge);
+
+		let state_root = PastEons::<T>::get()
+			.get(eon as usize)
+			.map(|eon| eon.final_state_root.clone())
+			.ok_or(Error::<T>::UnknownEon)?;
+		ensure!(!Claimed::contains_key(eon, key), Error::<T>::AlreadyClaimed);
+
+		let db = StorageProof::new(proof).into_memory_db::<T::Hashing>();
+		read_trie_value::<Layout<T::Hashing>, _>(&db, &state_root, key)
+			.map_err(|_| Error::<T>::InvalidProof)?
+			.ok_or(Error::<T>::NothingToClaim)
+	}
+
+	/// Check that the closest owned ancestor of a claimed name, if any, is
+	/// the claimed ownership itself or the registrar as the direct parent.
+	/// Returns whether the name is registered under the registrar.
+	fn check_ancestors(name: &Name, ownership: &T::Ownership) -> Result<bool, Error<T>> {
+		let parent = match name.parent() {
+			Some(parent) => parent,
+			None => return Ok(false),
+		};
+
+		match T::Registry::effective_owner(&parent) {
+			None => Ok(false),
+			Some(owner) if &owner == ownership => Ok(false),
+			Some(owner) if owner == T::Registrar::ownership() => {
+				// The registrar only registers direct children of its names.
+				ensure!(T::Registry::owner(&parent).is_some(), Error::<T>::AncestorOwned);
+				Ok(true)
+			}
+			Some(_) => Err(Error::<T>::AncestorOwned),
+		}
+	}
+
+	/// Past eons with their indexes.
+	pub fn indexed_past_eons() -> Vec<(u32, Eon<T::Hash>)> {
+		PastEons::<T>::get()
+			.into_iter()
+			.enumerate()
+			.map(|(index, eon)| (index as u32, eon))
+			.collect()
+	}
+
+	/// State root of a trie holding `entries`, and a storage proof of all
+	/// of them.
+	#[cfg(any(test, feature = "runtime-benchmarks"))]
+	pub fn trie_proof(entries: &[(Vec<u8>, Vec<u8>)]) -> (T::Hash, Vec<Vec<u8>>) {
+		use sp_trie::{MemoryDB, TrieDBMut, TrieMut};
+
+		let mut db = MemoryDB::<T::Hashing>::default();
+		let mut root = T::Hash::default();
+		{
+			let mut trie = TrieDBMut::<Layout<T::Hashing>>::new(&mut db, &mut root);
+			for (key, value) in entries {
+				trie.insert(key, value).expect("in-memory trie insertion never fails; qed");
+			}
+		}
+
+		let proof = db
+			.drain()
+			.into_iter()
+			.filter(|(_, (_, rc))| *rc > 0)
+			.map(|(_, (node, _))| node)
+			.collect();
+		(root, proof)
+	}
 }
@@ -0,0 +1,171 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// This file is part of Neatcoin.
+//
+// Copyright (c) 2021 Wei Tang.
+//
+// Neatcoin is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Neatcoin is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Neatcoin. If not, see <http://www.gnu.org/licenses/>.
+
+use crate as pallet_eons;
+use codec::{Decode, Encode};
+use frame_support::{parameter_types, traits::Everything};
+use frame_system::EnsureRoot;
+use scale_info::TypeInfo;
+use serde::{Deserialize, Serialize};
+use sp_core::H256;
+use sp_runtime::{
+	testing::Header,
+	traits::{BlakeTwo256, IdentityLookup},
+	RuntimeDebug,
+};
+
+type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
+type Block = frame_system::mocking::MockBlock<Test>;
+
+frame_support::construct_runtime!(
+	pub enum Test where
+		Block = Block,
+		NodeBlock = Block,
+		UncheckedExtrinsic = UncheckedExtrinsic,
+	{
+		System: frame_system::{Pallet, Call, Config, Storage, Event<T>},
+		Balances: pallet_balances::{Pallet, Call, Storage, Config<T>, Event<T>},
+		Registry: pallet_registry::{Pallet, Call, Storage, Event<T>},
+		FCFS: pallet_fcfs::{Pallet, Call, Storage, Event<T>},
+		Eons: pallet_eons::{Pallet, Call, Storage, Event<T>},
+	}
+);
+
+pub type AccountId = u64;
+pub type Balance = u64;
+
+#[derive(Encode, Decode, Clone, RuntimeDebug, PartialEq, Eq, TypeInfo, Serialize, Deserialize)]
+pub enum Ownership {
+	None,
+	Root,
+	FCFS,
+	Account(AccountId),
+}
+
+impl Default for Ownership {
+	fn default() -> Self {
+		Self::None
+	}
+}
+
+impl pallet_registry::Ownership for Ownership {
+	type AccountId = AccountId;
+
+	fn root() -> Self {
+		Self::Root
+	}
+	fn account(account: AccountId) -> Self {
+		Self::Account(account)
+	}
+	fn as_account(&self) -> Option<&AccountId> {
+		match self {
+			Self::Account(account) => Some(account),
+			_ => None,
+		}
+	}
+}
+
+parameter_types! {
+	pub const BlockHashCount: u64 = 250;
+}
+
+impl frame_system::Config for Test {
+	type BaseCallFilter = Everything;
+	type BlockWeights = ();
+	type BlockLength = ();
+	type DbWeight = ();
+	type Origin = Origin;
+	type Call = Call;
+	type Index = u64;
+	type BlockNumber = u64;
+	type Hash = H256;
+	type Hashing = BlakeTwo256;
+	type AccountId = AccountId;
+	type Lookup = IdentityLookup<AccountId>;
+	type Header = Header;
+	type Event = Event;
+	type BlockHashCount = BlockHashCount;
+	type Version = ();
+	type PalletInfo = PalletInfo;
+	type AccountData = pallet_balances::AccountData<Balance>;
+	type OnNewAccount = ();
+	type OnKilledAccount = ();
+	type SystemWeightInfo = ();
+	type OnSetCode = ();
+	type SS58Prefix = ();
+}
+
+parameter_types! {
+	pub const ExistentialDeposit: Balance = 10;
+}
+
+impl pallet_balances::Config for Test {
+	type Balance = Balance;
+	type DustRemoval = ();
+	type Event = Event;
+	type ExistentialDeposit = ExistentialDeposit;
+	type AccountStore = System;
+	type MaxLocks = ();
+	type MaxReserves = ();
+	type ReserveIdentifier = [u8; 8];
+	type WeightInfo = ();
+}
+
+impl pallet_registry::Config for Test {
+	type Ownership = Ownership;
+	type Event = Event;
+	type WeightInfo = ();
+}
+
+parameter_types! {
+	pub const DefaultFee: Balance = 50;
+	pub const Period: u64 = 100;
+	pub const CanRenewAfter: u64 = 50;
+	pub const FCFSOwnership: Ownership = Ownership::FCFS;
+}
+
+impl pallet_fcfs::Config for Test {
+	type Ownership = Ownership;
+	type FCFSOwnership = FCFSOwnership;
+	type Registry = Registry;
+	type Currency = Balances;
+	type DefaultFee = DefaultFee;
+	type Period = Period;
+	type CanRenewAfter = CanRenewAfter;
+	type ChargeFee = ();
+	type WeightInfo = ();
+	type Event = Event;
+}
+
+impl pallet_eons::Config for Test {
+	type Event = Event;
+	type EonOrigin = EnsureRoot<AccountId>;
+	type Currency = Balances;
+	type Ownership = Ownership;
+	type Registry = Registry;
+	type Registrar = FCFS;
+	type WeightInfo = ();
+}
+
+pub fn new_test_ext() -> sp_io::TestExternalities {
+	let storage = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap();
+
+	let mut ext = sp_io::TestExternalities::new(storage);
+	ext.execute_with(|| System::set_block_number(1));
+	ext
+}
@@ -0,0 +1,175 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// This file is part of Neatcoin.
+//
+// Copyright (c) 2021 Wei Tang.
+//
+// Neatcoin is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Neatcoin is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Neatcoin. If not, see <http://www.gnu.org/licenses/>.
+
+use crate::{mock::*, AccountData, AccountInfo, Eon, Error};
+use codec::Encode;
+use frame_support::{assert_noop, assert_ok};
+use np_domain::{Name, NameValue};
+use pallet_registry::Registry as _;
+use sp_core::H256;
+
+/// Push an eon whose final state holds `entries`, returning its index and a
+/// proof of the entries.
+fn push_proven_eon(entries: &[(Vec<u8>, Vec<u8>)]) -> (u32, Vec<Vec<u8>>) {
+	let (final_state_root, proof) = Eons::trie_proof(entries);
+	let index = Eons::past_eons().len() as u32;
+	let eon = Eon {
+		genesis_block_hash: H256::repeat_byte(index as u8 * 2 + 1),
+		final_block_hash: H256::repeat_byte(index as u8 * 2 + 2),
+		final_state_root,
+	};
+	assert_ok!(Eons::push_eon(Origin::root(), eon));
+
+	(index, proof)
+}
+
+fn balance_entry(who: AccountId, free: Balance) -> (Vec<u8>, Vec<u8>) {
+	let mut info = AccountInfo::<u64, AccountData<Balance>>::default();
+	info.data.free = free;
+	(frame_system::Account::<Test>::hashed_key_for(&who), info.encode())
+}
+
+fn name_entry(name: &Name, who: AccountId) -> (Vec<u8>, Vec<u8>) {
+	let value = NameValue::some(name.clone(), Ownership::Account(who));
+	(Registry::ownership_key(name), value.encode())
+}
+
+fn name(dotted: &[u8]) -> Name {
+	Name::from_dotted(dotted).expect("valid name; qed")
+}
+
+#[test]
+fn claims_balance_with_valid_proof() {
+	new_test_ext().execute_with(|| {
+		let (eon, proof) = push_proven_eon(&[balance_entry(1, 100), balance_entry(2, 200)]);
+
+		assert_ok!(Eons::claim_balance(Origin::signed(3), eon, 2, proof));
+		assert_eq!(Balances::free_balance(2), 200);
+		assert_eq!(Balances::free_balance(1), 0);
+	});
+}
+
+#[test]
+fn rejects_invalid_proof() {
+	new_test_ext().execute_with(|| {
+		let (_, proof) = push_proven_eon(&[balance_entry(1, 100)]);
+		let (eon, _) = push_proven_eon(&[balance_entry(1, 1000)]);
+
+		assert_noop!(
+			Eons::claim_balance(Origin::signed(1), eon, 1, proof),
+			Error::<Test>::InvalidProof,
+		);
+		assert_noop!(
+			Eons::claim_balance(Origin::signed(1), eon, 1, Vec::new()),
+			Error::<Test>::InvalidProof,
+		);
+	});
+}
+
+#[test]
+fn rejects_double_claim() {
+	new_test_ext().execute_with(|| {
+		let (eon, proof) = push_proven_eon(&[balance_entry(1, 100)]);
+
+		assert_ok!(Eons::claim_balance(Origin::signed(1), eon, 1, proof.clone()));
+		assert_noop!(
+			Eons::claim_balance(Origin::signed(1), eon, 1, proof),
+			Error::<Test>::AlreadyClaimed,
+		);
+		assert_eq!(Balances::free_balance(1), 100);
+	});
+}
+
+#[test]
+fn rejects_balance_below_existential_deposit() {
+	new_test_ext().execute_with(|| {
+		let (eon, proof) = push_proven_eon(&[balance_entry(1, ExistentialDeposit::get() - 1)]);
+		let key = balance_entry(1, 0).0;
+
+		assert_noop!(
+			Eons::claim_balance(Origin::signed(1), eon, 1, proof),
+			Error::<Test>::BelowMinimumBalance,
+		);
+		assert!(!Eons::claimed(eon, key));
+	});
+}
+
+#[test]
+fn claims_name_registered_under_registrar() {
+	new_test_ext().execute_with(|| {
+		let claimed = name(b"alice.neatuser");
+		Registry::set_ownership_unchecked(name(b"neatuser"), Some(Ownership::FCFS));
+		let (eon, proof) = push_proven_eon(&[name_entry(&claimed, 1)]);
+
+		assert_ok!(Eons::claim_name(Origin::signed(2), eon, claimed.clone(), proof));
+		assert_eq!(Registry::owner(&claimed), Some(Ownership::Account(1)));
+
+		let renewal = FCFS::renewals(claimed.hash()).into_value().expect("renewal set; qed");
+		assert_eq!(renewal.expire_at, 1 + Period::get());
+		assert_eq!(renewal.fee, DefaultFee::get());
+	});
+}
+
+#[test]
+fn claims_name_under_own_ancestor_without_renewal() {
+	new_test_ext().execute_with(|| {
+		let claimed = name(b"www.alice");
+		Registry::set_ownership_unchecked(name(b"alice"), Some(Ownership::Account(1)));
+		let (eon, proof) = push_proven_eon(&[name_entry(&claimed, 1)]);
+
+		assert_ok!(Eons::claim_name(Origin::signed(1), eon, claimed.clone(), proof));
+		assert_eq!(Registry::owner(&claimed), Some(Ownership::Account(1)));
+		assert!(FCFS::renewals(claimed.hash()).is_none());
+	});
+}
+
+#[test]
+fn rejects_name_with_ancestor_owned_by_other() {
+	new_test_ext().execute_with(|| {
+		let claimed = name(b"www.alice.neatuser");
+		Registry::set_ownership_unchecked(name(b"neatuser"), Some(Ownership::FCFS));
+		Registry::set_ownership_unchecked(name(b"alice.neatuser"), Some(Ownership::Account(2)));
+		let (eon, proof) = push_proven_eon(&[name_entry(&claimed, 1)]);
+
+		assert_noop!(
+			Eons::claim_name(Origin::signed(1), eon, claimed.clone(), proof.clone()),
+			Error::<Test>::AncestorOwned,
+		);
+
+		// Registrar names only register their direct children.
+		Registry::set_ownership_unchecked(name(b"alice.neatuser"), None);
+		assert_noop!(
+			Eons::claim_name(Origin::signed(1), eon, claimed, proof),
+			Error::<Test>::AncestorOwned,
+		);
+	});
+}
+
+#[test]
+fn rejects_occupied_name() {
+	new_test_ext().execute_with(|| {
+		let claimed = name(b"alice");
+		Registry::set_ownership_unchecked(claimed.clone(), Some(Ownership::Account(2)));
+		let (eon, proof) = push_proven_eon(&[name_entry(&claimed, 1)]);
+
+		assert_noop!(
+			Eons::claim_name(Origin::signed(1), eon, claimed, proof),
+			Error::<Test>::NameOccupied,
+		);
+	});
+}
@@ -25,7 +25,9 @@ mod default_weights;
 use codec::{Decode, Encode};
 use frame_support::{
 	ensure,
-	traits::{Currency, ExistenceRequirement, Get, OnUnbalanced, WithdrawReasons},
+	traits::{
+		Currency, ExistenceRequirement, Get, OnUnbalanced, StorageVersion, WithdrawReasons,
+	},
 	weights::Weight,
 };
 use frame_system::ensure_signed;
@@ -51,6 +53,16 @@ pub struct RenewalInfo<BlockNumber, Balance> {
 	pub fee: Balance,
 }
 
+/// First-come-first-served registrar, for pallets assigning its names
+/// outside of `register`.
+pub trait Registrar<Ownership> {
+	/// Ownership of the names the registrar registers children of.
+	fn ownership() -> Ownership;
+	/// Start the registration period of a name already assigned to its
+	/// owner, renewable at the current fee.
+	fn start_period(name: Name);
+}
+
 pub trait WeightInfo {
 	fn register() -> Weight;
 	fn renew() -> Weight;
@@ -58,6 +70,9 @@ pub trait WeightInfo {
 	fn set_fee() -> Weight;
 }
 
+/// Current storage version of the pallet.
+pub const STORAGE_VERSION: StorageVersion = StorageVersion::new(1);
+
 #[frame_support::pallet]
 pub mod pallet {
 	use super::*;
@@ -80,9 +95,11 @@ pub mod pallet {
 
 	#[pallet::pallet]
 	#[pallet::generate_store(pub(super) trait Store)]
+	#[pallet::storage_version(STORAGE_VERSION)]
 	pub struct Pallet<T>(_);
 
 	#[pallet::storage]
+	#[pallet::getter(fn renewals)]
 	pub(super) type Renewals<T: Config> = StorageMap<
 		_,
 		Identity,
@@ -95,6 +112,26 @@ pub mod pallet {
 	#[pallet::getter(fn key)]
 	pub(super) type Fee<T: Config> = StorageValue<_, BalanceOf<T>, ValueQuery>;
 
+	/// Registration fee set at genesis. Zero falls back to `DefaultFee`.
+	#[pallet::genesis_config]
+	pub struct GenesisConfig<T: Config> {
+		pub fee: BalanceOf<T>,
+	}
+
+	#[cfg(feature = "std")]
+	impl<T: Config> Default for GenesisConfig<T> {
+		fn default() -> Self {
+			Self { fee: Default::default() }
+		}
+	}
+
+	#[pallet::genesis_build]
+	impl<T: Config> GenesisBuild<T> for GenesisConfig<T> {
+		fn build(&self) {
+			Fee::<T>::put(self.fee);
+		}
+	}
+
 	#[pallet::event]
 	#[pallet::generate_deposit(pub(super) fn deposit_event)]
 	pub enum Event<T: Config> {
@@ -235,3 +272,17 @@ pub mod pallet {
 		}
 	}
 }
+
+impl<T: Config> Registrar<T::Ownership> for Pallet<T> {
+	fn ownership() -> T::Ownership {
+		T::FCFSOwnership::get()
+	}
+
+	fn start_period(name: Name) {
+		let expire_at = frame_system::Pallet::<T>::block_number() + T::Period::get();
+		let info = RenewalInfo { fee: Self::fee(), expire_at };
+
+		Renewals::<T>::insert(name.hash(), NameValue::some(name.clone(), info));
+		Self::deposit_event(Event::<T>::Registered(name, expire_at));
+	}
+}
@@ -0,0 +1,16 @@
+address 0x1 {
+
+/// Access to NEAT balances. Backed by `pallet_balances`.
+module NeatCoin {
+    /// Transfer was rejected, for example because of insufficient balance.
+    const ETRANSFER_FAILED: u64 = 1;
+    /// Amount does not fit in a balance.
+    const EINVALID_AMOUNT: u64 = 2;
+
+    /// Free balance of an account.
+    native public fun balance(addr: address): u128;
+
+    /// Transfer `amount` from the signer to `to`, keeping the signer alive.
+    native public fun transfer(from: &signer, to: address, amount: u128);
+}
+}
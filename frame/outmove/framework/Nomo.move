@@ -0,0 +1,23 @@
+address 0x1 {
+
+/// Read access to the Nomo registry and zones. Names are dotted, such as
+/// `b"example.neatuser"`.
+module Nomo {
+    /// Argument is not a valid dotted name.
+    const EINVALID_NAME: u64 = 3;
+
+    /// Account owning the name. The first value is `false` if the name is not
+    /// owned by an account.
+    native public fun owner(name: vector<u8>): (bool, address);
+
+    /// IPv4 addresses of the name's A record.
+    native public fun a(name: vector<u8>): vector<u64>;
+
+    /// IPv6 addresses of the name's AAAA record.
+    native public fun aaaa(name: vector<u8>): vector<u128>;
+
+    /// Target of the name's CNAME record. The first value is `false` if there
+    /// is none.
+    native public fun cname(name: vector<u8>): (bool, vector<u8>);
+}
+}
@@ -19,8 +19,16 @@
 // Ensure we're `no_std` when compiling for Wasm.
 #![cfg_attr(not(feature = "std"), no_std)]
 
-use frame_support::{decl_error, decl_event, decl_module, decl_storage, ensure};
-use frame_system::ensure_signed;
+pub mod natives;
+
+use frame_support::{
+	dispatch::DispatchResult,
+	ensure,
+	traits::{Currency, Get, ReservableCurrency, StorageVersion},
+	transactional,
+	weights::Weight,
+};
+use frame_system::{ensure_root, ensure_signed};
 use omv::{
 	core::errors::{PartialVMError, PartialVMResult, VMResult},
 	core::file_format::{
@@ -40,75 +48,145 @@ use omv::{
 	runtime::logging::NoContextLog,
 	types::gas_schedule::{self, CostStrategy, NativeCostIndex as N},
 };
-use sp_runtime::AccountId32;
-use sp_std::{borrow::ToOwned, prelude::*};
+use pallet_registry::{Ownership, Registry};
+use sp_runtime::{traits::Saturating, AccountId32};
+use sp_std::{borrow::ToOwned, marker::PhantomData, prelude::*};
 
-pub trait Config: frame_system::Config<AccountId = AccountId32> {
-	type Event: From<Event> + Into<<Self as frame_system::Config>::Event>;
-}
+pub use pallet::*;
+
+/// Current storage version of the pallet.
+pub const STORAGE_VERSION: StorageVersion = StorageVersion::new(1);
+
+pub type BalanceOf<T> =
+	<<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
+
+/// Base weight of publishing a module, besides storage access.
+const PUBLISH_BASE_WEIGHT: Weight = 20_000_000;
+/// Weight of publishing a module per byte of module data.
+const PUBLISH_WEIGHT_PER_BYTE: Weight = 2_000;
 
 pub type RawIdentifier = Vec<u8>;
 pub type RawStructTag = Vec<u8>;
 pub type RawArgument = Vec<u8>;
 pub type RawTypeTag = Vec<u8>;
 
-decl_storage! {
-	trait Store for Module<T: Config> as Outmove {
-		Modules: double_map hasher(blake2_128_concat) AccountId32, hasher(blake2_128_concat) RawIdentifier => Option<Vec<u8>>;
-		Resources: double_map hasher(blake2_128_concat) AccountId32, hasher(blake2_128_concat) RawStructTag => Option<Vec<u8>>;
-	}
-}
-
-decl_error! {
-	pub enum Error for Module<T: Config> {
-		InvalidModuleIdentifier,
-		InvalidTransactionArgument,
-		GasBudgetTooHigh,
-		RunScriptFailed,
-	}
-}
+#[frame_support::pallet]
+pub mod pallet {
+	use super::*;
+	use frame_support::pallet_prelude::*;
+	use frame_system::pallet_prelude::*;
 
-decl_event!(
-	pub enum Event {
-		Dummy,
+	#[pallet::config]
+	pub trait Config: frame_system::Config<AccountId = AccountId32> {
+		/// Currency backing the `NeatCoin` native module, and used for storage
+		/// deposits.
+		type Currency: ReservableCurrency<Self::AccountId>;
+		/// Deposit reserved from the publisher per byte of module data.
+		#[pallet::constant]
+		type ModuleDepositPerByte: Get<BalanceOf<Self>>;
+		/// Deposit reserved from the resource owner per byte of resource data.
+		#[pallet::constant]
+		type ResourceDepositPerByte: Get<BalanceOf<Self>>;
+		/// Maximum size of a published module.
+		#[pallet::constant]
+		type MaxModuleSize: Get<u32>;
+		/// Maximum size of a single resource.
+		#[pallet::constant]
+		type MaxResourceSize: Get<u32>;
+		type Ownership: Ownership<AccountId = Self::AccountId>;
+		/// Registry read by the `Nomo` native module.
+		type Registry: Registry<Ownership = Self::Ownership>;
+		/// Zone records read by the `Nomo` native module.
+		type Zone: pallet_zone::Zone;
+		type Event: From<Event> + IsType<<Self as frame_system::Config>::Event>;
 	}
-);
 
-decl_module! {
-	pub struct Module<T: Config> for enum Call where origin: T::Origin {
-		type Error = Error<T>;
+	#[pallet::pallet]
+	#[pallet::generate_store(pub(super) trait Store)]
+	#[pallet::storage_version(STORAGE_VERSION)]
+	pub struct Pallet<T>(_);
 
-		fn deposit_event() = default;
-
-		#[weight = 0]
-		fn publish(origin, identifier_raw: Vec<u8>, module_data: Vec<u8>) {
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		#[pallet::weight(0)]
+		#[transactional]
+		pub fn publish(
+			origin: OriginFor<T>,
+			identifier_raw: Vec<u8>,
+			module_data: Vec<u8>,
+		) -> DispatchResult {
 			let account_id = ensure_signed(origin)?;
-			let identifier = Identifier::from_utf8(identifier_raw.clone()).map_err(|_| Error::<T>::InvalidModuleIdentifier)?;
-			ensure!(Identifier::is_valid(identifier.as_str()), Error::<T>::InvalidModuleIdentifier);
+			Self::ensure_valid_module(&identifier_raw, &module_data)?;
 
 			// TODO: reject backward-incompatible publishing.
 
-			Modules::insert(account_id, identifier_raw, module_data);
+			let deposit = Self::deposit_for(T::ModuleDepositPerByte::get(), &module_data);
+			Self::update_deposit(
+				&account_id,
+				ModuleDeposits::<T>::get(&account_id, &identifier_raw),
+				deposit,
+			)?;
+
+			ModuleDeposits::<T>::insert(&account_id, &identifier_raw, deposit);
+			Modules::<T>::insert(account_id, identifier_raw, module_data);
+			Ok(())
+		}
+
+		/// Publish a module under an arbitrary address, such as the framework
+		/// modules declaring the Neatcoin natives.
+		#[pallet::weight(
+			T::DbWeight::get()
+				.reads_writes(2, 3)
+				.saturating_add(PUBLISH_BASE_WEIGHT)
+				.saturating_add(PUBLISH_WEIGHT_PER_BYTE.saturating_mul(module_data.len() as Weight))
+		)]
+		pub fn force_publish(
+			origin: OriginFor<T>,
+			address: AccountId32,
+			identifier_raw: Vec<u8>,
+			module_data: Vec<u8>,
+		) -> DispatchResult {
+			ensure_root(origin)?;
+			Self::ensure_valid_module(&identifier_raw, &module_data)?;
+
+			// Modules published by root carry no deposit. Refund any deposit
+			// previously paid for the module being replaced.
+			let deposit = ModuleDeposits::<T>::take(&address, &identifier_raw);
+			T::Currency::unreserve(&address, deposit);
+
+			Modules::<T>::insert(address, identifier_raw, module_data);
+			Ok(())
 		}
 
-		#[weight = 0]
-		fn run(origin, script: Vec<u8>, type_args: Vec<RawTypeTag>, raw_args: Vec<RawArgument>, gas_budget: Option<u64>) {
+		#[pallet::weight(0)]
+		#[transactional]
+		pub fn run(
+			origin: OriginFor<T>,
+			script: Vec<u8>,
+			type_args: Vec<RawTypeTag>,
+			raw_args: Vec<RawArgument>,
+			gas_budget: Option<u64>,
+		) -> DispatchResult {
 			let account_id = ensure_signed(origin)?;
 
 			let mut typs = Vec::<TypeTag>::new();
 			for typ in type_args {
-				typs.push(omv::serialize::from_bytes(&typ).map_err(|_| Error::<T>::InvalidTransactionArgument)?);
+				typs.push(
+					omv::serialize::from_bytes(&typ)
+						.map_err(|_| Error::<T>::InvalidTransactionArgument)?,
+				);
 			}
 
 			let mut signer_addresses = Vec::new();
 			signer_addresses.push(AccountAddress::new(account_id.into()));
 
-			let mut vm = omv::runtime::move_vm::MoveVM::new();
+			let mut vm =
+				omv::runtime::move_vm::MoveVM::new_with_natives(natives::native_functions::<T>());
 			let table = genesis_gas_schedule();
 			let mut cost_strategy = Self::get_cost_strategy(&table, gas_budget)?;
 			let log_context = NoContextLog::new();
 
-			let mut session = vm.new_session(&Self(core::marker::PhantomData));
+			let mut session = vm.new_session(&MoveStorage::<T>(PhantomData));
 			let res = session.execute_script(
 				script,
 				typs,
@@ -120,24 +198,181 @@ decl_module! {
 
 			ensure!(res.is_ok(), Error::<T>::RunScriptFailed);
 
-			let (changeset, _) = session.finish().map_err(|_| Error::<T>::RunScriptFailed)?; // This is currently okay as we haven't committed any events.
+			let (changeset, events) = session.finish().map_err(|_| Error::<T>::RunScriptFailed)?;
 
+			let mut writes = Vec::new();
 			for (addr, account) in changeset.accounts {
 				for (struct_tag, blob_opt) in account.resources {
 					let address = AccountId32::new(addr.to_u8());
-					let tag = &omv::serialize::to_bytes(&struct_tag).unwrap(); // TODO: handle this error.
+					let tag = omv::serialize::to_bytes(&struct_tag).unwrap(); // TODO: handle this error.
 
-					match blob_opt {
-						Some(blob) => Resources::insert(address, tag, blob),
-						None => Resources::remove(address, tag),
+					if let Some(blob) = &blob_opt {
+						ensure!(
+							blob.len() <= T::MaxResourceSize::get() as usize,
+							Error::<T>::ResourceTooLarge
+						);
 					}
+
+					writes.push((address, tag, blob_opt));
 				}
 			}
+
+			for (address, tag, blob_opt) in writes {
+				let old_deposit = ResourceDeposits::<T>::get(&address, &tag);
+
+				match blob_opt {
+					Some(blob) => {
+						let deposit = Self::deposit_for(T::ResourceDepositPerByte::get(), &blob);
+						Self::update_deposit(&address, old_deposit, deposit)?;
+
+						ResourceDeposits::<T>::insert(&address, &tag, deposit);
+						Resources::<T>::insert(address, tag, blob);
+					},
+					None => {
+						T::Currency::unreserve(&address, old_deposit);
+
+						ResourceDeposits::<T>::remove(&address, &tag);
+						Resources::<T>::remove(address, tag);
+					},
+				}
+			}
+
+			for (key, sequence_number, type_tag, layout, value) in events {
+				let tag = omv::serialize::to_bytes(&type_tag)
+					.map_err(|_| Error::<T>::RunScriptFailed)?;
+				let data = value.simple_serialize(&layout).ok_or(Error::<T>::RunScriptFailed)?;
+
+				Self::deposit_event(Event::Emitted(key, sequence_number, tag, data));
+			}
+
+			Ok(())
+		}
+	}
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event {
+		/// An event emitted by a Move script. Contains the event key, the
+		/// sequence number, the serialized type tag and the serialized value.
+		Emitted(Vec<u8>, u64, RawTypeTag, Vec<u8>),
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		InvalidModuleIdentifier,
+		InvalidTransactionArgument,
+		GasBudgetTooHigh,
+		RunScriptFailed,
+		/// Module data is larger than `MaxModuleSize`.
+		ModuleTooLarge,
+		/// A resource written by the script is larger than `MaxResourceSize`.
+		ResourceTooLarge,
+	}
+
+	#[pallet::storage]
+	pub(super) type Modules<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		AccountId32,
+		Blake2_128Concat,
+		RawIdentifier,
+		Vec<u8>,
+		OptionQuery,
+	>;
+
+	#[pallet::storage]
+	pub(super) type Resources<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		AccountId32,
+		Blake2_128Concat,
+		RawStructTag,
+		Vec<u8>,
+		OptionQuery,
+	>;
+
+	#[pallet::storage]
+	pub(super) type ModuleDeposits<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		AccountId32,
+		Blake2_128Concat,
+		RawIdentifier,
+		BalanceOf<T>,
+		ValueQuery,
+	>;
+
+	#[pallet::storage]
+	pub(super) type ResourceDeposits<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		AccountId32,
+		Blake2_128Concat,
+		RawStructTag,
+		BalanceOf<T>,
+		ValueQuery,
+	>;
+
+	/// Modules to publish at genesis without a deposit, by address and
+	/// identifier. These are usually the framework modules declaring the
+	/// Neatcoin natives, as compiled by `scripts/build-framework.sh`.
+	#[pallet::genesis_config]
+	#[derive(Default)]
+	pub struct GenesisConfig {
+		pub modules: Vec<(AccountId32, RawIdentifier, Vec<u8>)>,
+	}
+
+	#[pallet::genesis_build]
+	impl<T: Config> GenesisBuild<T> for GenesisConfig {
+		fn build(&self) {
+			for (address, identifier_raw, module_data) in &self.modules {
+				Pallet::<T>::ensure_valid_module(identifier_raw, module_data)
+					.expect("invalid genesis module");
+				Modules::<T>::insert(address, identifier_raw, module_data);
+			}
 		}
 	}
 }
 
-impl<T: Config> Module<T> {
+/// Move VM view of the module and resource storage.
+struct MoveStorage<T>(PhantomData<T>);
+
+impl<T: Config> Pallet<T> {
+	fn ensure_valid_module(identifier_raw: &[u8], module_data: &[u8]) -> DispatchResult {
+		let identifier = Identifier::from_utf8(identifier_raw.to_vec())
+			.map_err(|_| Error::<T>::InvalidModuleIdentifier)?;
+		ensure!(
+			Identifier::is_valid(identifier.as_str()),
+			Error::<T>::InvalidModuleIdentifier
+		);
+		ensure!(
+			module_data.len() <= T::MaxModuleSize::get() as usize,
+			Error::<T>::ModuleTooLarge
+		);
+
+		Ok(())
+	}
+
+	fn deposit_for(per_byte: BalanceOf<T>, data: &[u8]) -> BalanceOf<T> {
+		per_byte.saturating_mul((data.len() as u32).into())
+	}
+
+	/// Reserve or unreserve the difference between the deposit already held
+	/// for an item and the deposit it now requires.
+	fn update_deposit(
+		who: &AccountId32,
+		old_deposit: BalanceOf<T>,
+		new_deposit: BalanceOf<T>,
+	) -> DispatchResult {
+		if new_deposit > old_deposit {
+			T::Currency::reserve(who, new_deposit - old_deposit)?;
+		} else if old_deposit > new_deposit {
+			T::Currency::unreserve(who, old_deposit - new_deposit);
+		}
+
+		Ok(())
+	}
+
 	fn get_cost_strategy(
 		table: &CostTable,
 		gas_budget: Option<u64>,
@@ -158,12 +393,12 @@ impl<T: Config> Module<T> {
 	}
 }
 
-impl<T: Config> RemoteCache for Module<T> {
+impl<T: Config> RemoteCache for MoveStorage<T> {
 	fn get_module(&self, module_id: &ModuleId) -> VMResult<Option<Vec<u8>>> {
 		let address = AccountId32::new(module_id.address().to_u8());
 		let identifier = module_id.name().to_owned().into_bytes();
 
-		Ok(Modules::get(&address, &identifier))
+		Ok(Modules::<T>::get(&address, &identifier))
 	}
 
 	fn get_resource(
@@ -175,7 +410,7 @@ impl<T: Config> RemoteCache for Module<T> {
 		let tag = &omv::serialize::to_bytes(struct_tag)
 			.map_err(|_| PartialVMError::new(StatusCode::STORAGE_ERROR))?;
 
-		Ok(Resources::get(&address, &tag))
+		Ok(Resources::<T>::get(&address, &tag))
 	}
 }
 
@@ -310,10 +545,12 @@ fn genesis_gas_schedule() -> CostTable {
 			(N::EMIT_EVENT, GasCost::new(52, 1)),
 		];
 		native_table.sort_by_key(|cost| cost.0 as u64);
-		let raw_native_table = native_table
+		let mut raw_native_table = native_table
 			.into_iter()
 			.map(|(_, cost)| cost)
 			.collect::<Vec<_>>();
+		// Neatcoin natives are indexed right after the standard library ones.
+		raw_native_table.extend(natives::native_costs());
 		gas_schedule::new_from_instructions(instrs, raw_native_table)
 	}
 }
@@ -0,0 +1,307 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// This file is part of Neatcoin.
+//
+// Copyright (c) 2021 Wei Tang.
+//
+// Neatcoin is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Neatcoin is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Neatcoin. If not, see <http://www.gnu.org/licenses/>.
+
+//! Neatcoin-specific native functions for the Move VM.
+//!
+//! The natives are declared by the `0x1::NeatCoin` and `0x1::Nomo` modules
+//! found in the `framework` directory of this crate. They are compiled by
+//! `scripts/build-framework.sh`, and published either at genesis through the
+//! `modules` of the pallet genesis config, or later with `force_publish`.
+//! Their costs are taken from the native gas table, right after the Move
+//! standard library entries.
+
+use crate::Config;
+use frame_support::traits::{Currency, ExistenceRequirement};
+use np_domain::Name;
+use omv::{
+	core::errors::{PartialVMError, PartialVMResult},
+	primitives::{
+		account_address::AccountAddress,
+		gas_schedule::{AbstractMemorySize, GasAlgebra, GasCarrier, GasCost, GasUnits},
+		identifier::Identifier,
+		language_storage::CORE_CODE_ADDRESS,
+		vm_status::StatusCode,
+	},
+	runtime::native_functions::{NativeFunction, NativeFunctionTable},
+	types::{
+		gas_schedule::NativeCostIndex as N,
+		loaded_data::runtime_types::Type,
+		natives::function::{NativeContext, NativeResult},
+		values::{Reference, SignerRef, Value},
+	},
+};
+use pallet_registry::{Ownership, Registry};
+use pallet_zone::Zone;
+use sp_runtime::AccountId32;
+use sp_std::{
+	collections::vec_deque::VecDeque,
+	convert::{TryFrom, TryInto},
+	prelude::*,
+};
+
+/// Address the Neatcoin framework modules are published at.
+pub const FRAMEWORK_ADDRESS: AccountAddress = CORE_CODE_ADDRESS;
+
+/// Native gas table indexes of the Neatcoin natives.
+pub mod cost {
+	use super::N;
+
+	pub const NEATCOIN_BALANCE: u8 = N::EMIT_EVENT as u8 + 1;
+	pub const NEATCOIN_TRANSFER: u8 = NEATCOIN_BALANCE + 1;
+	pub const NOMO_OWNER: u8 = NEATCOIN_TRANSFER + 1;
+	pub const NOMO_A: u8 = NOMO_OWNER + 1;
+	pub const NOMO_AAAA: u8 = NOMO_A + 1;
+	pub const NOMO_CNAME: u8 = NOMO_AAAA + 1;
+}
+
+/// Abort code when the transfer is rejected by the currency.
+pub const ETRANSFER_FAILED: u64 = 1;
+/// Abort code when the amount does not fit in a balance.
+pub const EINVALID_AMOUNT: u64 = 2;
+/// Abort code when the argument is not a valid dotted name.
+pub const EINVALID_NAME: u64 = 3;
+
+/// Gas costs of the Neatcoin natives, in native gas table order.
+pub fn native_costs() -> Vec<GasCost> {
+	vec![
+		GasCost::new(150, 1), // NEATCOIN_BALANCE
+		GasCost::new(900, 1), // NEATCOIN_TRANSFER
+		GasCost::new(150, 1), // NOMO_OWNER
+		GasCost::new(150, 1), // NOMO_A
+		GasCost::new(150, 1), // NOMO_AAAA
+		GasCost::new(150, 1), // NOMO_CNAME
+	]
+}
+
+/// All Neatcoin natives, to be registered with the Move VM.
+pub fn native_functions<T: Config>() -> NativeFunctionTable {
+	let natives: [(&str, &str, NativeFunction); 6] = [
+		("NeatCoin", "balance", native_balance::<T>),
+		("NeatCoin", "transfer", native_transfer::<T>),
+		("Nomo", "owner", native_owner::<T>),
+		("Nomo", "a", native_a::<T>),
+		("Nomo", "aaaa", native_aaaa::<T>),
+		("Nomo", "cname", native_cname::<T>),
+	];
+
+	natives
+		.iter()
+		.map(|(module, function, native)| {
+			(
+				FRAMEWORK_ADDRESS,
+				Identifier::new(*module).expect("module name is a valid identifier; qed"),
+				Identifier::new(*function).expect("function name is a valid identifier; qed"),
+				*native,
+			)
+		})
+		.collect()
+}
+
+fn native_gas(context: &NativeContext, index: u8, size: usize) -> GasUnits<GasCarrier> {
+	let cost = &context.cost_table().native_table[index as usize];
+	cost.total().mul(AbstractMemorySize::new(size.max(1) as GasCarrier))
+}
+
+fn pop_arg<V>(arguments: &mut VecDeque<Value>) -> PartialVMResult<V>
+where
+	Value: omv::types::values::VMValueCast<V>,
+{
+	arguments
+		.pop_back()
+		.ok_or_else(|| PartialVMError::new(StatusCode::UNKNOWN_INVARIANT_VIOLATION_ERROR))?
+		.value_as::<V>()
+}
+
+fn to_account(address: &AccountAddress) -> AccountId32 {
+	AccountId32::new(address.to_u8())
+}
+
+fn to_address(account: &AccountId32) -> AccountAddress {
+	AccountAddress::new(account.clone().into())
+}
+
+/// `native public fun balance(addr: address): u128`
+fn native_balance<T: Config>(
+	context: &mut NativeContext,
+	_ty_args: Vec<Type>,
+	mut arguments: VecDeque<Value>,
+) -> PartialVMResult<NativeResult> {
+	let address = pop_arg::<AccountAddress>(&mut arguments)?;
+	let cost = native_gas(context, cost::NEATCOIN_BALANCE, 1);
+
+	let balance = T::Currency::free_balance(&to_account(&address));
+	Ok(NativeResult::ok(
+		cost,
+		vec![Value::u128(balance.try_into().unwrap_or(u128::MAX))],
+	))
+}
+
+/// `native public fun transfer(from: &signer, to: address, amount: u128)`
+fn native_transfer<T: Config>(
+	context: &mut NativeContext,
+	_ty_args: Vec<Type>,
+	mut arguments: VecDeque<Value>,
+) -> PartialVMResult<NativeResult> {
+	let amount = pop_arg::<u128>(&mut arguments)?;
+	let to = pop_arg::<AccountAddress>(&mut arguments)?;
+	let from = pop_arg::<SignerRef>(&mut arguments)?
+		.borrow_signer()?
+		.value_as::<Reference>()?
+		.read_ref()?
+		.value_as::<AccountAddress>()?;
+	let cost = native_gas(context, cost::NEATCOIN_TRANSFER, 1);
+
+	let amount = match crate::BalanceOf::<T>::try_from(amount) {
+		Ok(amount) => amount,
+		Err(_) => return Ok(NativeResult::err(cost, EINVALID_AMOUNT)),
+	};
+
+	match T::Currency::transfer(
+		&to_account(&from),
+		&to_account(&to),
+		amount,
+		ExistenceRequirement::KeepAlive,
+	) {
+		Ok(()) => Ok(NativeResult::ok(cost, vec![])),
+		Err(_) => Ok(NativeResult::err(cost, ETRANSFER_FAILED)),
+	}
+}
+
+/// `native public fun owner(name: vector<u8>): (bool, address)`
+///
+/// Returns `false` if the name is not owned by an account.
+fn native_owner<T: Config>(
+	context: &mut NativeContext,
+	_ty_args: Vec<Type>,
+	mut arguments: VecDeque<Value>,
+) -> PartialVMResult<NativeResult> {
+	let raw = pop_arg::<Vec<u8>>(&mut arguments)?;
+	let cost = native_gas(context, cost::NOMO_OWNER, raw.len());
+
+	let name = match Name::from_dotted(&raw) {
+		Some(name) => name,
+		None => return Ok(NativeResult::err(cost, EINVALID_NAME)),
+	};
+
+	let owner = T::Registry::owner(&name)
+		.as_ref()
+		.and_then(|ownership| ownership.as_account().map(to_address));
+	let values = match owner {
+		Some(address) => vec![Value::bool(true), Value::address(address)],
+		None => vec![Value::bool(false), Value::address(AccountAddress::ZERO)],
+	};
+
+	Ok(NativeResult::ok(cost, values))
+}
+
+/// `native public fun a(name: vector<u8>): vector<u64>`
+fn native_a<T: Config>(
+	context: &mut NativeContext,
+	_ty_args: Vec<Type>,
+	mut arguments: VecDeque<Value>,
+) -> PartialVMResult<NativeResult> {
+	let raw = pop_arg::<Vec<u8>>(&mut arguments)?;
+	let cost = native_gas(context, cost::NOMO_A, raw.len());
+
+	let name = match Name::from_dotted(&raw) {
+		Some(name) => name,
+		None => return Ok(NativeResult::err(cost, EINVALID_NAME)),
+	};
+
+	let record = T::Zone::a(&name).into_iter().map(u64::from).collect::<Vec<_>>();
+	Ok(NativeResult::ok(cost, vec![Value::vector_u64(record)]))
+}
+
+/// `native public fun aaaa(name: vector<u8>): vector<u128>`
+fn native_aaaa<T: Config>(
+	context: &mut NativeContext,
+	_ty_args: Vec<Type>,
+	mut arguments: VecDeque<Value>,
+) -> PartialVMResult<NativeResult> {
+	let raw = pop_arg::<Vec<u8>>(&mut arguments)?;
+	let cost = native_gas(context, cost::NOMO_AAAA, raw.len());
+
+	let name = match Name::from_dotted(&raw) {
+		Some(name) => name,
+		None => return Ok(NativeResult::err(cost, EINVALID_NAME)),
+	};
+
+	Ok(NativeResult::ok(
+		cost,
+		vec![Value::vector_u128(T::Zone::aaaa(&name))],
+	))
+}
+
+/// `native public fun cname(name: vector<u8>): (bool, vector<u8>)`
+fn native_cname<T: Config>(
+	context: &mut NativeContext,
+	_ty_args: Vec<Type>,
+	mut arguments: VecDeque<Value>,
+) -> PartialVMResult<NativeResult> {
+	let raw = pop_arg::<Vec<u8>>(&mut arguments)?;
+	let cost = native_gas(context, cost::NOMO_CNAME, raw.len());
+
+	let name = match Name::from_dotted(&raw) {
+		Some(name) => name,
+		None => return Ok(NativeResult::err(cost, EINVALID_NAME)),
+	};
+
+	let values = match T::Zone::cname(&name) {
+		Some(target) => vec![Value::bool(true), Value::vector_u8(target.to_dotted())],
+		None => vec![Value::bool(false), Value::vector_u8(Vec::new())],
+	};
+
+	Ok(NativeResult::ok(cost, values))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn native_costs_follow_gas_table_indexes() {
+		let costs = native_costs();
+		assert_eq!(costs.len(), 6);
+		assert_eq!(cost::NEATCOIN_BALANCE, N::EMIT_EVENT as u8 + 1);
+		assert_eq!(
+			cost::NOMO_CNAME as usize,
+			cost::NEATCOIN_BALANCE as usize + costs.len() - 1
+		);
+	}
+
+	#[test]
+	fn addresses_round_trip() {
+		let account = AccountId32::new([7u8; 32]);
+		assert_eq!(to_account(&to_address(&account)), account);
+
+		let address = AccountAddress::new([9u8; 32]);
+		assert_eq!(to_address(&to_account(&address)), address);
+	}
+
+	#[test]
+	fn name_arguments_round_trip() {
+		for raw in [&b"example.neatuser"[..], b"www.example.neatuser."] {
+			let name = Name::from_dotted(raw).unwrap();
+			assert_eq!(Name::from_dotted(&name.to_dotted()), Some(name));
+		}
+
+		for raw in [&b"Example.neatuser"[..], b"example..neatuser", b"-x.neatuser"] {
+			assert_eq!(Name::from_dotted(raw), None);
+		}
+	}
+}
@@ -0,0 +1,38 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// This file is part of Neatcoin.
+//
+// Copyright (c) 2021 Wei Tang.
+//
+// Neatcoin is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Neatcoin is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Neatcoin. If not, see <http://www.gnu.org/licenses/>.
+
+//! Runtime API definition for the registry pallet.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+use codec::Codec;
+use np_domain::{Label, Name};
+use sp_std::prelude::*;
+
+sp_api::decl_runtime_apis! {
+	/// The API to enumerate owned names.
+	pub trait RegistryApi<Ownership> where
+		Ownership: Codec,
+	{
+		/// Owned children of `parent` with their ownership, ordered by label
+		/// hash. At most `limit` children are returned, starting after the
+		/// child labelled `start_after`. Pass the label of the last child of
+		/// a page to get the next one.
+		fn children(parent: Name, start_after: Option<Label>, limit: u32) -> Vec<(Name, Ownership)>;
+	}
+}
@@ -35,8 +35,9 @@ use sp_std::marker::PhantomData;
 
 impl crate::WeightInfo for () {
 	// Storage: Registry Ownerships (r:0 w:1)
+	// Storage: Registry Children (r:0 w:1)
 	fn force_set_ownership() -> Weight {
 		(20_400_000 as Weight)
-			.saturating_add(DbWeight::get().writes(1 as Weight))
+			.saturating_add(DbWeight::get().writes(2 as Weight))
 	}
 }
@@ -21,18 +21,37 @@
 
 mod benchmarking;
 mod default_weights;
+pub mod migrations;
+#[cfg(test)]
+mod mock;
+#[cfg(test)]
+mod tests;
 
 use codec::{Decode, Encode, EncodeLike};
-use frame_support::{dispatch::DispatchResult, ensure, weights::Weight};
+use frame_support::{
+	dispatch::DispatchResult, ensure, storage::StoragePrefixedMap, traits::StorageVersion,
+	weights::Weight,
+};
 use frame_system::ensure_root;
-use np_domain::{Name, NameHash, NameValue};
+use np_domain::{Label, LabelHash, Name, NameHash, NameValue};
 use scale_info::TypeInfo;
+#[cfg(feature = "std")]
+use serde::{Deserialize, Serialize};
+use sp_runtime::{traits::MaybeSerializeDeserialize, RuntimeDebug};
 use sp_std::{fmt::Debug, prelude::*};
 
 pub use pallet::*;
 
 pub trait Ownership:
-	Encode + Decode + EncodeLike + Default + Eq + Debug + Clone + TypeInfo
+	Encode
+	+ Decode
+	+ EncodeLike
+	+ Default
+	+ Eq
+	+ Debug
+	+ Clone
+	+ TypeInfo
+	+ MaybeSerializeDeserialize
 {
 	type AccountId;
 
@@ -40,6 +59,45 @@ pub trait Ownership:
 	fn root() -> Self;
 	/// Owned by a specific account.
 	fn account(account: Self::AccountId) -> Self;
+	/// The account this ownership belongs to, if it is owned by an account.
+	fn as_account(&self) -> Option<&Self::AccountId>;
+}
+
+/// Ownership of names in the Nomo runtimes.
+#[derive(Encode, Decode, Clone, RuntimeDebug, PartialEq, Eq, TypeInfo)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub enum NomoOwnership<AccountId> {
+	None,
+	Root,
+	FCFS,
+	Account(AccountId),
+}
+
+impl<AccountId> Default for NomoOwnership<AccountId> {
+	fn default() -> Self {
+		Self::None
+	}
+}
+
+impl<AccountId> Ownership for NomoOwnership<AccountId>
+where
+	AccountId:
+		Encode + Decode + Eq + Debug + Clone + TypeInfo + MaybeSerializeDeserialize + 'static,
+{
+	type AccountId = AccountId;
+
+	fn root() -> Self {
+		Self::Root
+	}
+	fn account(account: AccountId) -> Self {
+		Self::Account(account)
+	}
+	fn as_account(&self) -> Option<&AccountId> {
+		match self {
+			Self::Account(account) => Some(account),
+			_ => None,
+		}
+	}
 }
 
 pub trait Registry {
@@ -54,6 +112,8 @@ pub trait Registry {
 	fn can_set_ownership(as_ownership: &Self::Ownership, name: &Name) -> bool;
 	fn ensure_can_set_ownership(as_ownership: &Self::Ownership, name: &Name) -> DispatchResult;
 	fn owner(name: &Name) -> Option<Self::Ownership>;
+	/// Raw storage key of the ownership entry of a name.
+	fn ownership_key(name: &Name) -> Vec<u8>;
 
 	fn parent_owner(name: &Name) -> Option<Self::Ownership> {
 		name.parent().and_then(|parent| Self::owner(&parent))
@@ -86,6 +146,12 @@ pub trait WeightInfo {
 	fn force_set_ownership() -> Weight;
 }
 
+/// Current storage version of the pallet.
+pub const STORAGE_VERSION: StorageVersion = StorageVersion::new(2);
+
+/// Maximum number of children returned in one page.
+pub const MAX_CHILDREN_PAGE: u32 = 1024;
+
 #[frame_support::pallet]
 pub mod pallet {
 	use super::*;
@@ -101,6 +167,7 @@ pub mod pallet {
 
 	#[pallet::pallet]
 	#[pallet::generate_store(pub(super) trait Store)]
+	#[pallet::storage_version(STORAGE_VERSION)]
 	pub struct Pallet<T>(_);
 
 	#[pallet::call]
@@ -134,6 +201,90 @@ pub mod pallet {
 	#[pallet::storage]
 	pub(super) type Ownerships<T: Config> =
 		StorageMap<_, Identity, NameHash, NameValue<T::Ownership>, ValueQuery>;
+
+	/// Index of owned names, by the name hash of their parent and their label
+	/// hash. A name is indexed under its direct parent whether or not the
+	/// parent is itself owned.
+	#[pallet::storage]
+	pub(super) type Children<T: Config> =
+		StorageDoubleMap<_, Identity, NameHash, Identity, LabelHash, (), OptionQuery>;
+
+	/// Names to assign at genesis, such as top-level names and their
+	/// registrars. Parent ownership is not checked.
+	#[pallet::genesis_config]
+	pub struct GenesisConfig<T: Config> {
+		pub ownerships: Vec<(Name, T::Ownership)>,
+	}
+
+	#[cfg(feature = "std")]
+	impl<T: Config> Default for GenesisConfig<T> {
+		fn default() -> Self {
+			Self { ownerships: Vec::new() }
+		}
+	}
+
+	#[pallet::genesis_build]
+	impl<T: Config> GenesisBuild<T> for GenesisConfig<T> {
+		fn build(&self) {
+			for (name, ownership) in &self.ownerships {
+				assert!(!name.is_root(), "genesis ownership of the root name");
+
+				Ownerships::<T>::insert(
+					name.hash(),
+					NameValue::some(name.clone(), ownership.clone()),
+				);
+				Pallet::<T>::index_child(name, true);
+			}
+		}
+	}
+}
+
+impl<T: Config> Pallet<T> {
+	/// Add or remove a name from the `Children` index of its parent.
+	pub(crate) fn index_child(name: &Name, owned: bool) {
+		if let (Some(parent), Some(label)) = (name.parent(), name.0.last()) {
+			if owned {
+				Children::<T>::insert(parent.hash(), label.hash(), ());
+			} else {
+				Children::<T>::remove(parent.hash(), label.hash());
+			}
+		}
+	}
+
+	/// Owned children of `parent` with their ownership, ordered by label
+	/// hash. At most `limit` children are returned, capped at
+	/// `MAX_CHILDREN_PAGE`, starting after the child labelled `start_after`.
+	pub fn children(
+		parent: &Name,
+		start_after: Option<&Label>,
+		limit: u32,
+	) -> Vec<(Name, T::Ownership)> {
+		let parent_hash = parent.hash();
+		let mut prefix = Children::<T>::final_prefix().to_vec();
+		prefix.extend_from_slice(parent_hash.as_bytes());
+
+		let mut key = prefix.clone();
+		if let Some(label) = start_after {
+			key.extend_from_slice(label.hash().as_bytes());
+		}
+
+		let limit = limit.min(MAX_CHILDREN_PAGE) as usize;
+		let mut children = Vec::new();
+		while children.len() < limit {
+			key = match sp_io::storage::next_key(&key) {
+				Some(next) if next.starts_with(&prefix) && next.len() == prefix.len() + 32 => next,
+				_ => break,
+			};
+
+			let label = LabelHash::from_slice(&key[prefix.len()..]);
+			let child = Name::child_hash(&parent_hash, &label);
+			if let Some(entry) = Ownerships::<T>::get(child).into_inner() {
+				children.push(entry);
+			}
+		}
+
+		children
+	}
 }
 
 impl<T: Config> Registry for Pallet<T> {
@@ -164,6 +315,7 @@ impl<T: Config> Registry for Pallet<T> {
 		} else {
 			Ownerships::<T>::remove(name.hash());
 		}
+		Self::index_child(&name, ownership.is_some());
 
 		Self::deposit_event(Event::<T>::OwnershipSet(name, ownership));
 	}
@@ -200,4 +352,8 @@ impl<T: Config> Registry for Pallet<T> {
 	fn owner(name: &Name) -> Option<T::Ownership> {
 		Ownerships::<T>::get(&name.hash()).into_value()
 	}
+
+	fn ownership_key(name: &Name) -> Vec<u8> {
+		Ownerships::<T>::hashed_key_for(&name.hash())
+	}
 }
@@ -0,0 +1,113 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// This file is part of Nomo.
+//
+// Copyright (c) 2019-2020 Wei Tang.
+//
+// Nomo is free software: you can redistribute it and/or modify it
+// under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Nomo is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Nomo. If not, see <http://www.gnu.org/licenses/>.
+
+//! Storage migrations for the registry pallet.
+
+use crate::{Config, Ownerships, Pallet};
+use frame_support::{
+	traits::{Get, OnRuntimeUpgrade, StorageVersion},
+	weights::Weight,
+};
+use sp_std::marker::PhantomData;
+#[cfg(feature = "try-runtime")]
+use crate::Children;
+
+/// Build the `Children` index from `Ownerships`, moving to storage version
+/// 2. Does nothing if the pallet is already at version 2 or later.
+///
+/// The index is built in a single block, with one read and one write per
+/// owned name. With RocksDB weights this fits a 2 second block up to about
+/// 16 000 owned names, while Neatcoin and Vodka hold the genesis names and
+/// FCFS registrations, well below that. `pre_upgrade` logs the number of
+/// names and warns if they would not fit in a block.
+pub struct BuildChildren<T>(PhantomData<T>);
+
+impl<T: Config> OnRuntimeUpgrade for BuildChildren<T> {
+	fn on_runtime_upgrade() -> Weight {
+		if StorageVersion::get::<Pallet<T>>() >= StorageVersion::new(2) {
+			return T::DbWeight::get().reads(1)
+		}
+
+		let mut count: Weight = 0;
+		for (_, entry) in Ownerships::<T>::iter() {
+			if let Some(name) = entry.into_name() {
+				Pallet::<T>::index_child(&name, true);
+				count += 1;
+			}
+		}
+		StorageVersion::new(2).put::<Pallet<T>>();
+
+		log::info!(target: "runtime::registry", "Indexed {} owned names", count);
+
+		T::DbWeight::get().reads_writes(1 + count, 1 + count)
+	}
+
+	#[cfg(feature = "try-runtime")]
+	fn pre_upgrade() -> Result<(), &'static str> {
+		if StorageVersion::get::<Pallet<T>>() >= StorageVersion::new(2) {
+			log::info!(target: "runtime::registry", "Children index already built");
+			return Ok(())
+		}
+
+		let count = Ownerships::<T>::iter_keys().count() as Weight;
+		let weight = T::DbWeight::get().reads_writes(1 + count, 1 + count);
+		let max_block = T::BlockWeights::get().max_block;
+		log::info!(
+			target: "runtime::registry",
+			"Indexing {} owned names, using {} of {} block weight",
+			count,
+			weight,
+			max_block,
+		);
+		if weight > max_block {
+			log::warn!(
+				target: "runtime::registry",
+				"Building the children index does not fit in a block",
+			);
+		}
+
+		Ok(())
+	}
+
+	#[cfg(feature = "try-runtime")]
+	fn post_upgrade() -> Result<(), &'static str> {
+		frame_support::ensure!(
+			StorageVersion::get::<Pallet<T>>() >= StorageVersion::new(2),
+			"storage version not updated",
+		);
+
+		let mut owned = 0usize;
+		for (_, entry) in Ownerships::<T>::iter() {
+			if let Some(name) = entry.into_name() {
+				let parent = name.parent().unwrap_or_default();
+				let label = name.0.last().ok_or("ownership of the root name")?;
+				frame_support::ensure!(
+					Children::<T>::contains_key(parent.hash(), label.hash()),
+					"owned name missing from the children index",
+				);
+				owned += 1;
+			}
+		}
+		frame_support::ensure!(
+			Children::<T>::iter().count() == owned,
+			"children index has entries without ownership",
+		);
+
+		Ok(())
+	}
+}
@@ -0,0 +1,144 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// This file is part of Nomo.
+//
+// Copyright (c) 2019-2020 Wei Tang.
+//
+// Nomo is free software: you can redistribute it and/or modify it
+// under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Nomo is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Nomo. If not, see <http://www.gnu.org/licenses/>.
+
+use crate::{migrations::BuildChildren, mock::*, Children, Ownerships, Registry as _};
+use frame_support::{
+	assert_ok,
+	traits::{OnRuntimeUpgrade, StorageVersion},
+};
+use np_domain::{Label, Name, NameValue};
+
+fn name(dotted: &[u8]) -> Name {
+	Name::from_dotted(dotted).expect("valid name; qed")
+}
+
+fn label(raw: &[u8]) -> Label {
+	Label::try_from(raw.to_vec()).expect("valid label; qed")
+}
+
+fn is_indexed(name: &Name) -> bool {
+	let parent = name.parent().expect("not the root name; qed");
+	let label = name.0.last().expect("not the root name; qed");
+	Children::<Test>::contains_key(parent.hash(), label.hash())
+}
+
+#[test]
+fn indexes_children_on_ownership_changes() {
+	new_test_ext().execute_with(|| {
+		let child = name(b"alice.neatuser");
+
+		// Register.
+		assert_ok!(Registry::force_set_ownership(
+			Origin::root(),
+			child.clone(),
+			Some(Ownership::Account(1)),
+		));
+		assert!(is_indexed(&child));
+		assert!(!is_indexed(&name(b"neatuser")));
+
+		// Transfer.
+		assert_ok!(Registry::force_set_ownership(
+			Origin::root(),
+			child.clone(),
+			Some(Ownership::Account(2)),
+		));
+		assert!(is_indexed(&child));
+		assert_eq!(
+			Registry::children(&name(b"neatuser"), None, 10),
+			vec![(child.clone(), Ownership::Account(2))],
+		);
+
+		// Unregister.
+		assert_ok!(Registry::force_set_ownership(Origin::root(), child.clone(), None));
+		assert!(!is_indexed(&child));
+		assert!(Registry::children(&name(b"neatuser"), None, 10).is_empty());
+	});
+}
+
+#[test]
+fn pages_children_by_label_hash() {
+	new_test_ext().execute_with(|| {
+		let parent = name(b"neatuser");
+		let labels = ["a", "b", "c", "d", "e"];
+		for raw in labels {
+			let mut child = parent.clone();
+			child.0.push(label(raw.as_bytes()));
+			Registry::set_ownership_unchecked(child, Some(Ownership::Account(1)));
+		}
+		// Neither grandchildren nor children of other names are listed.
+		Registry::set_ownership_unchecked(name(b"x.a.neatuser"), Some(Ownership::Account(1)));
+		Registry::set_ownership_unchecked(name(b"a.other"), Some(Ownership::Account(1)));
+
+		let all = Registry::children(&parent, None, 10);
+		assert_eq!(all.len(), labels.len());
+		let hashes = all.iter().map(|(child, _)| child.0[1].hash()).collect::<Vec<_>>();
+		let mut sorted = hashes.clone();
+		sorted.sort();
+		assert_eq!(hashes, sorted);
+
+		// Pages of two resume after the last label of the previous page.
+		let mut paged = Vec::new();
+		let mut start_after = None;
+		loop {
+			let page = Registry::children(&parent, start_after.as_ref(), 2);
+			assert!(page.len() <= 2);
+			match page.last() {
+				Some((child, _)) => start_after = child.0.last().cloned(),
+				None => break,
+			}
+			paged.extend(page);
+		}
+		assert_eq!(paged, all);
+
+		assert!(Registry::children(&parent, None, 0).is_empty());
+		let last = all.last().and_then(|(child, _)| child.0.last());
+		assert!(Registry::children(&parent, last, 10).is_empty());
+	});
+}
+
+#[test]
+fn build_children_migration_indexes_owned_names() {
+	new_test_ext().execute_with(|| {
+		let names = vec![name(b"neatuser"), name(b"alice.neatuser"), name(b"www.alice.neatuser")];
+		for owned in &names {
+			Ownerships::<Test>::insert(
+				owned.hash(),
+				NameValue::some(owned.clone(), Ownership::Account(1)),
+			);
+		}
+		StorageVersion::new(1).put::<Registry>();
+
+		BuildChildren::<Test>::on_runtime_upgrade();
+
+		assert_eq!(StorageVersion::get::<Registry>(), StorageVersion::new(2));
+		assert!(names.iter().all(is_indexed));
+		assert_eq!(Children::<Test>::iter().count(), names.len());
+		assert_eq!(
+			Registry::children(&name(b"alice.neatuser"), None, 10),
+			vec![(name(b"www.alice.neatuser"), Ownership::Account(1))],
+		);
+
+		// Already at version 2, so nothing is indexed again.
+		Ownerships::<Test>::insert(
+			name(b"bob.neatuser").hash(),
+			NameValue::some(name(b"bob.neatuser"), Ownership::Account(2)),
+		);
+		BuildChildren::<Test>::on_runtime_upgrade();
+		assert!(!is_indexed(&name(b"bob.neatuser")));
+	});
+}
@@ -0,0 +1,115 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// This file is part of Neatcoin.
+//
+// Copyright (c) 2020 Wei Tang.
+//
+// Neatcoin is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Neatcoin is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Neatcoin. If not, see <http://www.gnu.org/licenses/>.
+
+#![cfg(feature = "runtime-benchmarks")]
+
+use super::*;
+use frame_benchmarking::{benchmarks, whitelisted_caller};
+use frame_support::traits::OnInitialize;
+use frame_system::RawOrigin;
+use sp_std::{convert::TryFrom, prelude::*};
+
+const MAX_CHANGES: u32 = 100;
+
+fn registered_key<T: Config>() -> (KeyOf<T>, Value<T::AccountId>) {
+	let (key, kind) = T::Keys::keys()
+		.into_iter()
+		.next()
+		.expect("benchmarks require a registered key");
+
+	let value = match kind {
+		ValueKind::U32 => Value::U32(u32::MAX),
+		ValueKind::U64 => Value::U64(u64::MAX),
+		ValueKind::U128 => Value::U128(u128::MAX),
+		ValueKind::Bool => Value::Bool(true),
+		ValueKind::Perbill => Value::Perbill(Perbill::one()),
+		ValueKind::Permill => Value::Permill(Permill::one()),
+		ValueKind::AccountId => Value::AccountId(whitelisted_caller()),
+		ValueKind::Bytes => Value::Bytes(vec![0; T::MaxBytesLength::get() as usize]),
+		ValueKind::H256 => Value::H256(H256::repeat_byte(1)),
+	};
+
+	let key = KeyOf::<T>::try_from(key.to_vec())
+		.expect("registered keys are at most MaxKeyLength long");
+	(key, value)
+}
+
+benchmarks! {
+	set_value {
+		let (key, value) = registered_key::<T>();
+	}: _(RawOrigin::Root, key.clone(), value.clone())
+	verify {
+		assert_eq!(Values::<T>::get(key.to_vec()), Some(value));
+	}
+
+	reset_value {
+		let (key, value) = registered_key::<T>();
+		Values::<T>::insert(key.to_vec(), value);
+	}: _(RawOrigin::Root, key.clone())
+	verify {
+		assert_eq!(Values::<T>::get(key.to_vec()), None);
+	}
+
+	set_values {
+		let n in 1 .. MAX_CHANGES;
+
+		let (key, value) = registered_key::<T>();
+		let changes = (0..n).map(|_| (key.clone(), Some(value.clone()))).collect::<Vec<_>>();
+	}: _(RawOrigin::Root, changes)
+	verify {
+		assert_eq!(Values::<T>::get(key.to_vec()), Some(value));
+	}
+
+	schedule_value {
+		let (key, value) = registered_key::<T>();
+		let at = frame_system::Pallet::<T>::block_number() + 10u32.into();
+		let scheduled = (1..T::MaxScheduledPerBlock::get())
+			.map(|_| (key.clone(), Some(value.clone())))
+			.collect::<Vec<_>>();
+		Scheduled::<T>::insert(at, scheduled);
+	}: _(RawOrigin::Root, key, Some(value), at)
+	verify {
+		assert_eq!(Scheduled::<T>::get(at).len() as u32, T::MaxScheduledPerBlock::get());
+	}
+
+	cancel_scheduled {
+		let (key, value) = registered_key::<T>();
+		let at = frame_system::Pallet::<T>::block_number() + 10u32.into();
+		let scheduled = (0..T::MaxScheduledPerBlock::get())
+			.map(|_| (key.clone(), Some(value.clone())))
+			.collect::<Vec<_>>();
+		Scheduled::<T>::insert(at, scheduled);
+	}: _(RawOrigin::Root, key, at)
+	verify {
+		assert!(!Scheduled::<T>::contains_key(at));
+	}
+
+	enact_scheduled {
+		let n in 0 .. T::MaxScheduledPerBlock::get();
+
+		let (key, value) = registered_key::<T>();
+		let at = frame_system::Pallet::<T>::block_number() + 10u32.into();
+		let scheduled = (0..n).map(|_| (key.clone(), Some(value.clone()))).collect::<Vec<_>>();
+		Scheduled::<T>::insert(at, scheduled);
+	}: {
+		Pallet::<T>::on_initialize(at);
+	}
+	verify {
+		assert!(!Scheduled::<T>::contains_key(at));
+	}
+}
@@ -0,0 +1,54 @@
+//! Weights for `pallet_variables`
+//!
+//! NOT YET MEASURED. THESE ARE PLACEHOLDERS FOLLOWING THE STORAGE FOOTPRINT
+//! OF EACH CALL, UNTIL REPLACED BY THE OUTPUT OF `scripts/benchmark.sh`.
+
+
+#![cfg_attr(rustfmt, rustfmt_skip)]
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+
+use frame_support::{traits::Get, weights::{Weight, constants::RocksDbWeight as DbWeight}};
+use sp_std::marker::PhantomData;
+
+impl crate::WeightInfo for () {
+	// Storage: Variables Values (r:0 w:1)
+	fn set_value() -> Weight {
+		(20_000_000 as Weight)
+			.saturating_add(DbWeight::get().writes(1 as Weight))
+	}
+	// Storage: Variables Values (r:0 w:1)
+	fn reset_value() -> Weight {
+		(18_000_000 as Weight)
+			.saturating_add(DbWeight::get().writes(1 as Weight))
+	}
+	// Storage: Variables Values (r:0 w:1)
+	fn set_values(n: u32, ) -> Weight {
+		(5_000_000 as Weight)
+			// Standard Error: 0
+			.saturating_add((16_000_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(DbWeight::get().writes((1 as Weight).saturating_mul(n as Weight)))
+	}
+	// Storage: Variables Scheduled (r:1 w:1)
+	fn schedule_value() -> Weight {
+		(24_000_000 as Weight)
+			.saturating_add(DbWeight::get().reads(1 as Weight))
+			.saturating_add(DbWeight::get().writes(1 as Weight))
+	}
+	// Storage: Variables Scheduled (r:1 w:1)
+	fn cancel_scheduled() -> Weight {
+		(26_000_000 as Weight)
+			.saturating_add(DbWeight::get().reads(1 as Weight))
+			.saturating_add(DbWeight::get().writes(1 as Weight))
+	}
+	// Storage: Variables Scheduled (r:1 w:1)
+	// Storage: Variables Values (r:0 w:1)
+	fn enact_scheduled(n: u32, ) -> Weight {
+		(4_000_000 as Weight)
+			// Standard Error: 0
+			.saturating_add((17_000_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(DbWeight::get().reads(1 as Weight))
+			.saturating_add(DbWeight::get().writes(1 as Weight))
+			.saturating_add(DbWeight::get().writes((1 as Weight).saturating_mul(n as Weight)))
+	}
+}
@@ -17,67 +17,438 @@
 // along with Neatcoin. If not, see <http://www.gnu.org/licenses/>.
 
 //! Variable storage pallet.
+//!
+//! Runtime parameters can be backed by a variable through the [`Variable`]
+//! adapter, which falls back to a compile-time default when the variable is
+//! not set. Only keys registered through [`Config::Keys`] can be set, by the
+//! origin [`Config::KeyOrigin`] configures for the key. Changes can also be
+//! scheduled to take effect at a future block.
 
 #![cfg_attr(not(feature = "std"), no_std)]
 
+mod benchmarking;
+mod default_weights;
+
 use codec::{Decode, Encode};
-use frame_support::{decl_event, decl_module, decl_storage};
-use frame_system::ensure_root;
+use frame_support::{
+	dispatch::DispatchResult,
+	ensure,
+	traits::{Get, StorageVersion},
+	weights::Weight,
+	BoundedVec,
+};
 use scale_info::TypeInfo;
 #[cfg(feature = "std")]
 use serde::{Deserialize, Serialize};
-use sp_core::RuntimeDebug;
-use sp_std::vec::Vec;
+use sp_core::{crypto::AccountId32, RuntimeDebug, H256};
+use sp_runtime::{Perbill, Permill};
+use sp_std::{marker::PhantomData, vec::Vec};
+
+pub use pallet::*;
 
-pub trait Config: frame_system::Config {
-	/// The overarching event type.
-	type Event: From<Event> + Into<<Self as frame_system::Config>::Event>;
+pub trait WeightInfo {
+	fn set_value() -> Weight;
+	fn reset_value() -> Weight;
+	fn set_values(n: u32) -> Weight;
+	fn schedule_value() -> Weight;
+	fn cancel_scheduled() -> Weight;
+	fn enact_scheduled(n: u32) -> Weight;
 }
 
+/// Current storage version of the pallet.
+pub const STORAGE_VERSION: StorageVersion = StorageVersion::new(1);
+
+/// Variable key, of at most `MaxKeyLength` bytes.
+pub type KeyOf<T> = BoundedVec<u8, <T as Config>::MaxKeyLength>;
+
 /// Variable value.
 #[derive(Encode, Decode, Clone, RuntimeDebug, PartialEq, Eq, TypeInfo)]
 #[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
-pub enum Value {
+pub enum Value<AccountId> {
 	U32(u32),
 	U64(u64),
 	U128(u128),
 	Bool(bool),
+	Perbill(Perbill),
+	Permill(Permill),
+	AccountId(AccountId),
+	/// Byte string of at most `MaxBytesLength` bytes.
+	Bytes(Vec<u8>),
+	H256(H256),
+}
+
+/// Kind of a variable value, matching the variants of [`Value`].
+#[derive(Encode, Decode, Clone, Copy, RuntimeDebug, PartialEq, Eq, TypeInfo)]
+pub enum ValueKind {
+	U32,
+	U64,
+	U128,
+	Bool,
+	Perbill,
+	Permill,
+	AccountId,
+	Bytes,
+	H256,
+}
+
+impl<AccountId> Value<AccountId> {
+	/// Kind of the value.
+	pub fn kind(&self) -> ValueKind {
+		match self {
+			Value::U32(_) => ValueKind::U32,
+			Value::U64(_) => ValueKind::U64,
+			Value::U128(_) => ValueKind::U128,
+			Value::Bool(_) => ValueKind::Bool,
+			Value::Perbill(_) => ValueKind::Perbill,
+			Value::Permill(_) => ValueKind::Permill,
+			Value::AccountId(_) => ValueKind::AccountId,
+			Value::Bytes(_) => ValueKind::Bytes,
+			Value::H256(_) => ValueKind::H256,
+		}
+	}
+}
+
+/// A type that can be read from a variable value.
+pub trait FromValue<AccountId>: Sized {
+	/// Kind of value the type is read from.
+	const KIND: ValueKind;
+
+	/// Convert from a value. Returns `None` if the value is of another kind.
+	fn from_value(value: Value<AccountId>) -> Option<Self>;
+}
+
+macro_rules! impl_from_value {
+	( $t:ty, $variant:ident ) => {
+		impl<AccountId> FromValue<AccountId> for $t {
+			const KIND: ValueKind = ValueKind::$variant;
+
+			fn from_value(value: Value<AccountId>) -> Option<Self> {
+				match value {
+					Value::$variant(v) => Some(v),
+					_ => None,
+				}
+			}
+		}
+	};
+}
+
+impl_from_value!(u32, U32);
+impl_from_value!(u64, U64);
+impl_from_value!(u128, U128);
+impl_from_value!(bool, Bool);
+impl_from_value!(Perbill, Perbill);
+impl_from_value!(Permill, Permill);
+impl_from_value!(Vec<u8>, Bytes);
+impl_from_value!(H256, H256);
+
+impl FromValue<AccountId32> for AccountId32 {
+	const KIND: ValueKind = ValueKind::AccountId;
+
+	fn from_value(value: Value<AccountId32>) -> Option<Self> {
+		match value {
+			Value::AccountId(v) => Some(v),
+			_ => None,
+		}
+	}
+}
+
+/// Registered variable keys.
+pub trait VariableKeys {
+	/// All registered keys, with the kind of value they hold.
+	fn keys() -> Vec<(&'static [u8], ValueKind)>;
+
+	/// Kind of value the key holds, or `None` if the key is not registered.
+	fn kind(key: &[u8]) -> Option<ValueKind> {
+		Self::keys()
+			.into_iter()
+			.find(|(registered, _)| *registered == key)
+			.map(|(_, kind)| kind)
+	}
 }
 
-decl_storage! {
-	trait Store for Module<T: Config> as Variables {
-		/// Storage values.
-		pub Values: map hasher(blake2_128_concat) Vec<u8> => Option<Value>;
+impl VariableKeys for () {
+	fn keys() -> Vec<(&'static [u8], ValueKind)> {
+		Vec::new()
 	}
 }
 
-decl_event! {
-	pub enum Event {
+/// Origin allowed to change a variable, depending on its key.
+///
+/// Benchmarks change variables with the root origin, so implementations must
+/// accept it for all keys.
+pub trait KeyOrigin<Origin> {
+	/// Ensure `origin` is allowed to change `key`.
+	fn ensure_origin(key: &[u8], origin: Origin) -> DispatchResult;
+}
+
+/// A `Get` adapter reading the variable `Key`, falling back to `Default` if
+/// the variable is not set or holds a value of another kind.
+pub struct Variable<T, Key, Default>(PhantomData<(T, Key, Default)>);
+
+impl<T, V, Key, Default> Get<V> for Variable<T, Key, Default>
+where
+	T: Config,
+	V: FromValue<T::AccountId>,
+	Key: Get<&'static [u8]>,
+	Default: Get<V>,
+{
+	fn get() -> V {
+		Values::<T>::get(Key::get())
+			.and_then(V::from_value)
+			.unwrap_or_else(Default::get)
+	}
+}
+
+#[frame_support::pallet]
+pub mod pallet {
+	use super::*;
+	use frame_support::pallet_prelude::*;
+	use frame_system::pallet_prelude::*;
+
+	#[pallet::config]
+	pub trait Config: frame_system::Config {
+		/// The overarching event type.
+		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+		/// Keys that can be set, and the kind of value they hold.
+		type Keys: VariableKeys;
+		/// Origin allowed to change a key.
+		type KeyOrigin: KeyOrigin<Self::Origin>;
+		/// Maximum number of changes scheduled for a single block.
+		#[pallet::constant]
+		type MaxScheduledPerBlock: Get<u32>;
+		/// Maximum length of a key.
+		#[pallet::constant]
+		type MaxKeyLength: Get<u32>;
+		/// Maximum length of a `Bytes` value.
+		#[pallet::constant]
+		type MaxBytesLength: Get<u32>;
+		type WeightInfo: WeightInfo;
+	}
+
+	#[pallet::pallet]
+	#[pallet::generate_store(pub(super) trait Store)]
+	#[pallet::storage_version(STORAGE_VERSION)]
+	pub struct Pallet<T>(_);
+
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		fn on_initialize(now: T::BlockNumber) -> Weight {
+			let changes = Scheduled::<T>::take(now);
+			let count = changes.len() as u32;
+
+			for (key, value) in changes {
+				// The registered keys may have changed since the change was
+				// scheduled.
+				if Self::ensure_valid(&key, value.as_ref()).is_ok() {
+					Self::apply(key.to_vec(), value);
+					Self::deposit_event(Event::<T>::ScheduledEnacted(key.into_inner(), now));
+				} else {
+					Self::deposit_event(Event::<T>::ScheduledDropped(key.into_inner(), now));
+				}
+			}
+
+			T::WeightInfo::enact_scheduled(count)
+		}
+	}
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		#[pallet::weight(T::WeightInfo::set_value())]
+		pub fn set_value(
+			origin: OriginFor<T>,
+			key: KeyOf<T>,
+			value: Value<T::AccountId>,
+		) -> DispatchResult {
+			T::KeyOrigin::ensure_origin(&key, origin)?;
+			Self::ensure_valid(&key, Some(&value))?;
+
+			Self::apply(key.into_inner(), Some(value));
+			Ok(())
+		}
+
+		#[pallet::weight(T::WeightInfo::reset_value())]
+		pub fn reset_value(origin: OriginFor<T>, key: KeyOf<T>) -> DispatchResult {
+			T::KeyOrigin::ensure_origin(&key, origin)?;
+			Self::ensure_valid(&key, None)?;
+
+			Self::apply(key.into_inner(), None);
+			Ok(())
+		}
+
+		/// Set or reset several values at once. Either all changes are
+		/// applied, or none of them.
+		#[pallet::weight(T::WeightInfo::set_values(changes.len() as u32))]
+		pub fn set_values(
+			origin: OriginFor<T>,
+			changes: Vec<(KeyOf<T>, Option<Value<T::AccountId>>)>,
+		) -> DispatchResult {
+			for (key, value) in &changes {
+				T::KeyOrigin::ensure_origin(key, origin.clone())?;
+				Self::ensure_valid(key, value.as_ref())?;
+			}
+
+			for (key, value) in changes {
+				Self::apply(key.into_inner(), value);
+			}
+			Ok(())
+		}
+
+		/// Schedule setting, or resetting if `value` is `None`, a key at a
+		/// future block.
+		#[pallet::weight(T::WeightInfo::schedule_value())]
+		pub fn schedule_value(
+			origin: OriginFor<T>,
+			key: KeyOf<T>,
+			value: Option<Value<T::AccountId>>,
+			at: T::BlockNumber,
+		) -> DispatchResult {
+			T::KeyOrigin::ensure_origin(&key, origin)?;
+			Self::ensure_valid(&key, value.as_ref())?;
+			ensure!(at > frame_system::Pallet::<T>::block_number(), Error::<T>::ScheduleInPast);
+
+			Scheduled::<T>::try_mutate(at, |scheduled| -> DispatchResult {
+				ensure!(
+					(scheduled.len() as u32) < T::MaxScheduledPerBlock::get(),
+					Error::<T>::TooManyScheduled,
+				);
+				scheduled.push((key.clone(), value));
+				Ok(())
+			})?;
+
+			Self::deposit_event(Event::<T>::ChangeScheduled(key.into_inner(), at));
+			Ok(())
+		}
+
+		/// Cancel all changes of a key scheduled at a block.
+		#[pallet::weight(T::WeightInfo::cancel_scheduled())]
+		pub fn cancel_scheduled(
+			origin: OriginFor<T>,
+			key: KeyOf<T>,
+			at: T::BlockNumber,
+		) -> DispatchResult {
+			T::KeyOrigin::ensure_origin(&key, origin)?;
+
+			Scheduled::<T>::try_mutate_exists(at, |maybe_scheduled| -> DispatchResult {
+				let scheduled = maybe_scheduled.as_mut().ok_or(Error::<T>::NotScheduled)?;
+				let before = scheduled.len();
+				scheduled.retain(|(scheduled_key, _)| scheduled_key != &key);
+				ensure!(scheduled.len() != before, Error::<T>::NotScheduled);
+
+				if scheduled.is_empty() {
+					*maybe_scheduled = None;
+				}
+				Ok(())
+			})?;
+
+			Self::deposit_event(Event::<T>::ScheduledCancelled(key.into_inner(), at));
+			Ok(())
+		}
+	}
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
 		/// Value set.
-		ValueSet(Vec<u8>, Value),
+		ValueSet(Vec<u8>, Value<T::AccountId>),
 		/// Value reset.
 		ValueReset(Vec<u8>),
+		/// A change of the key was scheduled for the block.
+		ChangeScheduled(Vec<u8>, T::BlockNumber),
+		/// A scheduled change of the key was enacted.
+		ScheduledEnacted(Vec<u8>, T::BlockNumber),
+		/// Scheduled changes of the key at the block were cancelled.
+		ScheduledCancelled(Vec<u8>, T::BlockNumber),
+		/// A scheduled change of the key was dropped, as the key is no longer
+		/// registered or the value no longer of its registered kind.
+		ScheduledDropped(Vec<u8>, T::BlockNumber),
 	}
-}
 
-decl_module! {
-	pub struct Module<T: Config> for enum Call where origin: T::Origin {
-		fn deposit_event() = default;
+	#[pallet::error]
+	pub enum Error<T> {
+		/// The key is not registered.
+		UnknownKey,
+		/// The genesis key is longer than `MaxKeyLength`.
+		KeyTooLong,
+		/// The value is not of the kind registered for the key.
+		ValueKindMismatch,
+		/// The `Bytes` value is longer than `MaxBytesLength`.
+		ValueTooLong,
+		/// The block to schedule at is not in the future.
+		ScheduleInPast,
+		/// The block already has `MaxScheduledPerBlock` scheduled changes.
+		TooManyScheduled,
+		/// There is no scheduled change of the key at the block.
+		NotScheduled,
+	}
+
+	/// Storage values.
+	#[pallet::storage]
+	pub type Values<T: Config> =
+		StorageMap<_, Blake2_128Concat, Vec<u8>, Value<T::AccountId>, OptionQuery>;
+
+	/// Changes scheduled for a block. `None` resets the value.
+	#[pallet::storage]
+	pub type Scheduled<T: Config> = StorageMap<
+		_,
+		Twox64Concat,
+		T::BlockNumber,
+		Vec<(KeyOf<T>, Option<Value<T::AccountId>>)>,
+		ValueQuery,
+	>;
+
+	/// Values to set at genesis. Keys must be registered and values of the
+	/// registered kind.
+	#[pallet::genesis_config]
+	pub struct GenesisConfig<T: Config> {
+		pub values: Vec<(Vec<u8>, Value<T::AccountId>)>,
+	}
 
-		#[weight = 0]
-		fn set_value(origin, key: Vec<u8>, value: Value) {
-			ensure_root(origin)?;
+	#[cfg(feature = "std")]
+	impl<T: Config> Default for GenesisConfig<T> {
+		fn default() -> Self {
+			Self { values: Vec::new() }
+		}
+	}
 
-			Values::insert(key.clone(), value.clone());
-			Self::deposit_event(Event::ValueSet(key, value));
+	#[pallet::genesis_build]
+	impl<T: Config> GenesisBuild<T> for GenesisConfig<T> {
+		fn build(&self) {
+			for (key, value) in &self.values {
+				Pallet::<T>::ensure_valid(key, Some(value))
+					.expect("genesis variable is not a registered key or of the wrong kind");
+				Values::<T>::insert(key, value);
+			}
 		}
+	}
+}
 
-		#[weight = 0]
-		fn reset_value(origin, key: Vec<u8>) {
-			ensure_root(origin)?;
+impl<T: Config> Pallet<T> {
+	/// Check that the key is registered, and that the value, if any, is of the
+	/// registered kind.
+	fn ensure_valid(key: &[u8], value: Option<&Value<T::AccountId>>) -> DispatchResult {
+		ensure!(key.len() <= T::MaxKeyLength::get() as usize, Error::<T>::KeyTooLong);
+		let kind = T::Keys::kind(key).ok_or(Error::<T>::UnknownKey)?;
+
+		if let Some(value) = value {
+			ensure!(value.kind() == kind, Error::<T>::ValueKindMismatch);
+			if let Value::Bytes(bytes) = value {
+				ensure!(bytes.len() <= T::MaxBytesLength::get() as usize, Error::<T>::ValueTooLong);
+			}
+		}
+
+		Ok(())
+	}
 
-			Values::remove(key.clone());
-			Self::deposit_event(Event::ValueReset(key));
+	fn apply(key: Vec<u8>, value: Option<Value<T::AccountId>>) {
+		match value {
+			Some(value) => {
+				Values::<T>::insert(key.clone(), value.clone());
+				Self::deposit_event(Event::<T>::ValueSet(key, value));
+			},
+			None => {
+				Values::<T>::remove(key.clone());
+				Self::deposit_event(Event::<T>::ValueReset(key));
+			},
 		}
 	}
 }
@@ -23,95 +23,165 @@ use frame_benchmarking::{benchmarks, whitelisted_caller};
 use frame_system::RawOrigin;
 use np_domain::{Label, Name};
 
+/// Maximum number of labels of the name records are set for.
+const MAX_DEPTH: u32 = 16;
+/// Maximum length of a record holding names.
+const MAX_LEN: u32 = 1_000;
+
+fn label(raw: &[u8]) -> Label {
+	Label::try_from(raw.to_vec()).unwrap()
+}
+
+/// A name of `depth` labels below `neatuser`, with only `neatuser` owned by
+/// `caller`, so that the effective owner is looked up through all ancestors.
+fn owned_name<T: Config>(caller: T::AccountId, depth: u32) -> Name {
+	let tld = Name(vec![label(b"neatuser")]);
+	T::Registry::set_ownership_unchecked(tld.clone(), Some(T::Ownership::account(caller)));
+
+	let mut labels = tld.0;
+	for i in 1..depth {
+		labels.push(label(&[b'a' + (i % 26) as u8]));
+	}
+	Name(labels)
+}
+
+/// A name of a single label of `len` bytes.
+fn name_of_len(len: u32) -> Name {
+	Name(vec![label(&vec![b'a'; len.max(1) as usize])])
+}
+
+fn bytes<S: Get<u32>>(len: u32) -> BoundedVec<u8, S> {
+	BoundedVec::try_from(vec![0; len as usize]).unwrap()
+}
+
+/// RRSIG record data with the root as signer and a signature making it `len`
+/// bytes long.
+fn rrsig<T: Config>(len: u32) -> RawRrsigOf<T> {
+	let mut raw = vec![0; RRSIG_FIXED_LENGTH + 1];
+	raw.resize(len as usize, 7);
+	BoundedVec::try_from(raw).unwrap()
+}
+
+fn root_name<T: Config>() -> Name {
+	let name = Name(vec![label(b"root")]);
+	T::Registry::set_ownership_unchecked(name.clone(), Some(T::Ownership::root()));
+	name
+}
+
 benchmarks! {
 	set_a {
+		let n in 1 .. MAX_DEPTH;
+		let r in 1 .. T::RecordLimit::get();
+
 		let caller: T::AccountId = whitelisted_caller();
-		let name = Name(vec![
-			Label::try_from(b"neatuser".to_vec()).unwrap(),
-			Label::try_from(b"testname".to_vec()).unwrap(),
-		]);
-		let record = vec![0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15];
-		T::Registry::set_ownership_unchecked(name.clone(), Some(T::Ownership::account(caller.clone())));
-	}: _(RawOrigin::Signed(caller.clone()), name.clone(), record)
+		let name = owned_name::<T>(caller.clone(), n);
+		let record = (0..r).collect::<Vec<RawIpv4>>();
+	}: _(RawOrigin::Signed(caller), name.clone(), record)
+	verify {
+		assert_eq!(<Pallet<T> as Zone>::a(&name).len(), r as usize);
+	}
 
 	set_aaaa {
+		let n in 1 .. MAX_DEPTH;
+		let r in 1 .. T::RecordLimit::get();
+
 		let caller: T::AccountId = whitelisted_caller();
-		let name = Name(vec![
-			Label::try_from(b"neatuser".to_vec()).unwrap(),
-			Label::try_from(b"testname".to_vec()).unwrap(),
-		]);
-		let record = vec![0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15];
-		T::Registry::set_ownership_unchecked(name.clone(), Some(T::Ownership::account(caller.clone())));
-	}: _(RawOrigin::Signed(caller.clone()), name.clone(), record)
+		let name = owned_name::<T>(caller.clone(), n);
+		let record = (0..r).map(RawIpv6::from).collect::<Vec<_>>();
+	}: _(RawOrigin::Signed(caller), name.clone(), record)
+	verify {
+		assert_eq!(<Pallet<T> as Zone>::aaaa(&name).len(), r as usize);
+	}
 
 	set_ns {
+		let n in 1 .. MAX_DEPTH;
+		let l in 1 .. MAX_LEN;
+
 		let caller: T::AccountId = whitelisted_caller();
-		let name = Name(vec![
-			Label::try_from(b"neatuser".to_vec()).unwrap(),
-			Label::try_from(b"testname".to_vec()).unwrap(),
-		]);
-		let ns_name = Name(vec![
-			Label::try_from(b"neatuser".to_vec()).unwrap(),
-			Label::try_from(b"testname".to_vec()).unwrap(),
-			Label::try_from(b"ns".to_vec()).unwrap(),
-		]);
-		let record = vec![ns_name];
-		T::Registry::set_ownership_unchecked(name.clone(), Some(T::Ownership::account(caller.clone())));
-	}: _(RawOrigin::Signed(caller.clone()), name.clone(), record)
+		let name = owned_name::<T>(caller.clone(), n);
+		let record = vec![name_of_len(l)];
+	}: _(RawOrigin::Signed(caller), name, record)
 
 	set_cname {
+		let n in 1 .. MAX_DEPTH;
+		let l in 1 .. MAX_LEN;
+
 		let caller: T::AccountId = whitelisted_caller();
-		let name = Name(vec![
-			Label::try_from(b"neatuser".to_vec()).unwrap(),
-			Label::try_from(b"testname".to_vec()).unwrap(),
-		]);
-		let cname = Name(vec![
-			Label::try_from(b"neatuser".to_vec()).unwrap(),
-			Label::try_from(b"testname".to_vec()).unwrap(),
-			Label::try_from(b"cname".to_vec()).unwrap(),
-		]);
-		T::Registry::set_ownership_unchecked(name.clone(), Some(T::Ownership::account(caller.clone())));
-	}: _(RawOrigin::Signed(caller.clone()), name.clone(), Some(cname))
+		let name = owned_name::<T>(caller.clone(), n);
+		let cname = name_of_len(l);
+	}: _(RawOrigin::Signed(caller), name.clone(), Some(cname.clone()))
+	verify {
+		assert_eq!(<Pallet<T> as Zone>::cname(&name), Some(cname));
+	}
 
 	set_mx {
+		let n in 1 .. MAX_DEPTH;
+		let l in 1 .. MAX_LEN;
+
 		let caller: T::AccountId = whitelisted_caller();
-		let name = Name(vec![
-			Label::try_from(b"neatuser".to_vec()).unwrap(),
-			Label::try_from(b"testname".to_vec()).unwrap(),
-		]);
-		let mx_name = Name(vec![
-			Label::try_from(b"neatuser".to_vec()).unwrap(),
-			Label::try_from(b"testname".to_vec()).unwrap(),
-			Label::try_from(b"mx".to_vec()).unwrap(),
-		]);
-		T::Registry::set_ownership_unchecked(name.clone(), Some(T::Ownership::account(caller.clone())));
-	}: _(RawOrigin::Signed(caller.clone()), name.clone(), Some((0, mx_name)))
+		let name = owned_name::<T>(caller.clone(), n);
+		let mx_name = name_of_len(l);
+	}: _(RawOrigin::Signed(caller), name, Some((0, mx_name)))
 
 	set_icann {
-		let name = Name(vec![
-			Label::try_from(b"root".to_vec()).unwrap(),
-		]);
-		T::Registry::set_ownership_unchecked(name.clone(), Some(T::Ownership::root()));
-	}: _(RawOrigin::Root, name.clone())
+		let name = root_name::<T>();
+	}: _(RawOrigin::Root, name)
 
 	set_opennic {
-		let name = Name(vec![
-			Label::try_from(b"root".to_vec()).unwrap(),
-		]);
-		T::Registry::set_ownership_unchecked(name.clone(), Some(T::Ownership::root()));
-	}: _(RawOrigin::Root, name.clone())
+		let name = root_name::<T>();
+	}: _(RawOrigin::Root, name)
 
 	set_handshake {
-		let name = Name(vec![
-			Label::try_from(b"root".to_vec()).unwrap(),
-		]);
-		T::Registry::set_ownership_unchecked(name.clone(), Some(T::Ownership::root()));
-	}: _(RawOrigin::Root, name.clone())
+		let name = root_name::<T>();
+	}: _(RawOrigin::Root, name)
 
 	reset_extern {
-		let name = Name(vec![
-			Label::try_from(b"root".to_vec()).unwrap(),
-		]);
-		T::Registry::set_ownership_unchecked(name.clone(), Some(T::Ownership::root()));
-	}: _(RawOrigin::Root, name.clone())
+		let name = root_name::<T>();
+	}: _(RawOrigin::Root, name)
+
+	set_dnskey {
+		let n in 1 .. MAX_DEPTH;
+		let l in 1 .. T::MaxKeyLength::get();
+
+		let caller: T::AccountId = whitelisted_caller();
+		let name = owned_name::<T>(caller.clone(), n);
+		let record: Vec<DnsKeyOf<T>> = vec![DnsKey { flags: 257, algorithm: 15, public_key: bytes(l) }];
+	}: _(RawOrigin::Signed(caller), name.clone(), record.clone())
+	verify {
+		assert_eq!(DNSKEYs::<T>::get(name.hash()).into_value().unwrap().into_inner(), record);
+	}
+
+	set_ds {
+		let n in 1 .. MAX_DEPTH;
+		let l in 1 .. T::MaxDigestLength::get();
+
+		let caller: T::AccountId = whitelisted_caller();
+		let name = owned_name::<T>(caller.clone(), n);
+		let record: Vec<DsOf<T>> = vec![Ds { key_tag: 0, algorithm: 15, digest_type: 2, digest: bytes(l) }];
+	}: _(RawOrigin::Signed(caller), name.clone(), record.clone())
+	verify {
+		assert_eq!(DSs::<T>::get(name.hash()).into_value().unwrap().into_inner(), record);
+	}
+
+	set_rrsig {
+		let n in 1 .. MAX_DEPTH;
+		let l in (RRSIG_FIXED_LENGTH as u32 + 2) .. T::MaxSignatureLength::get();
+
+		let caller: T::AccountId = whitelisted_caller();
+		let name = owned_name::<T>(caller.clone(), n);
+		let record = vec![rrsig::<T>(l)];
+	}: _(RawOrigin::Signed(caller), name.clone(), record.clone())
+	verify {
+		assert_eq!(RRSIGs::<T>::get(name.hash()).into_value().unwrap().into_inner(), record);
+	}
+
+	set_dnssec {
+		let n in 1 .. MAX_DEPTH;
+
+		let caller: T::AccountId = whitelisted_caller();
+		let name = owned_name::<T>(caller.clone(), n);
+	}: _(RawOrigin::Signed(caller), name.clone(), Some(Dnssec::Keystore))
+	verify {
+		assert_eq!(DNSSECs::<T>::get(name.hash()).into_value(), Some(Dnssec::Keystore));
+	}
 }
@@ -1,9 +1,7 @@
-
-//! Autogenerated weights for `pallet_zone`
+//! Weights for `pallet_zone`
 //!
-//! THIS FILE WAS AUTO-GENERATED USING THE SUBSTRATE BENCHMARK CLI VERSION 4.0.0-dev
-//! DATE: 2021-12-11, STEPS: `50`, REPEAT: 20, LOW RANGE: `[]`, HIGH RANGE: `[]`
-//! EXECUTION: Some(Wasm), WASM-EXECUTION: Compiled, CHAIN: Some("dev"), DB CACHE: 128
+//! NOT YET MEASURED. THESE ARE PLACEHOLDERS FOLLOWING THE STORAGE FOOTPRINT
+//! OF EACH CALL, UNTIL REPLACED BY THE OUTPUT OF THE COMMAND BELOW.
 
 // Executed Command:
 // ./target/release/neatcoin
@@ -35,39 +33,64 @@ use sp_std::marker::PhantomData;
 
 impl crate::WeightInfo for () {
 	// Storage: Registry Ownerships (r:1 w:0)
-	// Storage: Zone As (r:0 w:1)
-	fn set_a() -> Weight {
-		(27_500_000 as Weight)
+	// Storage: Zone As (r:1 w:1)
+	// Storage: Zone Subnames (r:1 w:1)
+	fn set_a(n: u32, r: u32, ) -> Weight {
+		(25_300_000 as Weight)
+			.saturating_add((1_100_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add((70_000 as Weight).saturating_mul(r as Weight))
 			.saturating_add(DbWeight::get().reads(1 as Weight))
+			.saturating_add(DbWeight::get().reads((2 as Weight).saturating_mul(n as Weight)))
 			.saturating_add(DbWeight::get().writes(1 as Weight))
+			.saturating_add(DbWeight::get().writes((1 as Weight).saturating_mul(n as Weight)))
 	}
 	// Storage: Registry Ownerships (r:1 w:0)
-	// Storage: Zone AAAAs (r:0 w:1)
-	fn set_aaaa() -> Weight {
-		(28_800_000 as Weight)
+	// Storage: Zone AAAAs (r:1 w:1)
+	// Storage: Zone Subnames (r:1 w:1)
+	fn set_aaaa(n: u32, r: u32, ) -> Weight {
+		(25_900_000 as Weight)
+			.saturating_add((1_100_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add((110_000 as Weight).saturating_mul(r as Weight))
 			.saturating_add(DbWeight::get().reads(1 as Weight))
+			.saturating_add(DbWeight::get().reads((2 as Weight).saturating_mul(n as Weight)))
 			.saturating_add(DbWeight::get().writes(1 as Weight))
+			.saturating_add(DbWeight::get().writes((1 as Weight).saturating_mul(n as Weight)))
 	}
 	// Storage: Registry Ownerships (r:1 w:0)
-	// Storage: Zone NSs (r:0 w:1)
-	fn set_ns() -> Weight {
-		(29_800_000 as Weight)
+	// Storage: Zone NSs (r:1 w:1)
+	// Storage: Zone Subnames (r:1 w:1)
+	fn set_ns(n: u32, l: u32, ) -> Weight {
+		(27_600_000 as Weight)
+			.saturating_add((1_100_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add((4_000 as Weight).saturating_mul(l as Weight))
 			.saturating_add(DbWeight::get().reads(1 as Weight))
+			.saturating_add(DbWeight::get().reads((2 as Weight).saturating_mul(n as Weight)))
 			.saturating_add(DbWeight::get().writes(1 as Weight))
+			.saturating_add(DbWeight::get().writes((1 as Weight).saturating_mul(n as Weight)))
 	}
 	// Storage: Registry Ownerships (r:1 w:0)
-	// Storage: Zone CNAMEs (r:0 w:1)
-	fn set_cname() -> Weight {
-		(29_900_000 as Weight)
+	// Storage: Zone CNAMEs (r:1 w:1)
+	// Storage: Zone Subnames (r:1 w:1)
+	fn set_cname(n: u32, l: u32, ) -> Weight {
+		(27_700_000 as Weight)
+			.saturating_add((1_100_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add((4_000 as Weight).saturating_mul(l as Weight))
 			.saturating_add(DbWeight::get().reads(1 as Weight))
+			.saturating_add(DbWeight::get().reads((2 as Weight).saturating_mul(n as Weight)))
 			.saturating_add(DbWeight::get().writes(1 as Weight))
+			.saturating_add(DbWeight::get().writes((1 as Weight).saturating_mul(n as Weight)))
 	}
 	// Storage: Registry Ownerships (r:1 w:0)
-	// Storage: Zone MXs (r:0 w:1)
-	fn set_mx() -> Weight {
-		(29_500_000 as Weight)
+	// Storage: Zone MXs (r:1 w:1)
+	// Storage: Zone Subnames (r:1 w:1)
+	fn set_mx(n: u32, l: u32, ) -> Weight {
+		(27_300_000 as Weight)
+			.saturating_add((1_100_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add((4_000 as Weight).saturating_mul(l as Weight))
 			.saturating_add(DbWeight::get().reads(1 as Weight))
+			.saturating_add(DbWeight::get().reads((2 as Weight).saturating_mul(n as Weight)))
 			.saturating_add(DbWeight::get().writes(1 as Weight))
+			.saturating_add(DbWeight::get().writes((1 as Weight).saturating_mul(n as Weight)))
 	}
 	// Storage: Registry Ownerships (r:1 w:0)
 	// Storage: Zone ICANNs (r:0 w:1)
@@ -99,4 +122,51 @@ impl crate::WeightInfo for () {
 			.saturating_add(DbWeight::get().reads(1 as Weight))
 			.saturating_add(DbWeight::get().writes(3 as Weight))
 	}
+	// Storage: Registry Ownerships (r:1 w:0)
+	// Storage: Zone DNSKEYs (r:1 w:1)
+	// Storage: Zone Subnames (r:1 w:1)
+	fn set_dnskey(n: u32, l: u32, ) -> Weight {
+		(27_800_000 as Weight)
+			.saturating_add((1_100_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add((4_000 as Weight).saturating_mul(l as Weight))
+			.saturating_add(DbWeight::get().reads(1 as Weight))
+			.saturating_add(DbWeight::get().reads((2 as Weight).saturating_mul(n as Weight)))
+			.saturating_add(DbWeight::get().writes(1 as Weight))
+			.saturating_add(DbWeight::get().writes((1 as Weight).saturating_mul(n as Weight)))
+	}
+	// Storage: Registry Ownerships (r:1 w:0)
+	// Storage: Zone DSs (r:1 w:1)
+	// Storage: Zone Subnames (r:1 w:1)
+	fn set_ds(n: u32, l: u32, ) -> Weight {
+		(27_600_000 as Weight)
+			.saturating_add((1_100_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add((4_000 as Weight).saturating_mul(l as Weight))
+			.saturating_add(DbWeight::get().reads(1 as Weight))
+			.saturating_add(DbWeight::get().reads((2 as Weight).saturating_mul(n as Weight)))
+			.saturating_add(DbWeight::get().writes(1 as Weight))
+			.saturating_add(DbWeight::get().writes((1 as Weight).saturating_mul(n as Weight)))
+	}
+	// Storage: Registry Ownerships (r:1 w:0)
+	// Storage: Zone RRSIGs (r:1 w:1)
+	// Storage: Zone Subnames (r:1 w:1)
+	fn set_rrsig(n: u32, l: u32, ) -> Weight {
+		(28_100_000 as Weight)
+			.saturating_add((1_100_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add((4_000 as Weight).saturating_mul(l as Weight))
+			.saturating_add(DbWeight::get().reads(1 as Weight))
+			.saturating_add(DbWeight::get().reads((2 as Weight).saturating_mul(n as Weight)))
+			.saturating_add(DbWeight::get().writes(1 as Weight))
+			.saturating_add(DbWeight::get().writes((1 as Weight).saturating_mul(n as Weight)))
+	}
+	// Storage: Registry Ownerships (r:1 w:0)
+	// Storage: Zone DNSSECs (r:1 w:1)
+	// Storage: Zone Subnames (r:1 w:1)
+	fn set_dnssec(n: u32, ) -> Weight {
+		(26_900_000 as Weight)
+			.saturating_add((1_100_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(DbWeight::get().reads(1 as Weight))
+			.saturating_add(DbWeight::get().reads((2 as Weight).saturating_mul(n as Weight)))
+			.saturating_add(DbWeight::get().writes(1 as Weight))
+			.saturating_add(DbWeight::get().writes((1 as Weight).saturating_mul(n as Weight)))
+	}
 }
@@ -21,198 +21,581 @@
 
 mod benchmarking;
 mod default_weights;
+pub mod migrations;
 
+use codec::{Decode, Encode};
 use frame_support::{
-	decl_error, decl_event, decl_module, decl_storage, ensure, storage::bounded_vec::BoundedVec,
-	traits::Get, weights::Weight,
+	dispatch::DispatchResult,
+	ensure, parameter_types,
+	storage::bounded_vec::BoundedVec,
+	traits::{Get, StorageVersion},
+	weights::Weight,
+	CloneNoBound, EqNoBound, PartialEqNoBound, RuntimeDebugNoBound,
 };
 use frame_system::{ensure_root, ensure_signed};
-use np_domain::{Name, NameHash, NameValue};
+use np_domain::{Label, LabelHash, Name, NameHash, NameValue};
 use pallet_registry::{Ownership, Registry};
-use sp_std::prelude::*;
+use scale_info::TypeInfo;
+use sp_runtime::RuntimeDebug;
+use sp_std::{convert::TryFrom, prelude::*};
 
+pub use pallet::*;
+
+/// Weights of the zone calls. `n` is the number of labels of the name the
+/// record is set for, `r` the number of addresses, and `l` the encoded length
+/// of a record holding names or DNSSEC data.
 pub trait WeightInfo {
-	fn set_a() -> Weight;
-	fn set_aaaa() -> Weight;
-	fn set_ns() -> Weight;
-	fn set_cname() -> Weight;
-	fn set_mx() -> Weight;
+	fn set_a(n: u32, r: u32) -> Weight;
+	fn set_aaaa(n: u32, r: u32) -> Weight;
+	fn set_ns(n: u32, l: u32) -> Weight;
+	fn set_cname(n: u32, l: u32) -> Weight;
+	fn set_mx(n: u32, l: u32) -> Weight;
 	fn set_icann() -> Weight;
 	fn set_opennic() -> Weight;
 	fn set_handshake() -> Weight;
 	fn reset_extern() -> Weight;
-}
-
-pub trait Config: frame_system::Config {
-	type Ownership: Ownership<AccountId = Self::AccountId>;
-	type Registry: Registry<Ownership = Self::Ownership>;
-	type Event: From<Event> + Into<<Self as frame_system::Config>::Event>;
-	type RecordLimit: Get<u32>;
-	type WeightInfo: WeightInfo;
+	fn set_dnskey(n: u32, l: u32) -> Weight;
+	fn set_ds(n: u32, l: u32) -> Weight;
+	fn set_rrsig(n: u32, l: u32) -> Weight;
+	fn set_dnssec(n: u32) -> Weight;
 }
 
 pub type RawIpv4 = u32;
 pub type RawIpv6 = u128;
 
-decl_storage! {
-	trait Store for Module<T: Config> as Zone {
-		As: map hasher(identity) NameHash => NameValue<BoundedVec<RawIpv4, T::RecordLimit>>;
-		AAAAs: map hasher(identity) NameHash => NameValue<BoundedVec<RawIpv6, T::RecordLimit>>;
-		NSs: map hasher(identity) NameHash => NameValue<BoundedVec<Name, T::RecordLimit>>;
-		CNAMEs: map hasher(identity) NameHash => NameValue<Name>;
-		MXs: map hasher(identity) NameHash => NameValue<(u16, Name)>;
-
-		ICANNs: map hasher(identity) NameHash => NameValue<()>;
-		OpenNICs: map hasher(identity) NameHash => NameValue<()>;
-		Handshakes: map hasher(identity) NameHash => NameValue<()>;
-	}
+/// DNSKEY record data (RFC 4034). The protocol field is always 3.
+#[derive(
+	Encode, Decode, CloneNoBound, EqNoBound, PartialEqNoBound, RuntimeDebugNoBound, TypeInfo,
+)]
+#[scale_info(skip_type_params(KeyLimit))]
+pub struct DnsKey<KeyLimit: Get<u32>> {
+	pub flags: u16,
+	pub algorithm: u8,
+	pub public_key: BoundedVec<u8, KeyLimit>,
 }
 
-decl_event! {
-	pub enum Event {
-		SetA(Name, Vec<RawIpv4>),
-		SetAAAA(Name, Vec<RawIpv6>),
-		SetNS(Name, Vec<Name>),
-		SetCNAME(Name, Option<Name>),
-		SetMX(Name, Option<(u16, Name)>),
+/// DS record data (RFC 4034). It is served from the zone above the name it
+/// is set for.
+#[derive(
+	Encode, Decode, CloneNoBound, EqNoBound, PartialEqNoBound, RuntimeDebugNoBound, TypeInfo,
+)]
+#[scale_info(skip_type_params(DigestLimit))]
+pub struct Ds<DigestLimit: Get<u32>> {
+	pub key_tag: u16,
+	pub algorithm: u8,
+	pub digest_type: u8,
+	pub digest: BoundedVec<u8, DigestLimit>,
+}
 
-		SetICANN(Name),
-		SetOpenNIC(Name),
-		SetHandshake(Name),
+/// RRSIG record data in wire format (RFC 4034), with an uncompressed signer
+/// name.
+pub type RawRrsig<SignatureLimit> = BoundedVec<u8, SignatureLimit>;
+
+pub type DnsKeyOf<T> = DnsKey<<T as Config>::MaxKeyLength>;
+pub type DsOf<T> = Ds<<T as Config>::MaxDigestLength>;
+pub type RawRrsigOf<T> = RawRrsig<<T as Config>::MaxSignatureLength>;
+
+parameter_types! {
+	/// Public key limit of DNSKEY records in the Nomo runtimes.
+	pub const MaxDnsKeyLength: u32 = 1024;
+	/// Digest limit of DS records in the Nomo runtimes.
+	pub const MaxDsDigestLength: u32 = 64;
+	/// Limit of RRSIG record data in the Nomo runtimes.
+	pub const MaxRrsigLength: u32 = 1024;
+}
 
-		ResetExtern(Name),
-	}
+/// Length of the fixed fields of RRSIG record data, before the signer name.
+const RRSIG_FIXED_LENGTH: usize = 18;
+/// Maximum length of a name in wire format.
+const MAX_NAME_LENGTH: usize = 255;
+/// Maximum length of a label in wire format, without its length byte.
+const MAX_LABEL_LENGTH: usize = 63;
+
+/// How answers from the zone of an apex name are signed.
+#[derive(Encode, Decode, Clone, Copy, Eq, PartialEq, RuntimeDebug, TypeInfo)]
+pub enum Dnssec {
+	/// Nodes sign answers with the DNSKEYs of the zone they hold in their
+	/// keystore, and deny existence with NSEC records made on the fly.
+	Keystore,
+	/// Nodes serve the RRSIG records set by the owner. Denials are not
+	/// signed.
+	Presigned,
 }
 
-decl_error! {
-	pub enum Error for Module<T: Config> {
-		OwnershipMismatch,
-		RecordTooLarge,
-	}
+/// Read access to zone records for other pallets.
+pub trait Zone {
+	fn a(name: &Name) -> Vec<RawIpv4>;
+	fn aaaa(name: &Name) -> Vec<RawIpv6>;
+	fn cname(name: &Name) -> Option<Name>;
 }
 
-decl_module! {
-	pub struct Module<T: Config> for enum Call where origin: T::Origin {
-		type Error = Error<T>;
+/// Current storage version of the pallet.
+pub const STORAGE_VERSION: StorageVersion = StorageVersion::new(2);
+
+#[frame_support::pallet]
+pub mod pallet {
+	use super::*;
+	use frame_support::pallet_prelude::*;
+	use frame_system::pallet_prelude::*;
+
+	#[pallet::config]
+	pub trait Config: frame_system::Config {
+		type Ownership: Ownership<AccountId = Self::AccountId>;
+		type Registry: Registry<Ownership = Self::Ownership>;
+		type Event: From<Event<Self>> + IsType<<Self as frame_system::Config>::Event>;
+		#[pallet::constant]
+		type RecordLimit: Get<u32>;
+		/// Maximum length of the public key of a DNSKEY record.
+		#[pallet::constant]
+		type MaxKeyLength: Get<u32>;
+		/// Maximum length of the digest of a DS record.
+		#[pallet::constant]
+		type MaxDigestLength: Get<u32>;
+		/// Maximum length of an RRSIG record in wire format, signer name
+		/// included.
+		#[pallet::constant]
+		type MaxSignatureLength: Get<u32>;
+		type WeightInfo: WeightInfo;
+	}
 
-		fn deposit_event() = default;
+	#[pallet::pallet]
+	#[pallet::generate_store(pub(super) trait Store)]
+	#[pallet::storage_version(STORAGE_VERSION)]
+	pub struct Pallet<T>(_);
 
-		#[weight = T::WeightInfo::set_a()]
-		fn set_a(origin, name: Name, record: Vec<RawIpv4>) {
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		#[pallet::weight(T::WeightInfo::set_a(name.0.len() as u32, record.len() as u32))]
+		pub fn set_a(origin: OriginFor<T>, name: Name, record: Vec<RawIpv4>) -> DispatchResult {
 			let owner = ensure_signed(origin)?;
-			ensure!(T::Registry::is_effective_owned(&T::Ownership::account(owner), &name), Error::<T>::OwnershipMismatch);
+			Self::ensure_effective_owned(owner, &name)?;
 
-			let record = BoundedVec::try_from(record).map_err(|_| Error::<T>::RecordTooLarge)?;
+			let record = Self::insert_or_remove::<As<T>, _>(&name, record)?;
 
-			if record.is_empty() {
-				As::<T>::remove(name.hash());
-			} else {
-				As::<T>::insert(name.hash(), NameValue::some(name.clone(), record.clone()));
-			}
-
-			Self::deposit_event(Event::SetA(name, record.into_inner()));
+			Self::deposit_event(Event::SetA(name, record));
+			Ok(())
 		}
 
-		#[weight = T::WeightInfo::set_aaaa()]
-		fn set_aaaa(origin, name: Name, record: Vec<RawIpv6>) {
+		#[pallet::weight(T::WeightInfo::set_aaaa(name.0.len() as u32, record.len() as u32))]
+		pub fn set_aaaa(origin: OriginFor<T>, name: Name, record: Vec<RawIpv6>) -> DispatchResult {
 			let owner = ensure_signed(origin)?;
-			ensure!(T::Registry::is_effective_owned(&T::Ownership::account(owner), &name), Error::<T>::OwnershipMismatch);
-
-			let record = BoundedVec::try_from(record).map_err(|_| Error::<T>::RecordTooLarge)?;
+			Self::ensure_effective_owned(owner, &name)?;
 
-			if record.is_empty() {
-				AAAAs::<T>::remove(name.hash());
-			} else {
-				AAAAs::<T>::insert(name.hash(), NameValue::some(name.clone(), record.clone()));
-			}
+			let record = Self::insert_or_remove::<AAAAs<T>, _>(&name, record)?;
 
-			Self::deposit_event(Event::SetAAAA(name, record.into_inner()));
+			Self::deposit_event(Event::SetAAAA(name, record));
+			Ok(())
 		}
 
-		#[weight = T::WeightInfo::set_ns()]
-		fn set_ns(origin, name: Name, record: Vec<Name>) {
+		#[pallet::weight(T::WeightInfo::set_ns(name.0.len() as u32, record.encoded_size() as u32))]
+		pub fn set_ns(origin: OriginFor<T>, name: Name, record: Vec<Name>) -> DispatchResult {
 			let owner = ensure_signed(origin)?;
-			ensure!(T::Registry::is_effective_owned(&T::Ownership::account(owner), &name), Error::<T>::OwnershipMismatch);
+			Self::ensure_effective_owned(owner, &name)?;
 
-			let record = BoundedVec::try_from(record).map_err(|_| Error::<T>::RecordTooLarge)?;
+			let record = Self::insert_or_remove::<NSs<T>, _>(&name, record)?;
 
-			if record.is_empty() {
-				NSs::<T>::remove(name.hash());
-			} else {
-				NSs::<T>::insert(name.hash(), NameValue::some(name.clone(), record.clone()));
-			}
-
-			Self::deposit_event(Event::SetNS(name, record.into_inner()));
+			Self::deposit_event(Event::SetNS(name, record));
+			Ok(())
 		}
 
-		#[weight = T::WeightInfo::set_cname()]
-		fn set_cname(origin, name: Name, record: Option<Name>) {
+		#[pallet::weight(T::WeightInfo::set_cname(name.0.len() as u32, record.encoded_size() as u32))]
+		pub fn set_cname(origin: OriginFor<T>, name: Name, record: Option<Name>) -> DispatchResult {
 			let owner = ensure_signed(origin)?;
-			ensure!(T::Registry::is_effective_owned(&T::Ownership::account(owner), &name), Error::<T>::OwnershipMismatch);
+			Self::ensure_effective_owned(owner, &name)?;
 
-			if let Some(record) = record.clone() {
-				CNAMEs::insert(name.hash(), NameValue::some(name.clone(), record));
-			} else {
-				CNAMEs::remove(name.hash());
-			}
+			Self::set_entry::<CNAMEs<T>, _>(&name, record.clone());
 
 			Self::deposit_event(Event::SetCNAME(name, record));
+			Ok(())
 		}
 
-		#[weight = T::WeightInfo::set_mx()]
-		fn set_mx(origin, name: Name, record: Option<(u16, Name)>) {
+		#[pallet::weight(T::WeightInfo::set_mx(name.0.len() as u32, record.encoded_size() as u32))]
+		pub fn set_mx(
+			origin: OriginFor<T>,
+			name: Name,
+			record: Option<(u16, Name)>,
+		) -> DispatchResult {
 			let owner = ensure_signed(origin)?;
-			ensure!(T::Registry::is_effective_owned(&T::Ownership::account(owner), &name), Error::<T>::OwnershipMismatch);
+			Self::ensure_effective_owned(owner, &name)?;
 
-			if let Some(record) = record.clone() {
-				MXs::insert(name.hash(), NameValue::some(name.clone(), record));
-			} else {
-				MXs::remove(name.hash());
-			}
+			Self::set_entry::<MXs<T>, _>(&name, record.clone());
 
 			Self::deposit_event(Event::SetMX(name, record));
+			Ok(())
 		}
 
-		#[weight = T::WeightInfo::set_icann()]
-		fn set_icann(origin, name: Name) {
+		#[pallet::weight(T::WeightInfo::set_icann())]
+		pub fn set_icann(origin: OriginFor<T>, name: Name) -> DispatchResult {
 			ensure_root(origin)?;
-			ensure!(T::Registry::is_owned(&T::Ownership::root(), &name), Error::<T>::OwnershipMismatch);
+			Self::ensure_root_owned(&name)?;
 
-			ICANNs::insert(name.hash(), NameValue::some(name.clone(), ()));
+			ICANNs::<T>::insert(name.hash(), NameValue::some(name.clone(), ()));
 
 			Self::deposit_event(Event::SetICANN(name));
+			Ok(())
 		}
 
-		#[weight = T::WeightInfo::set_opennic()]
-		fn set_opennic(origin, name: Name) {
+		#[pallet::weight(T::WeightInfo::set_opennic())]
+		pub fn set_opennic(origin: OriginFor<T>, name: Name) -> DispatchResult {
 			ensure_root(origin)?;
-			ensure!(T::Registry::is_owned(&T::Ownership::root(), &name), Error::<T>::OwnershipMismatch);
+			Self::ensure_root_owned(&name)?;
 
-			OpenNICs::insert(name.hash(), NameValue::some(name.clone(), ()));
+			OpenNICs::<T>::insert(name.hash(), NameValue::some(name.clone(), ()));
 
 			Self::deposit_event(Event::SetOpenNIC(name));
+			Ok(())
 		}
 
-		#[weight = T::WeightInfo::set_handshake()]
-		fn set_handshake(origin, name: Name) {
+		#[pallet::weight(T::WeightInfo::set_handshake())]
+		pub fn set_handshake(origin: OriginFor<T>, name: Name) -> DispatchResult {
 			ensure_root(origin)?;
-			ensure!(T::Registry::is_owned(&T::Ownership::root(), &name), Error::<T>::OwnershipMismatch);
+			Self::ensure_root_owned(&name)?;
 
-			Handshakes::insert(name.hash(), NameValue::some(name.clone(), ()));
+			Handshakes::<T>::insert(name.hash(), NameValue::some(name.clone(), ()));
 
 			Self::deposit_event(Event::SetHandshake(name));
+			Ok(())
 		}
 
-		#[weight = T::WeightInfo::reset_extern()]
-		fn reset_extern(origin, name: Name) {
+		#[pallet::weight(T::WeightInfo::reset_extern())]
+		pub fn reset_extern(origin: OriginFor<T>, name: Name) -> DispatchResult {
 			ensure_root(origin)?;
-			ensure!(T::Registry::is_owned(&T::Ownership::root(), &name), Error::<T>::OwnershipMismatch);
+			Self::ensure_root_owned(&name)?;
 
-			ICANNs::remove(name.hash());
-			OpenNICs::remove(name.hash());
-			Handshakes::remove(name.hash());
+			ICANNs::<T>::remove(name.hash());
+			OpenNICs::<T>::remove(name.hash());
+			Handshakes::<T>::remove(name.hash());
 
 			Self::deposit_event(Event::ResetExtern(name));
+			Ok(())
+		}
+
+		#[pallet::weight(T::WeightInfo::set_dnskey(name.0.len() as u32, record.encoded_size() as u32))]
+		pub fn set_dnskey(
+			origin: OriginFor<T>,
+			name: Name,
+			record: Vec<DnsKeyOf<T>>,
+		) -> DispatchResult {
+			let owner = ensure_signed(origin)?;
+			Self::ensure_effective_owned(owner, &name)?;
+
+			let record = Self::insert_or_remove::<DNSKEYs<T>, _>(&name, record)?;
+
+			Self::deposit_event(Event::SetDNSKEY(name, record));
+			Ok(())
+		}
+
+		#[pallet::weight(T::WeightInfo::set_ds(name.0.len() as u32, record.encoded_size() as u32))]
+		pub fn set_ds(origin: OriginFor<T>, name: Name, record: Vec<DsOf<T>>) -> DispatchResult {
+			let owner = ensure_signed(origin)?;
+			Self::ensure_effective_owned(owner, &name)?;
+
+			let record = Self::insert_or_remove::<DSs<T>, _>(&name, record)?;
+
+			Self::deposit_event(Event::SetDS(name, record));
+			Ok(())
+		}
+
+		#[pallet::weight(T::WeightInfo::set_rrsig(name.0.len() as u32, record.encoded_size() as u32))]
+		pub fn set_rrsig(
+			origin: OriginFor<T>,
+			name: Name,
+			record: Vec<RawRrsigOf<T>>,
+		) -> DispatchResult {
+			let owner = ensure_signed(origin)?;
+			Self::ensure_effective_owned(owner, &name)?;
+			for rrsig in &record {
+				Self::ensure_valid_rrsig(&name, rrsig)?;
+			}
+
+			let record = Self::insert_or_remove::<RRSIGs<T>, _>(&name, record)?;
+
+			Self::deposit_event(Event::SetRRSIG(name, record));
+			Ok(())
+		}
+
+		#[pallet::weight(T::WeightInfo::set_dnssec(name.0.len() as u32))]
+		pub fn set_dnssec(
+			origin: OriginFor<T>,
+			name: Name,
+			mode: Option<Dnssec>,
+		) -> DispatchResult {
+			let owner = ensure_signed(origin)?;
+			Self::ensure_effective_owned(owner, &name)?;
+
+			Self::set_entry::<DNSSECs<T>, _>(&name, mode);
+
+			Self::deposit_event(Event::SetDNSSEC(name, mode));
+			Ok(())
+		}
+	}
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		SetA(Name, Vec<RawIpv4>),
+		SetAAAA(Name, Vec<RawIpv6>),
+		SetNS(Name, Vec<Name>),
+		SetCNAME(Name, Option<Name>),
+		SetMX(Name, Option<(u16, Name)>),
+
+		SetICANN(Name),
+		SetOpenNIC(Name),
+		SetHandshake(Name),
+
+		ResetExtern(Name),
+
+		SetDNSKEY(Name, Vec<DnsKeyOf<T>>),
+		SetDS(Name, Vec<DsOf<T>>),
+		SetRRSIG(Name, Vec<RawRrsigOf<T>>),
+		SetDNSSEC(Name, Option<Dnssec>),
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		OwnershipMismatch,
+		RecordTooLarge,
+		/// An RRSIG record is not in wire format with an uncompressed signer
+		/// name, or has more labels than the name it is set for.
+		InvalidRrsig,
+	}
+
+	#[pallet::storage]
+	pub(super) type As<T: Config> = StorageMap<
+		_,
+		Identity,
+		NameHash,
+		NameValue<BoundedVec<RawIpv4, T::RecordLimit>>,
+		ValueQuery,
+	>;
+
+	#[pallet::storage]
+	pub(super) type AAAAs<T: Config> = StorageMap<
+		_,
+		Identity,
+		NameHash,
+		NameValue<BoundedVec<RawIpv6, T::RecordLimit>>,
+		ValueQuery,
+	>;
+
+	#[pallet::storage]
+	pub(super) type NSs<T: Config> =
+		StorageMap<_, Identity, NameHash, NameValue<BoundedVec<Name, T::RecordLimit>>, ValueQuery>;
+
+	#[pallet::storage]
+	pub(super) type CNAMEs<T: Config> =
+		StorageMap<_, Identity, NameHash, NameValue<Name>, ValueQuery>;
+
+	#[pallet::storage]
+	pub(super) type MXs<T: Config> =
+		StorageMap<_, Identity, NameHash, NameValue<(u16, Name)>, ValueQuery>;
+
+	#[pallet::storage]
+	pub(super) type ICANNs<T: Config> = StorageMap<_, Identity, NameHash, NameValue<()>, ValueQuery>;
+
+	#[pallet::storage]
+	pub(super) type OpenNICs<T: Config> =
+		StorageMap<_, Identity, NameHash, NameValue<()>, ValueQuery>;
+
+	#[pallet::storage]
+	pub(super) type Handshakes<T: Config> =
+		StorageMap<_, Identity, NameHash, NameValue<()>, ValueQuery>;
+
+	#[pallet::storage]
+	pub(super) type DNSKEYs<T: Config> = StorageMap<
+		_,
+		Identity,
+		NameHash,
+		NameValue<BoundedVec<DnsKeyOf<T>, T::RecordLimit>>,
+		ValueQuery,
+	>;
+
+	#[pallet::storage]
+	pub(super) type DSs<T: Config> = StorageMap<
+		_,
+		Identity,
+		NameHash,
+		NameValue<BoundedVec<DsOf<T>, T::RecordLimit>>,
+		ValueQuery,
+	>;
+
+	#[pallet::storage]
+	pub(super) type RRSIGs<T: Config> = StorageMap<
+		_,
+		Identity,
+		NameHash,
+		NameValue<BoundedVec<RawRrsigOf<T>, T::RecordLimit>>,
+		ValueQuery,
+	>;
+
+	#[pallet::storage]
+	pub(super) type DNSSECs<T: Config> =
+		StorageMap<_, Identity, NameHash, NameValue<Dnssec>, ValueQuery>;
+
+	/// Index of names holding records or with records under them, by the
+	/// name hash of their parent and their label hash. The value is the
+	/// label and the number of record entries at or under the name. The
+	/// ICANN, OpenNIC and Handshake flags are not counted.
+	#[pallet::storage]
+	pub(super) type Subnames<T: Config> =
+		StorageDoubleMap<_, Identity, NameHash, Identity, LabelHash, (Label, u32), OptionQuery>;
+
+	/// Zone records to seed at genesis. Ownership of the names is not checked.
+	#[pallet::genesis_config]
+	#[derive(Default)]
+	pub struct GenesisConfig {
+		pub a: Vec<(Name, Vec<RawIpv4>)>,
+		pub aaaa: Vec<(Name, Vec<RawIpv6>)>,
+		pub ns: Vec<(Name, Vec<Name>)>,
+		pub cname: Vec<(Name, Name)>,
+		pub mx: Vec<(Name, u16, Name)>,
+	}
+
+	#[pallet::genesis_build]
+	impl<T: Config> GenesisBuild<T> for GenesisConfig {
+		fn build(&self) {
+			for (name, record) in &self.a {
+				Pallet::<T>::insert_or_remove::<As<T>, _>(name, record.clone())
+					.expect("genesis A record exceeds the record limit");
+			}
+			for (name, record) in &self.aaaa {
+				Pallet::<T>::insert_or_remove::<AAAAs<T>, _>(name, record.clone())
+					.expect("genesis AAAA record exceeds the record limit");
+			}
+			for (name, record) in &self.ns {
+				Pallet::<T>::insert_or_remove::<NSs<T>, _>(name, record.clone())
+					.expect("genesis NS record exceeds the record limit");
+			}
+			for (name, target) in &self.cname {
+				Pallet::<T>::set_entry::<CNAMEs<T>, _>(name, Some(target.clone()));
+			}
+			for (name, preference, exchange) in &self.mx {
+				Pallet::<T>::set_entry::<MXs<T>, _>(name, Some((*preference, exchange.clone())));
+			}
 		}
 	}
 }
+
+impl<T: Config> Pallet<T> {
+	fn ensure_effective_owned(owner: T::AccountId, name: &Name) -> DispatchResult {
+		ensure!(
+			T::Registry::is_effective_owned(&T::Ownership::account(owner), name),
+			Error::<T>::OwnershipMismatch
+		);
+		Ok(())
+	}
+
+	fn ensure_root_owned(name: &Name) -> DispatchResult {
+		ensure!(
+			T::Registry::is_owned(&T::Ownership::root(), name),
+			Error::<T>::OwnershipMismatch
+		);
+		Ok(())
+	}
+
+	/// Ensure `rrsig` is RRSIG record data of `name` in wire format: the fixed
+	/// fields, an uncompressed signer name and a non-empty signature.
+	fn ensure_valid_rrsig(name: &Name, rrsig: &[u8]) -> DispatchResult {
+		ensure!(rrsig.len() > RRSIG_FIXED_LENGTH, Error::<T>::InvalidRrsig);
+		ensure!(rrsig[3] as usize <= name.0.len(), Error::<T>::InvalidRrsig);
+
+		let mut offset = RRSIG_FIXED_LENGTH;
+		loop {
+			let length = *rrsig.get(offset).ok_or(Error::<T>::InvalidRrsig)? as usize;
+			ensure!(length <= MAX_LABEL_LENGTH, Error::<T>::InvalidRrsig);
+			offset += 1 + length;
+			ensure!(offset - RRSIG_FIXED_LENGTH <= MAX_NAME_LENGTH, Error::<T>::InvalidRrsig);
+			if length == 0 {
+				break
+			}
+		}
+		ensure!(offset < rrsig.len(), Error::<T>::InvalidRrsig);
+
+		Ok(())
+	}
+
+	/// Store a list record, or remove it if empty. Returns the stored record.
+	fn insert_or_remove<S, V>(name: &Name, record: Vec<V>) -> Result<Vec<V>, Error<T>>
+	where
+		S: frame_support::StorageMap<NameHash, NameValue<BoundedVec<V, T::RecordLimit>>>,
+		V: codec::FullCodec + Clone,
+	{
+		let record =
+			BoundedVec::<V, T::RecordLimit>::try_from(record).map_err(|_| Error::<T>::RecordTooLarge)?;
+
+		if record.is_empty() {
+			Self::set_entry::<S, _>(name, None);
+		} else {
+			Self::set_entry::<S, _>(name, Some(record.clone()));
+		}
+
+		Ok(record.into_inner())
+	}
+
+	/// Store the entry of `name` in a record map, or remove it, and count it
+	/// in `Subnames`.
+	fn set_entry<S, V>(name: &Name, value: Option<V>)
+	where
+		S: frame_support::StorageMap<NameHash, NameValue<V>>,
+		V: codec::FullCodec,
+	{
+		let existed = S::contains_key(name.hash());
+		match value {
+			Some(value) => {
+				S::insert(name.hash(), NameValue::some(name.clone(), value));
+				if !existed {
+					Self::index_name(name, true);
+				}
+			}
+			None => {
+				S::remove(name.hash());
+				if existed {
+					Self::index_name(name, false);
+				}
+			}
+		}
+	}
+
+	/// Count one more or one less record entry for `name` in `Subnames`, at
+	/// the name and each of its ancestors.
+	pub(crate) fn index_name(name: &Name, added: bool) {
+		let mut parent = NameHash::default();
+		for label in &name.0 {
+			let label_hash = label.hash();
+			Subnames::<T>::mutate_exists(parent, label_hash, |entry| {
+				let count = entry.as_ref().map_or(0, |(_, count)| *count);
+				let count = if added {
+					count.saturating_add(1)
+				} else {
+					count.saturating_sub(1)
+				};
+				*entry = if count == 0 {
+					None
+				} else {
+					Some((label.clone(), count))
+				};
+			});
+			parent = Name::child_hash(&parent, &label_hash);
+		}
+	}
+}
+
+impl<T: Config> Zone for Pallet<T> {
+	fn a(name: &Name) -> Vec<RawIpv4> {
+		As::<T>::get(name.hash())
+			.into_value()
+			.map(|record| record.into_inner())
+			.unwrap_or_default()
+	}
+
+	fn aaaa(name: &Name) -> Vec<RawIpv6> {
+		AAAAs::<T>::get(name.hash())
+			.into_value()
+			.map(|record| record.into_inner())
+			.unwrap_or_default()
+	}
+
+	fn cname(name: &Name) -> Option<Name> {
+		CNAMEs::<T>::get(name.hash()).into_value()
+	}
+}
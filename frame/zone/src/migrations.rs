@@ -0,0 +1,161 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// This file is part of Nomo.
+//
+// Copyright (c) 2019-2020 Wei Tang.
+//
+// Nomo is free software: you can redistribute it and/or modify it
+// under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Nomo is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Nomo. If not, see <http://www.gnu.org/licenses/>.
+
+//! Storage migrations for the zone pallet.
+
+use crate::{AAAAs, As, CNAMEs, Config, DNSKEYs, DNSSECs, DSs, MXs, NSs, Pallet, RRSIGs};
+use frame_support::{
+	storage::IterableStorageMap,
+	traits::{Get, OnRuntimeUpgrade, StorageVersion},
+	weights::Weight,
+};
+use np_domain::{NameHash, NameValue};
+use sp_std::marker::PhantomData;
+#[cfg(feature = "try-runtime")]
+use crate::Subnames;
+
+/// Build the `Subnames` index from the record maps, moving to storage
+/// version 2. Does nothing if the pallet is already at version 2 or later.
+///
+/// The index is built in a single block, with one read per record entry and
+/// one read and one write per label of its name. `pre_upgrade` logs the
+/// number of entries, to check the migration fits in a block before it is
+/// enacted.
+pub struct BuildSubnames<T>(PhantomData<T>);
+
+impl<T: Config> OnRuntimeUpgrade for BuildSubnames<T> {
+	fn on_runtime_upgrade() -> Weight {
+		if StorageVersion::get::<Pallet<T>>() >= StorageVersion::new(2) {
+			return T::DbWeight::get().reads(1)
+		}
+
+		let (mut entries, mut labels) = (0, 0);
+		for (count, depth) in [
+			index::<T, As<T>, _>(),
+			index::<T, AAAAs<T>, _>(),
+			index::<T, NSs<T>, _>(),
+			index::<T, CNAMEs<T>, _>(),
+			index::<T, MXs<T>, _>(),
+			index::<T, DNSKEYs<T>, _>(),
+			index::<T, DSs<T>, _>(),
+			index::<T, RRSIGs<T>, _>(),
+			index::<T, DNSSECs<T>, _>(),
+		] {
+			entries += count;
+			labels += depth;
+		}
+		StorageVersion::new(2).put::<Pallet<T>>();
+
+		log::info!(target: "runtime::zone", "Indexed {} record entries", entries);
+
+		T::DbWeight::get().reads_writes(1 + entries + labels, 1 + labels)
+	}
+
+	#[cfg(feature = "try-runtime")]
+	fn pre_upgrade() -> Result<(), &'static str> {
+		if StorageVersion::get::<Pallet<T>>() >= StorageVersion::new(2) {
+			log::info!(target: "runtime::zone", "Subnames index already built");
+			return Ok(())
+		}
+
+		let entries = As::<T>::iter_keys().count()
+			+ AAAAs::<T>::iter_keys().count()
+			+ NSs::<T>::iter_keys().count()
+			+ CNAMEs::<T>::iter_keys().count()
+			+ MXs::<T>::iter_keys().count()
+			+ DNSKEYs::<T>::iter_keys().count()
+			+ DSs::<T>::iter_keys().count()
+			+ RRSIGs::<T>::iter_keys().count()
+			+ DNSSECs::<T>::iter_keys().count();
+		log::info!(target: "runtime::zone", "Indexing {} record entries", entries);
+
+		Ok(())
+	}
+
+	#[cfg(feature = "try-runtime")]
+	fn post_upgrade() -> Result<(), &'static str> {
+		frame_support::ensure!(
+			StorageVersion::get::<Pallet<T>>() >= StorageVersion::new(2),
+			"storage version not updated",
+		);
+
+		let entries = [
+			check::<T, As<T>, _>()?,
+			check::<T, AAAAs<T>, _>()?,
+			check::<T, NSs<T>, _>()?,
+			check::<T, CNAMEs<T>, _>()?,
+			check::<T, MXs<T>, _>()?,
+			check::<T, DNSKEYs<T>, _>()?,
+			check::<T, DSs<T>, _>()?,
+			check::<T, RRSIGs<T>, _>()?,
+			check::<T, DNSSECs<T>, _>()?,
+		]
+		.iter()
+		.sum::<u32>();
+		let indexed = Subnames::<T>::iter_prefix_values(NameHash::default())
+			.map(|(_, count)| count)
+			.sum::<u32>();
+		frame_support::ensure!(
+			indexed == entries,
+			"subnames index does not count every record entry",
+		);
+
+		Ok(())
+	}
+}
+
+/// Count every entry of a record map in `Subnames`. Returns the number of
+/// entries and the total number of labels of their names.
+fn index<T: Config, S, V>() -> (Weight, Weight)
+where
+	S: IterableStorageMap<NameHash, NameValue<V>>,
+	V: codec::FullCodec,
+{
+	let (mut entries, mut labels) = (0, 0);
+	for (_, entry) in S::iter() {
+		if let Some(name) = entry.into_name() {
+			Pallet::<T>::index_name(&name, true);
+			entries += 1;
+			labels += name.0.len() as Weight;
+		}
+	}
+	(entries, labels)
+}
+
+/// Ensure every entry of a record map is indexed under its parent. Returns
+/// the number of entries.
+#[cfg(feature = "try-runtime")]
+fn check<T: Config, S, V>() -> Result<u32, &'static str>
+where
+	S: IterableStorageMap<NameHash, NameValue<V>>,
+	V: codec::FullCodec,
+{
+	let mut entries = 0;
+	for (_, entry) in S::iter() {
+		if let Some(name) = entry.into_name() {
+			let parent = name.parent().ok_or("records of the root name")?;
+			let label = name.0.last().ok_or("records of the root name")?;
+			frame_support::ensure!(
+				Subnames::<T>::contains_key(parent.hash(), label.hash()),
+				"name with records missing from the subnames index",
+			);
+			entries += 1;
+		}
+	}
+	Ok(entries)
+}
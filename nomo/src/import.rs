@@ -0,0 +1,245 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// This file is part of Neatcoin.
+//
+// Copyright (c) 2021 Wei Tang.
+//
+// Neatcoin is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Neatcoin is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Neatcoin. If not, see <http://www.gnu.org/licenses/>.
+
+//! Zone calls bringing on-chain records in line with a parsed master file.
+
+use crate::{name_order, Records};
+use np_domain::Name;
+use pallet_zone::{RawIpv4, RawIpv6};
+use std::{collections::BTreeMap, fmt};
+
+/// A zone call, independent of the runtime it is dispatched in.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ZoneCall {
+	SetA(Name, Vec<RawIpv4>),
+	SetAAAA(Name, Vec<RawIpv6>),
+	SetNS(Name, Vec<Name>),
+	SetCNAME(Name, Option<Name>),
+	SetMX(Name, Option<(u16, Name)>),
+}
+
+impl ZoneCall {
+	/// Name the call sets records of.
+	pub fn name(&self) -> &Name {
+		match self {
+			Self::SetA(name, _) |
+			Self::SetAAAA(name, _) |
+			Self::SetNS(name, _) |
+			Self::SetCNAME(name, _) |
+			Self::SetMX(name, _) => name,
+		}
+	}
+
+	/// The call of the zone pallet of a runtime.
+	pub fn into_call<T: pallet_zone::Config>(self) -> pallet_zone::Call<T> {
+		match self {
+			Self::SetA(name, record) => pallet_zone::Call::set_a { name, record },
+			Self::SetAAAA(name, record) => pallet_zone::Call::set_aaaa { name, record },
+			Self::SetNS(name, record) => pallet_zone::Call::set_ns { name, record },
+			Self::SetCNAME(name, record) => pallet_zone::Call::set_cname { name, record },
+			Self::SetMX(name, record) => pallet_zone::Call::set_mx { name, record },
+		}
+	}
+}
+
+impl fmt::Display for ZoneCall {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		let dotted = |name: &Name| String::from_utf8_lossy(&name.to_dotted()).into_owned();
+		let list = |items: Vec<String>| {
+			if items.is_empty() {
+				"-".to_string()
+			} else {
+				items.join(" ")
+			}
+		};
+
+		match self {
+			Self::SetA(name, record) => write!(
+				f,
+				"A {} {}",
+				dotted(name),
+				list(
+					record
+						.iter()
+						.map(|addr| std::net::Ipv4Addr::from(*addr).to_string())
+						.collect()
+				)
+			),
+			Self::SetAAAA(name, record) => write!(
+				f,
+				"AAAA {} {}",
+				dotted(name),
+				list(
+					record
+						.iter()
+						.map(|addr| std::net::Ipv6Addr::from(*addr).to_string())
+						.collect()
+				)
+			),
+			Self::SetNS(name, record) => write!(
+				f,
+				"NS {} {}",
+				dotted(name),
+				list(record.iter().map(dotted).collect())
+			),
+			Self::SetCNAME(name, record) => write!(
+				f,
+				"CNAME {} {}",
+				dotted(name),
+				record
+					.as_ref()
+					.map(dotted)
+					.unwrap_or_else(|| "-".to_string())
+			),
+			Self::SetMX(name, record) => write!(
+				f,
+				"MX {} {}",
+				dotted(name),
+				record
+					.as_ref()
+					.map(|(preference, exchange)| format!("{} {}", preference, dotted(exchange)))
+					.unwrap_or_else(|| "-".to_string())
+			),
+		}
+	}
+}
+
+/// Minimal zone calls turning the `current` records of a zone into the
+/// `desired` ones. Names missing from `desired` have their records removed.
+/// Record sets are compared regardless of order, and the ICANN, OpenNIC and
+/// Handshake flags and DNSSEC data are left untouched.
+pub fn diff(current: &[(Name, Records)], desired: &[(Name, Records)]) -> Vec<ZoneCall> {
+	let mut names = BTreeMap::<Vec<u8>, (Name, Option<&Records>, Option<&Records>)>::new();
+	for (name, records) in current {
+		names
+			.entry(name.hash().as_bytes().to_vec())
+			.or_insert_with(|| (name.clone(), None, None))
+			.1 = Some(records);
+	}
+	for (name, records) in desired {
+		names
+			.entry(name.hash().as_bytes().to_vec())
+			.or_insert_with(|| (name.clone(), None, None))
+			.2 = Some(records);
+	}
+
+	let mut names = names
+		.into_iter()
+		.map(|(_, entry)| entry)
+		.collect::<Vec<_>>();
+	names.sort_by(|(a, _, _), (b, _, _)| name_order(a, b));
+
+	let empty = Records::default();
+	let mut calls = Vec::new();
+	for (name, current, desired) in names {
+		let current = current.unwrap_or(&empty);
+		let desired = desired.unwrap_or(&empty);
+
+		if !same_set(&current.a, &desired.a) {
+			calls.push(ZoneCall::SetA(
+				name.clone(),
+				desired.a.iter().cloned().map(RawIpv4::from).collect(),
+			));
+		}
+		if !same_set(&current.aaaa, &desired.aaaa) {
+			calls.push(ZoneCall::SetAAAA(
+				name.clone(),
+				desired.aaaa.iter().cloned().map(RawIpv6::from).collect(),
+			));
+		}
+		if !same_set(&current.ns, &desired.ns) {
+			calls.push(ZoneCall::SetNS(name.clone(), desired.ns.clone()));
+		}
+		if current.cname != desired.cname {
+			calls.push(ZoneCall::SetCNAME(name.clone(), desired.cname.clone()));
+		}
+		if current.mx != desired.mx {
+			calls.push(ZoneCall::SetMX(name.clone(), desired.mx.clone()));
+		}
+	}
+
+	calls
+}
+
+fn same_set<T: PartialEq>(a: &[T], b: &[T]) -> bool {
+	a.len() == b.len() && a.iter().all(|item| b.contains(item))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::net::Ipv4Addr;
+
+	fn name(dotted: &str) -> Name {
+		Name::from_dotted(dotted.as_bytes()).unwrap()
+	}
+
+	#[test]
+	fn diff_is_minimal() {
+		let current = vec![
+			(
+				name("example.neatuser"),
+				Records {
+					ns: vec![name("ns1.example.neatuser"), name("ns2.example.neatuser")],
+					mx: Some((10, name("mail.example.neatuser"))),
+					icann: true,
+					..Default::default()
+				},
+			),
+			(
+				name("old.example.neatuser"),
+				Records {
+					a: vec![Ipv4Addr::new(10, 0, 0, 9)],
+					cname: Some(name("example.neatuser")),
+					..Default::default()
+				},
+			),
+		];
+		let desired = vec![
+			(
+				name("example.neatuser"),
+				Records {
+					ns: vec![name("ns2.example.neatuser"), name("ns1.example.neatuser")],
+					mx: Some((20, name("mail.example.neatuser"))),
+					..Default::default()
+				},
+			),
+			(
+				name("www.example.neatuser"),
+				Records {
+					a: vec![Ipv4Addr::new(10, 0, 0, 1)],
+					..Default::default()
+				},
+			),
+		];
+
+		assert_eq!(
+			diff(&current, &desired),
+			vec![
+				ZoneCall::SetMX(
+					name("example.neatuser"),
+					Some((20, name("mail.example.neatuser")))
+				),
+				ZoneCall::SetA(name("old.example.neatuser"), vec![]),
+				ZoneCall::SetCNAME(name("old.example.neatuser"), None),
+				ZoneCall::SetA(name("www.example.neatuser"), vec![0x0a000001]),
+			]
+		);
+		assert!(diff(&desired, &desired).is_empty());
+	}
+}
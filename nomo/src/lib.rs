@@ -0,0 +1,481 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// This file is part of Neatcoin.
+//
+// Copyright (c) 2021 Wei Tang.
+//
+// Neatcoin is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Neatcoin is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Neatcoin. If not, see <http://www.gnu.org/licenses/>.
+
+//! Reading Nomo names and zone records directly from chain storage.
+
+pub mod import;
+pub mod zonefile;
+
+use codec::Decode;
+use np_domain::{Label, LabelHash, Name, NameHash, NameValue};
+use np_opaque::{AccountId, Balance, Block, BlockNumber};
+use pallet_fcfs::RenewalInfo;
+use pallet_zone::{Dnssec, RawIpv4, RawIpv6};
+use sc_client_api::{Backend, StorageProvider};
+use sp_core::hashing::twox_128;
+use sp_runtime::generic::BlockId;
+use sp_storage::StorageKey;
+use std::{
+	cmp::Ordering,
+	marker::PhantomData,
+	net::{Ipv4Addr, Ipv6Addr},
+};
+
+/// Ownership of a name. Both runtimes share the same type.
+pub type Ownership = pallet_registry::NomoOwnership<AccountId>;
+
+/// DNSSEC record data of a name, with the limits both runtimes share.
+pub type DnsKey = pallet_zone::DnsKey<pallet_zone::MaxDnsKeyLength>;
+pub type Ds = pallet_zone::Ds<pallet_zone::MaxDsDigestLength>;
+pub type RawRrsig = pallet_zone::RawRrsig<pallet_zone::MaxRrsigLength>;
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+	#[error(transparent)]
+	Blockchain(#[from] sp_blockchain::Error),
+
+	#[error("invalid {item} entry: {error}")]
+	Decode {
+		item: &'static str,
+		error: codec::Error,
+	},
+
+	#[error("remote node: {0}")]
+	Remote(String),
+}
+
+/// Raw storage access at a fixed block.
+pub trait StorageRead {
+	/// Value of a storage key.
+	fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>, Error>;
+	/// All key-value pairs under a prefix.
+	fn pairs(&self, prefix: &[u8]) -> Result<Vec<(Vec<u8>, Vec<u8>)>, Error>;
+}
+
+/// Storage of a client at a block.
+pub struct ClientStorage<'a, C, B> {
+	client: &'a C,
+	at: BlockId<Block>,
+	_marker: PhantomData<B>,
+}
+
+impl<'a, C, B> ClientStorage<'a, C, B> {
+	pub fn new(client: &'a C, at: BlockId<Block>) -> Self {
+		Self {
+			client,
+			at,
+			_marker: PhantomData,
+		}
+	}
+}
+
+impl<'a, C, B> StorageRead for ClientStorage<'a, C, B>
+where
+	C: StorageProvider<Block, B>,
+	B: Backend<Block>,
+{
+	fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>, Error> {
+		Ok(self
+			.client
+			.storage(&self.at, &StorageKey(key.to_vec()))?
+			.map(|data| data.0))
+	}
+
+	fn pairs(&self, prefix: &[u8]) -> Result<Vec<(Vec<u8>, Vec<u8>)>, Error> {
+		Ok(self
+			.client
+			.storage_pairs(&self.at, &StorageKey(prefix.to_vec()))?
+			.into_iter()
+			.map(|(key, data)| (key.0, data.0))
+			.collect())
+	}
+}
+
+/// Zone records of a name.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Records {
+	pub a: Vec<Ipv4Addr>,
+	pub aaaa: Vec<Ipv6Addr>,
+	pub ns: Vec<Name>,
+	pub cname: Option<Name>,
+	pub mx: Option<(u16, Name)>,
+	pub dnskey: Vec<DnsKey>,
+	pub ds: Vec<Ds>,
+	pub rrsig: Vec<RawRrsig>,
+	pub dnssec: Option<Dnssec>,
+	pub icann: bool,
+	pub opennic: bool,
+	pub handshake: bool,
+}
+
+impl Records {
+	/// Whether the name has no records.
+	pub fn is_empty(&self) -> bool {
+		self == &Self::default()
+	}
+
+	/// Name server and DS records, which the zone above a cut holds for it.
+	pub fn delegation(&self) -> Self {
+		Self {
+			ns: self.ns.clone(),
+			ds: self.ds.clone(),
+			..Default::default()
+		}
+	}
+
+	/// Address records, which the zone above a cut holds for name servers
+	/// under it.
+	pub fn glue(&self) -> Self {
+		Self {
+			a: self.a.clone(),
+			aaaa: self.aaaa.clone(),
+			..Default::default()
+		}
+	}
+}
+
+/// Part of the records of a name held by a zone.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ZonePart {
+	/// All records, for names of the zone.
+	All,
+	/// Name server and DS records, for unowned names with name servers below
+	/// the apex, which are zone cuts.
+	Delegation,
+	/// Address records, for name servers under a cut.
+	Glue,
+	/// DS records, for owned names below the apex, which are apexes of their
+	/// own zones.
+	Ds,
+}
+
+impl ZonePart {
+	/// The held part of `records`.
+	pub fn of(&self, records: &Records) -> Records {
+		match self {
+			Self::All => records.clone(),
+			Self::Delegation => records.delegation(),
+			Self::Glue => records.glue(),
+			Self::Ds => Records {
+				ds: records.ds.clone(),
+				..Default::default()
+			},
+		}
+	}
+}
+
+/// Storage prefix of a map of the given pallet.
+fn map_prefix(pallet: &str, item: &str) -> Vec<u8> {
+	let mut prefix = twox_128(pallet.as_bytes()).to_vec();
+	prefix.extend_from_slice(&twox_128(item.as_bytes()));
+	prefix
+}
+
+/// Key of an `Identity`-hashed name map entry, or prefix of the entries
+/// under a name of an `Identity`-hashed double map.
+fn name_key(pallet: &str, item: &str, hash: &NameHash) -> Vec<u8> {
+	let mut key = map_prefix(pallet, item);
+	key.extend_from_slice(hash.as_bytes());
+	key
+}
+
+/// Reader of Nomo pallet storage.
+pub struct NomoReader<S> {
+	storage: S,
+}
+
+impl<S: StorageRead> NomoReader<S> {
+	pub fn new(storage: S) -> Self {
+		Self { storage }
+	}
+
+	fn name_value<T: Decode>(
+		&self,
+		pallet: &str,
+		item: &'static str,
+		name: &Name,
+	) -> Result<Option<T>, Error> {
+		let raw = match self.storage.get(&name_key(pallet, item, &name.hash()))? {
+			Some(raw) => raw,
+			None => return Ok(None),
+		};
+
+		Ok(NameValue::<T>::decode(&mut &raw[..])
+			.map_err(|error| Error::Decode { item, error })?
+			.into_value())
+	}
+
+	fn flag(&self, item: &'static str, name: &Name) -> Result<bool, Error> {
+		Ok(self.name_value::<()>("Zone", item, name)?.is_some())
+	}
+
+	/// Explicit owner of a name.
+	pub fn owner(&self, name: &Name) -> Result<Option<Ownership>, Error> {
+		self.name_value("Registry", "Ownerships", name)
+	}
+
+	/// Whether owned names are indexed as children of `parent`.
+	pub fn has_children(&self, parent: &Name) -> Result<bool, Error> {
+		let prefix = name_key("Registry", "Children", &parent.hash());
+		Ok(!self.storage.pairs(&prefix)?.is_empty())
+	}
+
+	/// Owners of the name and its ancestors, from the name itself up to the
+	/// first owned one, which is the effective owner.
+	pub fn owner_chain(&self, name: &Name) -> Result<Vec<(Name, Option<Ownership>)>, Error> {
+		let mut chain = Vec::new();
+		let mut current = Some(name.clone());
+		while let Some(check) = current {
+			let owner = self.owner(&check)?;
+			let owned = owner.is_some();
+			current = check.parent();
+			chain.push((check, owner));
+
+			if owned {
+				break
+			}
+		}
+		Ok(chain)
+	}
+
+	/// Effective owner of a name, with the name it is inherited from.
+	pub fn effective_owner(&self, name: &Name) -> Result<Option<(Name, Ownership)>, Error> {
+		Ok(self
+			.owner_chain(name)?
+			.pop()
+			.and_then(|(name, owner)| owner.map(|owner| (name, owner))))
+	}
+
+	/// FCFS renewal info of a name.
+	pub fn renewal(&self, name: &Name) -> Result<Option<RenewalInfo<BlockNumber, Balance>>, Error> {
+		self.name_value("FCFS", "Renewals", name)
+	}
+
+	/// Raw `System::Events` value of the block, to be decoded with the event
+	/// type of its runtime.
+	pub fn events(&self) -> Result<Option<Vec<u8>>, Error> {
+		self.storage.get(&map_prefix("System", "Events"))
+	}
+
+	/// Zone records of a name.
+	pub fn records(&self, name: &Name) -> Result<Records, Error> {
+		Ok(Records {
+			a: self
+				.name_value::<Vec<RawIpv4>>("Zone", "As", name)?
+				.unwrap_or_default()
+				.into_iter()
+				.map(Ipv4Addr::from)
+				.collect(),
+			aaaa: self
+				.name_value::<Vec<RawIpv6>>("Zone", "AAAAs", name)?
+				.unwrap_or_default()
+				.into_iter()
+				.map(Ipv6Addr::from)
+				.collect(),
+			ns: self.name_value("Zone", "NSs", name)?.unwrap_or_default(),
+			cname: self.name_value("Zone", "CNAMEs", name)?,
+			mx: self.name_value("Zone", "MXs", name)?,
+			dnskey: self
+				.name_value("Zone", "DNSKEYs", name)?
+				.unwrap_or_default(),
+			ds: self.name_value("Zone", "DSs", name)?.unwrap_or_default(),
+			rrsig: self.name_value("Zone", "RRSIGs", name)?.unwrap_or_default(),
+			dnssec: self.name_value("Zone", "DNSSECs", name)?,
+			icann: self.flag("ICANNs", name)?,
+			opennic: self.flag("OpenNICs", name)?,
+			handshake: self.flag("Handshakes", name)?,
+		})
+	}
+
+	/// All owned names strictly under `parent`, ordered by depth and then
+	/// by dotted name. They are found by walking the registry `Children`
+	/// index down from `parent`, through owned names only, so owned names
+	/// under a name without an owner are not reached.
+	pub fn descendants(&self, parent: &Name) -> Result<Vec<(Name, Ownership)>, Error> {
+		let mut names = Vec::new();
+		let mut parents = vec![parent.hash()];
+		while let Some(parent) = parents.pop() {
+			let prefix = name_key("Registry", "Children", &parent);
+			for (key, _) in self.storage.pairs(&prefix)? {
+				if key.len() != prefix.len() + 32 {
+					continue
+				}
+				let child = Name::child_hash(&parent, &LabelHash::from_slice(&key[prefix.len()..]));
+				let raw = match self
+					.storage
+					.get(&name_key("Registry", "Ownerships", &child))?
+				{
+					Some(raw) => raw,
+					None => continue,
+				};
+				let entry = NameValue::<Ownership>::decode(&mut &raw[..]).map_err(|error| {
+					Error::Decode {
+						item: "Ownerships",
+						error,
+					}
+				})?;
+
+				if let Some(entry) = entry.into_inner() {
+					names.push(entry);
+					parents.push(child);
+				}
+			}
+		}
+
+		names.sort_by(|(a, _), (b, _)| name_order(a, b));
+		Ok(names)
+	}
+
+	/// Zone records of `apex` and the names under it whose effective owner
+	/// is `apex`, ordered by depth and then by dotted name. Names are found
+	/// by walking the zone `Subnames` index down from `apex`, leaving out
+	/// owned names below it and the names under them.
+	pub fn owned_records(&self, apex: &Name) -> Result<Vec<(Name, Records)>, Error> {
+		let mut entries = Vec::new();
+		let mut names = vec![apex.clone()];
+		while let Some(name) = names.pop() {
+			if &name != apex && self.owner(&name)?.is_some() {
+				continue
+			}
+			names.extend(self.subnames(&name)?);
+
+			let records = self.records(&name)?;
+			if !records.is_empty() {
+				entries.push((name, records));
+			}
+		}
+
+		entries.sort_by(|(a, _), (b, _)| name_order(a, b));
+		Ok(entries)
+	}
+
+	/// Zone records held by the zone of `apex`, ordered by depth and then by
+	/// dotted name. Names are found by walking the zone `Subnames` index down
+	/// from `apex`. Owned names below it are apexes of their own zones and
+	/// only their DS records are held, and names with name servers below it
+	/// are cuts, held with their delegation and the glue of name servers
+	/// under them. Neither is walked further.
+	pub fn zone_entries(&self, apex: &Name) -> Result<Vec<(Name, Records)>, Error> {
+		let mut entries = Vec::new();
+		let mut names = vec![apex.clone()];
+		while let Some(name) = names.pop() {
+			let records = self.records(&name)?;
+			let part = if &name == apex {
+				ZonePart::All
+			} else if self.owner(&name)?.is_some() {
+				ZonePart::Ds
+			} else if !records.ns.is_empty() {
+				ZonePart::Delegation
+			} else {
+				ZonePart::All
+			};
+
+			if part == ZonePart::Delegation {
+				let mut servers = records
+					.ns
+					.iter()
+					.filter(|server| is_under(server, &name) && *server != &name)
+					.collect::<Vec<_>>();
+				servers.sort_by(|a, b| name_order(a, b));
+				servers.dedup();
+				for server in servers {
+					let glue = self.records(server)?.glue();
+					if !glue.is_empty() {
+						entries.push((server.clone(), glue));
+					}
+				}
+			}
+			if part == ZonePart::All {
+				names.extend(self.subnames(&name)?);
+			}
+
+			let records = part.of(&records);
+			if !records.is_empty() {
+				entries.push((name, records));
+			}
+		}
+
+		entries.sort_by(|(a, _), (b, _)| name_order(a, b));
+		Ok(entries)
+	}
+
+	/// Part of the records of `name` held by the zone of `apex`, or `None`
+	/// if the zone holds none of them. See `zone_entries`.
+	pub fn zone_part(&self, apex: &Name, name: &Name) -> Result<Option<ZonePart>, Error> {
+		if !is_under(name, apex) {
+			return Ok(None)
+		}
+
+		for depth in apex.0.len() + 1..=name.0.len() {
+			let cut = Name(name.0[..depth].to_vec());
+			if self.owner(&cut)?.is_some() {
+				return Ok(if &cut == name {
+					Some(ZonePart::Ds)
+				} else {
+					None
+				})
+			}
+
+			let ns = self.records(&cut)?.ns;
+			if ns.is_empty() {
+				continue
+			}
+			return Ok(if &cut == name {
+				Some(ZonePart::Delegation)
+			} else if ns.contains(name) {
+				Some(ZonePart::Glue)
+			} else {
+				None
+			})
+		}
+		Ok(Some(ZonePart::All))
+	}
+
+	/// Names directly under `parent` that hold zone records or have records
+	/// under them.
+	pub fn subnames(&self, parent: &Name) -> Result<Vec<Name>, Error> {
+		let mut children = Vec::new();
+		for (_, raw) in self
+			.storage
+			.pairs(&name_key("Zone", "Subnames", &parent.hash()))?
+		{
+			let (label, _) =
+				<(Label, u32)>::decode(&mut &raw[..]).map_err(|error| Error::Decode {
+					item: "Subnames",
+					error,
+				})?;
+
+			let mut child = parent.clone();
+			child.0.push(label);
+			children.push(child);
+		}
+		Ok(children)
+	}
+}
+
+/// Whether `name` is `parent` or a name under it.
+pub fn is_under(name: &Name, parent: &Name) -> bool {
+	name.0.starts_with(&parent.0)
+}
+
+/// Order names by depth, and then by dotted name.
+pub fn name_order(a: &Name, b: &Name) -> Ordering {
+	a.0.len()
+		.cmp(&b.0.len())
+		.then_with(|| a.to_dotted().cmp(&b.to_dotted()))
+}
@@ -0,0 +1,800 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// This file is part of Neatcoin.
+//
+// Copyright (c) 2021 Wei Tang.
+//
+// Neatcoin is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Neatcoin is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Neatcoin. If not, see <http://www.gnu.org/licenses/>.
+
+//! Rendering of on-chain zones as RFC 1035 master files, and parsing of
+//! master files back into zone records.
+
+use crate::{is_under, name_order, Error, NomoReader, Records, StorageRead};
+use np_domain::Name;
+use np_opaque::{BlockNumber, Hash};
+use pallet_zone::Dnssec;
+use std::{
+	collections::BTreeMap,
+	fmt::{self, Write},
+	net::{Ipv4Addr, Ipv6Addr},
+};
+
+/// Default TTL of exported records, in seconds.
+pub const DEFAULT_TTL: u32 = 3600;
+/// SOA refresh, retry, expire and minimum TTL, in seconds.
+pub const SOA_TIMERS: (u32, u32, u32, u32) = (3600, 900, 604800, 300);
+
+/// Zone of an apex name and the names beneath it that inherit its owner,
+/// read at a block.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Zone {
+	/// Apex name of the zone.
+	pub apex: Name,
+	/// Block number the zone is read at, used as the SOA serial.
+	pub number: BlockNumber,
+	/// Block hash the zone is read at.
+	pub hash: Hash,
+	/// Records of the apex and the names under it whose effective owner it
+	/// is. Owned names below the apex are zones of their own.
+	pub entries: Vec<(Name, Records)>,
+	/// Addresses of name servers outside the zone that are themselves Nomo
+	/// names. They are not valid zone data and are rendered as comments.
+	pub external_glue: Vec<(Name, Records)>,
+}
+
+impl Zone {
+	/// Read the zone of `apex`.
+	pub fn read<S: StorageRead>(
+		reader: &NomoReader<S>,
+		apex: &Name,
+		number: BlockNumber,
+		hash: Hash,
+	) -> Result<Self, Error> {
+		let entries = reader.owned_records(apex)?;
+
+		let mut external = entries
+			.iter()
+			.flat_map(|(_, records)| records.ns.iter())
+			.filter(|server| !entries.iter().any(|(name, _)| name == *server))
+			.cloned()
+			.collect::<Vec<_>>();
+		external.sort_by(name_order);
+		external.dedup();
+
+		let mut external_glue = Vec::new();
+		for server in external {
+			let records = reader.records(&server)?;
+			if !records.a.is_empty() || !records.aaaa.is_empty() {
+				external_glue.push((server, records));
+			}
+		}
+
+		Ok(Self {
+			apex: apex.clone(),
+			number,
+			hash,
+			entries,
+			external_glue,
+		})
+	}
+
+	/// Render the zone as a master file.
+	pub fn render(&self) -> String {
+		let mut out = String::new();
+		self.write(&mut out).expect("writing to a string never fails; qed");
+		out
+	}
+
+	fn write(&self, out: &mut String) -> fmt::Result {
+		let apex = absolute(&self.apex);
+		let apex_records = self
+			.entries
+			.iter()
+			.find(|(name, _)| name == &self.apex)
+			.map(|(_, records)| records);
+		let primary = apex_records
+			.and_then(|records| records.ns.first())
+			.map(absolute)
+			.unwrap_or_else(|| apex.clone());
+		let (refresh, retry, expire, minimum) = SOA_TIMERS;
+
+		writeln!(out, "; Nomo zone {}", apex)?;
+		writeln!(out, "; Exported at block #{} ({:?})", self.number, self.hash)?;
+		writeln!(out, "$ORIGIN {}", apex)?;
+		writeln!(out, "$TTL {}", DEFAULT_TTL)?;
+		writeln!(
+			out,
+			"@\tIN\tSOA\t{} {} {} {} {} {} {}",
+			primary,
+			hostmaster(&self.apex),
+			self.number,
+			refresh,
+			retry,
+			expire,
+			minimum
+		)?;
+
+		for (name, records) in &self.entries {
+			writeln!(out)?;
+			self.write_records(out, &self.relative(name), records)?;
+		}
+
+		if !self.external_glue.is_empty() {
+			writeln!(out)?;
+			writeln!(out, "; Addresses of name servers outside the zone")?;
+			for (name, records) in &self.external_glue {
+				for addr in &records.a {
+					writeln!(out, "; {}\tIN\tA\t{}", absolute(name), addr)?;
+				}
+				for addr in &records.aaaa {
+					writeln!(out, "; {}\tIN\tAAAA\t{}", absolute(name), addr)?;
+				}
+			}
+		}
+
+		Ok(())
+	}
+
+	fn write_records(&self, out: &mut String, owner: &str, records: &Records) -> fmt::Result {
+		for (set, external) in [
+			(records.icann, "ICANN"),
+			(records.opennic, "OpenNIC"),
+			(records.handshake, "Handshake"),
+		] {
+			if set {
+				writeln!(out, "; {} is also resolved through {}", owner, external)?;
+			}
+		}
+		// DS records of the apex belong to the zone above it.
+		for (rtype, rdata) in presentation(records)
+			.into_iter()
+			.filter(|(rtype, _)| *rtype != "DS" || owner != "@")
+		{
+			writeln!(out, "{}\tIN\t{}\t{}", owner, rtype, rdata)?;
+		}
+		if !records.rrsig.is_empty() {
+			writeln!(
+				out,
+				"; {} has {} pre-computed RRSIG records, which are not exported",
+				owner,
+				records.rrsig.len()
+			)?;
+		}
+		match records.dnssec {
+			Some(Dnssec::Keystore) => writeln!(out, "; {} is signed by node keystores", owner)?,
+			Some(Dnssec::Presigned) => writeln!(out, "; {} is signed by its owner", owner)?,
+			None => (),
+		}
+
+		Ok(())
+	}
+
+	/// Owner name relative to the apex.
+	fn relative(&self, name: &Name) -> String {
+		if name == &self.apex {
+			return "@".to_string()
+		}
+
+		let relative = Name(name.0[self.apex.0.len()..].to_vec());
+		String::from_utf8_lossy(&relative.to_dotted()).into_owned()
+	}
+}
+
+/// Type mnemonics and presentation-format data of the DNS records of a
+/// name, in the order they are exported.
+pub fn presentation(records: &Records) -> Vec<(&'static str, String)> {
+	let mut rrs = Vec::new();
+	rrs.extend(records.ns.iter().map(|server| ("NS", absolute(server))));
+	rrs.extend(records.a.iter().map(|addr| ("A", addr.to_string())));
+	rrs.extend(records.aaaa.iter().map(|addr| ("AAAA", addr.to_string())));
+	rrs.extend(
+		records
+			.cname
+			.iter()
+			.map(|target| ("CNAME", absolute(target))),
+	);
+	rrs.extend(records.mx.iter().map(|(preference, exchange)| {
+		("MX", format!("{} {}", preference, absolute(exchange)))
+	}));
+	rrs.extend(records.dnskey.iter().map(|key| {
+		(
+			"DNSKEY",
+			format!(
+				"{} 3 {} {}",
+				key.flags,
+				key.algorithm,
+				base64::encode(&key.public_key[..])
+			),
+		)
+	}));
+	rrs.extend(records.ds.iter().map(|ds| {
+		(
+			"DS",
+			format!(
+				"{} {} {} {}",
+				ds.key_tag,
+				ds.algorithm,
+				ds.digest_type,
+				ds.digest
+					.iter()
+					.map(|byte| format!("{:02X}", byte))
+					.collect::<String>()
+			),
+		)
+	}));
+	rrs
+}
+
+/// Absolute dotted name, with the trailing dot.
+pub fn absolute(name: &Name) -> String {
+	if name.is_root() {
+		return ".".to_string()
+	}
+
+	let mut dotted = String::from_utf8_lossy(&name.to_dotted()).into_owned();
+	dotted.push('.');
+	dotted
+}
+
+/// SOA responsible mailbox of an apex.
+pub fn hostmaster(apex: &Name) -> String {
+	if apex.is_root() {
+		"hostmaster.".to_string()
+	} else {
+		format!("hostmaster.{}", absolute(apex))
+	}
+}
+
+/// Error of a master file that cannot be imported.
+#[derive(thiserror::Error, Debug, Eq, PartialEq)]
+#[error("line {line}: {message}")]
+pub struct ParseError {
+	pub line: usize,
+	pub message: String,
+}
+
+/// Records of a master file, restricted to the types Nomo stores.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ParsedZone {
+	/// Records of the apex and names under it, ordered by depth and then by
+	/// dotted name.
+	pub entries: Vec<(Name, Records)>,
+	/// Lines that were not imported, with the reason.
+	pub skipped: Vec<(usize, String)>,
+}
+
+/// Parse a master file of the zone of `apex`. `$ORIGIN` defaults to the
+/// apex.
+///
+/// A, AAAA, NS, CNAME and MX records are imported. SOA records are ignored,
+/// as they are generated on export. Other types, and names outside the zone,
+/// are skipped. TTLs and classes are accepted but not stored.
+pub fn parse(input: &str, apex: &Name) -> Result<ParsedZone, ParseError> {
+	let mut origin = apex.clone();
+	let mut owner: Option<Name> = None;
+	let mut entries = BTreeMap::<Vec<u8>, (Name, Records)>::new();
+	let mut skipped = Vec::new();
+
+	for (line, continued, tokens) in logical_lines(input)? {
+		let error = |message: String| ParseError { line, message };
+		let mut tokens = tokens.into_iter().peekable();
+
+		let first = match tokens.peek() {
+			Some(first) => first.clone(),
+			None => continue,
+		};
+		match first.to_ascii_uppercase().as_str() {
+			"$ORIGIN" => {
+				tokens.next();
+				let raw = tokens
+					.next()
+					.ok_or_else(|| error("$ORIGIN without a name".into()))?;
+				origin =
+					resolve(&raw, &origin).ok_or_else(|| error(format!("invalid name {}", raw)))?;
+				continue
+			}
+			"$TTL" => continue,
+			"$INCLUDE" | "$GENERATE" => return Err(error(format!("{} is not supported", first))),
+			_ => (),
+		}
+
+		if !continued {
+			let raw = tokens.next().expect("peeked above; qed");
+			owner =
+				Some(resolve(&raw, &origin).ok_or_else(|| error(format!("invalid name {}", raw)))?);
+		}
+		let name = owner
+			.clone()
+			.ok_or_else(|| error("record without an owner name".into()))?;
+
+		let mut rtype = None;
+		for token in &mut tokens {
+			let upper = token.to_ascii_uppercase();
+			let is_ttl = token.chars().next().map_or(false, |c| c.is_ascii_digit());
+			if !is_ttl && !matches!(upper.as_str(), "IN" | "CH" | "HS" | "CS") {
+				rtype = Some(upper);
+				break
+			}
+		}
+		let rtype = rtype.ok_or_else(|| error("record without a type".into()))?;
+		let rdata = tokens.collect::<Vec<_>>();
+
+		if rtype == "SOA" {
+			continue
+		}
+		if !is_under(&name, apex) {
+			skipped.push((line, format!("{} is outside the zone", absolute(&name))));
+			continue
+		}
+
+		let arity = |count: usize| {
+			if rdata.len() == count {
+				Ok(())
+			} else {
+				Err(error(format!("{} expects {} fields", rtype, count)))
+			}
+		};
+		let target = |raw: &String| {
+			resolve(raw, &origin).ok_or_else(|| error(format!("invalid name {}", raw)))
+		};
+
+		let key = name.hash().as_bytes().to_vec();
+		let records = &mut entries
+			.entry(key)
+			.or_insert_with(|| (name.clone(), Records::default()))
+			.1;
+
+		match rtype.as_str() {
+			"A" => {
+				arity(1)?;
+				let addr = rdata[0]
+					.parse::<Ipv4Addr>()
+					.map_err(|_| error(format!("invalid IPv4 address {}", rdata[0])))?;
+				if !records.a.contains(&addr) {
+					records.a.push(addr);
+				}
+			}
+			"AAAA" => {
+				arity(1)?;
+				let addr = rdata[0]
+					.parse::<Ipv6Addr>()
+					.map_err(|_| error(format!("invalid IPv6 address {}", rdata[0])))?;
+				if !records.aaaa.contains(&addr) {
+					records.aaaa.push(addr);
+				}
+			}
+			"NS" => {
+				arity(1)?;
+				let server = target(&rdata[0])?;
+				if !records.ns.contains(&server) {
+					records.ns.push(server);
+				}
+			}
+			"CNAME" => {
+				arity(1)?;
+				let cname = target(&rdata[0])?;
+				if records
+					.cname
+					.as_ref()
+					.map_or(false, |current| current != &cname)
+				{
+					return Err(error(format!(
+						"{} has more than one CNAME",
+						absolute(&name)
+					)));
+				}
+				records.cname = Some(cname);
+			}
+			"MX" => {
+				arity(2)?;
+				let preference = rdata[0]
+					.parse::<u16>()
+					.map_err(|_| error(format!("invalid MX preference {}", rdata[0])))?;
+				let mx = (preference, target(&rdata[1])?);
+				if records.mx.as_ref().map_or(false, |current| current != &mx) {
+					return Err(error(format!(
+						"{} has more than one MX, but Nomo stores only one",
+						absolute(&name)
+					)));
+				}
+				records.mx = Some(mx);
+			}
+			_ => skipped.push((line, format!("{} records are not supported", rtype))),
+		}
+	}
+
+	let mut entries = entries
+		.into_iter()
+		.map(|(_, entry)| entry)
+		.filter(|(_, records)| !records.is_empty())
+		.collect::<Vec<_>>();
+	entries.sort_by(|(a, _), (b, _)| name_order(a, b));
+
+	Ok(ParsedZone { entries, skipped })
+}
+
+/// Resolve a possibly relative name of a master file against the origin.
+fn resolve(raw: &str, origin: &Name) -> Option<Name> {
+	if raw == "@" {
+		return Some(origin.clone())
+	}
+	if raw.ends_with('.') {
+		return Name::from_dotted(raw.as_bytes())
+	}
+
+	let mut name = Name::from_dotted(raw.as_bytes())?;
+	let mut labels = origin.0.clone();
+	labels.append(&mut name.0);
+	name.0 = labels;
+	Some(name)
+}
+
+/// Split a master file into logical lines, joining parenthesized records
+/// and dropping comments. Each line comes with its first line number and
+/// whether it starts with whitespace, continuing the previous owner.
+fn logical_lines(input: &str) -> Result<Vec<(usize, bool, Vec<String>)>, ParseError> {
+	let mut lines = Vec::new();
+	let mut current: Option<(usize, bool, Vec<String>)> = None;
+	let mut depth = 0usize;
+
+	for (index, raw) in input.lines().enumerate() {
+		let line = index + 1;
+		let (tokens, opened, closed) =
+			tokenize(raw).map_err(|message| ParseError { line, message })?;
+
+		let entry = current.get_or_insert_with(|| {
+			(
+				line,
+				raw.starts_with(|c: char| c.is_whitespace()),
+				Vec::new(),
+			)
+		});
+		entry.2.extend(tokens);
+
+		depth += opened;
+		depth = depth.checked_sub(closed).ok_or_else(|| ParseError {
+			line,
+			message: "unbalanced parentheses".into(),
+		})?;
+
+		if depth == 0 {
+			let entry = current.take().expect("inserted above; qed");
+			if !entry.2.is_empty() {
+				lines.push(entry);
+			}
+		}
+	}
+
+	match current {
+		Some((line, _, _)) => Err(ParseError {
+			line,
+			message: "unclosed parentheses".into(),
+		}),
+		None => Ok(lines),
+	}
+}
+
+/// Tokens of a physical line, with the number of opened and closed
+/// parentheses.
+fn tokenize(raw: &str) -> Result<(Vec<String>, usize, usize), String> {
+	let mut tokens = Vec::new();
+	let (mut opened, mut closed) = (0, 0);
+	let mut token = String::new();
+	let mut quoted = false;
+	let mut chars = raw.chars();
+
+	while let Some(c) = chars.next() {
+		match c {
+			'\\' => {
+				token.push(c);
+				token.extend(chars.next());
+			}
+			'"' => {
+				token.push(c);
+				quoted = !quoted;
+			}
+			_ if quoted => token.push(c),
+			';' => break,
+			'(' | ')' => {
+				if c == '(' {
+					opened += 1;
+				} else {
+					closed += 1;
+				}
+				if !token.is_empty() {
+					tokens.push(std::mem::take(&mut token));
+				}
+			}
+			_ if c.is_whitespace() => {
+				if !token.is_empty() {
+					tokens.push(std::mem::take(&mut token));
+				}
+			}
+			_ => token.push(c),
+		}
+	}
+
+	if quoted {
+		return Err("unterminated quoted string".into())
+	}
+	if !token.is_empty() {
+		tokens.push(token);
+	}
+
+	Ok((tokens, opened, closed))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use codec::{Decode, Encode};
+	use np_domain::{Label, NameHash, NameValue};
+	use sp_core::hashing::twox_128;
+	use std::collections::BTreeMap;
+
+	#[derive(Default)]
+	struct MemoryStorage(BTreeMap<Vec<u8>, Vec<u8>>);
+
+	impl MemoryStorage {
+		/// Insert a zone entry, counting it in `Subnames` like the pallet
+		/// does for records.
+		fn insert<T: Encode>(&mut self, item: &str, name: &str, value: T) {
+			let name = Name::from_dotted(name.as_bytes()).unwrap();
+			let mut key = twox_128(b"Zone").to_vec();
+			key.extend_from_slice(&twox_128(item.as_bytes()));
+			key.extend_from_slice(name.hash().as_bytes());
+			self.0.insert(key, NameValue::some(name.clone(), value).encode());
+
+			if matches!(item, "ICANNs" | "OpenNICs" | "Handshakes") {
+				return
+			}
+			let mut parent = NameHash::default();
+			for label in name.0 {
+				let mut key = twox_128(b"Zone").to_vec();
+				key.extend_from_slice(&twox_128(b"Subnames"));
+				key.extend_from_slice(parent.as_bytes());
+				key.extend_from_slice(label.hash().as_bytes());
+				let count = self
+					.0
+					.get(&key)
+					.map(|raw| <(Label, u32)>::decode(&mut &raw[..]).unwrap().1)
+					.unwrap_or_default();
+				parent = Name::child_hash(&parent, &label.hash());
+				self.0.insert(key, (label, count + 1).encode());
+			}
+		}
+	}
+
+	impl MemoryStorage {
+		fn own(&mut self, name: &str) {
+			let name = Name::from_dotted(name.as_bytes()).unwrap();
+			let mut key = twox_128(b"Registry").to_vec();
+			key.extend_from_slice(&twox_128(b"Ownerships"));
+			key.extend_from_slice(name.hash().as_bytes());
+			self.0
+				.insert(key, NameValue::some(name, crate::Ownership::Root).encode());
+		}
+	}
+
+	impl StorageRead for MemoryStorage {
+		fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>, Error> {
+			Ok(self.0.get(key).cloned())
+		}
+
+		fn pairs(&self, prefix: &[u8]) -> Result<Vec<(Vec<u8>, Vec<u8>)>, Error> {
+			Ok(self
+				.0
+				.iter()
+				.filter(|(key, _)| key.starts_with(prefix))
+				.map(|(key, value)| (key.clone(), value.clone()))
+				.collect())
+		}
+	}
+
+	fn name(dotted: &str) -> Name {
+		Name::from_dotted(dotted.as_bytes()).unwrap()
+	}
+
+	#[test]
+	fn renders_zone_with_glue() {
+		let mut storage = MemoryStorage::default();
+		storage.insert(
+			"NSs",
+			"example.neatuser",
+			vec![name("ns1.example.neatuser"), name("ns.provider.neatuser")],
+		);
+		storage.insert("As", "ns1.example.neatuser", vec![0x0a000001u32]);
+		storage.insert("As", "ns.provider.neatuser", vec![0x0a000002u32]);
+		storage.insert("As", "www.example.neatuser", vec![0x0a000003u32]);
+		storage.insert("MXs", "example.neatuser", (10u16, name("mail.example.neatuser")));
+		storage.insert("ICANNs", "example.neatuser", ());
+		storage.insert("As", "other.neatuser", vec![0x0a000004u32]);
+
+		let zone = Zone::read(
+			&NomoReader::new(storage),
+			&name("example.neatuser"),
+			42,
+			Hash::default(),
+		)
+		.unwrap();
+
+		assert_eq!(
+			zone.render(),
+			"; Nomo zone example.neatuser.\n\
+			 ; Exported at block #42 (0x0000000000000000000000000000000000000000000000000000000000000000)\n\
+			 $ORIGIN example.neatuser.\n\
+			 $TTL 3600\n\
+			 @\tIN\tSOA\tns1.example.neatuser. hostmaster.example.neatuser. 42 3600 900 604800 300\n\
+			 \n\
+			 ; @ is also resolved through ICANN\n\
+			 @\tIN\tNS\tns1.example.neatuser.\n\
+			 @\tIN\tNS\tns.provider.neatuser.\n\
+			 @\tIN\tMX\t10 mail.example.neatuser.\n\
+			 \n\
+			 ns1\tIN\tA\t10.0.0.1\n\
+			 \n\
+			 www\tIN\tA\t10.0.0.3\n\
+			 \n\
+			 ; Addresses of name servers outside the zone\n\
+			 ; ns.provider.neatuser.\tIN\tA\t10.0.0.2\n"
+		);
+	}
+
+	#[test]
+	fn leaves_out_owned_names() {
+		let mut storage = MemoryStorage::default();
+		storage.own("example.neatuser");
+		storage.own("shop.example.neatuser");
+		storage.insert(
+			"NSs",
+			"example.neatuser",
+			vec![name("ns.shop.example.neatuser")],
+		);
+		storage.insert("As", "www.example.neatuser", vec![0x0a000001u32]);
+		storage.insert("As", "shop.example.neatuser", vec![0x0a000002u32]);
+		storage.insert("As", "ns.shop.example.neatuser", vec![0x0a000003u32]);
+
+		let zone = Zone::read(
+			&NomoReader::new(storage),
+			&name("example.neatuser"),
+			1,
+			Hash::default(),
+		)
+		.unwrap();
+
+		assert_eq!(
+			zone.entries
+				.iter()
+				.map(|(name, _)| name.clone())
+				.collect::<Vec<_>>(),
+			vec![name("example.neatuser"), name("www.example.neatuser")]
+		);
+		assert_eq!(
+			zone.external_glue,
+			vec![(
+				name("ns.shop.example.neatuser"),
+				Records {
+					a: vec![Ipv4Addr::new(10, 0, 0, 3)],
+					..Default::default()
+				}
+			)]
+		);
+	}
+
+	#[test]
+	fn renders_empty_zone() {
+		let zone = Zone::read(
+			&NomoReader::new(MemoryStorage::default()),
+			&name("neatuser"),
+			1,
+			Hash::default(),
+		)
+		.unwrap();
+
+		assert!(zone.entries.is_empty());
+		assert!(zone
+			.render()
+			.contains("@\tIN\tSOA\tneatuser. hostmaster.neatuser. 1 3600 900 604800 300\n"));
+	}
+
+	#[test]
+	fn parses_master_file() {
+		let parsed = parse(
+			"$ORIGIN example.neatuser.\n\
+			 $TTL 3600\n\
+			 @ IN SOA ns1 hostmaster ( 1 3600 900\n\
+			 \t604800 300 ) ; timers\n\
+			 @ 300 IN NS ns1\n\
+			 \tIN NS ns.provider.neatuser.\n\
+			 \tMX 10 mail\n\
+			 \tTXT \"v=spf1 ; -all\"\n\
+			 ns1 A 10.0.0.1\n\
+			 www IN 60 AAAA ::1\n\
+			 www.other.neatuser. A 10.0.0.2\n",
+			&name("example.neatuser"),
+		)
+		.unwrap();
+
+		assert_eq!(
+			parsed.entries,
+			vec![
+				(
+					name("example.neatuser"),
+					Records {
+						ns: vec![name("ns1.example.neatuser"), name("ns.provider.neatuser")],
+						mx: Some((10, name("mail.example.neatuser"))),
+						..Default::default()
+					}
+				),
+				(
+					name("ns1.example.neatuser"),
+					Records {
+						a: vec![Ipv4Addr::new(10, 0, 0, 1)],
+						..Default::default()
+					}
+				),
+				(
+					name("www.example.neatuser"),
+					Records {
+						aaaa: vec![Ipv6Addr::LOCALHOST],
+						..Default::default()
+					}
+				),
+			]
+		);
+		assert_eq!(
+			parsed.skipped,
+			vec![
+				(8, "TXT records are not supported".to_string()),
+				(11, "www.other.neatuser. is outside the zone".to_string()),
+			]
+		);
+	}
+
+	#[test]
+	fn rejects_second_mx() {
+		assert_eq!(
+			parse("@ MX 10 mx1\n@ MX 20 mx2\n", &name("example.neatuser")),
+			Err(ParseError {
+				line: 2,
+				message: "example.neatuser. has more than one MX, but Nomo stores only one".into(),
+			})
+		);
+	}
+
+	#[test]
+	fn rejects_unclosed_parentheses() {
+		assert_eq!(
+			parse("@ SOA ns1 hostmaster ( 1\n", &name("example.neatuser")),
+			Err(ParseError {
+				line: 1,
+				message: "unclosed parentheses".into(),
+			})
+		);
+	}
+
+	#[test]
+	fn exported_zone_parses_back() {
+		let mut storage = MemoryStorage::default();
+		storage.insert("NSs", "example.neatuser", vec![name("ns1.example.neatuser")]);
+		storage.insert("As", "ns1.example.neatuser", vec![0x0a000001u32]);
+		storage.insert("CNAMEs", "www.example.neatuser", name("example.neatuser"));
+
+		let apex = name("example.neatuser");
+		let zone = Zone::read(&NomoReader::new(storage), &apex, 7, Hash::default()).unwrap();
+		let parsed = parse(&zone.render(), &apex).unwrap();
+
+		assert_eq!(parsed.entries, zone.entries);
+		assert!(parsed.skipped.is_empty());
+	}
+}
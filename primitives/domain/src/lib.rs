@@ -80,6 +80,7 @@ impl<T> NameValue<T> {
 }
 
 pub type NameHash = H256;
+pub type LabelHash = H256;
 
 /// A domain name. It's a list of labels, with the top-level one in the front.
 #[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
@@ -92,17 +93,23 @@ impl Name {
 		let mut current = H256::default();
 
 		for label in &self.0 {
-			let mut input = [0u8; 64];
-
-			input[0..32].copy_from_slice(&current[..]);
-			input[32..64].copy_from_slice(&label.hash()[..]);
-
-			current = H256::from_slice(blake2b(32, &[], &input).as_bytes());
+			current = Self::child_hash(&current, &label.hash());
 		}
 
 		current
 	}
 
+	/// Get the name hash of a child, from the name hash of its parent and
+	/// its label hash.
+	pub fn child_hash(parent: &NameHash, label: &LabelHash) -> NameHash {
+		let mut input = [0u8; 64];
+
+		input[0..32].copy_from_slice(&parent[..]);
+		input[32..64].copy_from_slice(&label[..]);
+
+		H256::from_slice(blake2b(32, &[], &input).as_bytes())
+	}
+
 	/// Get parent of current name.
 	pub fn parent(&self) -> Option<Name> {
 		let mut parent = self.clone();
@@ -116,6 +123,40 @@ impl Name {
 	pub fn is_root(&self) -> bool {
 		self.0.len() == 0
 	}
+
+	/// Parse a dotted name, such as `example.neatuser`. Both the empty string
+	/// and `.` are the root name, and a single trailing dot is accepted.
+	pub fn from_dotted(raw: &[u8]) -> Option<Name> {
+		let raw = raw.strip_suffix(b".").unwrap_or(raw);
+		if raw.is_empty() {
+			return Some(Name::default());
+		}
+
+		let mut labels = Vec::new();
+		for raw_label in raw.rsplit(|c| *c == b'.') {
+			labels.push(Label::try_from(raw_label.to_vec()).ok()?);
+		}
+
+		Some(Name(labels))
+	}
+
+	/// Dotted representation of the name, without the trailing dot. The root
+	/// name is represented as `.`.
+	pub fn to_dotted(&self) -> Vec<u8> {
+		if self.is_root() {
+			return b".".to_vec();
+		}
+
+		let mut dotted = Vec::new();
+		for (i, label) in self.0.iter().rev().enumerate() {
+			if i != 0 {
+				dotted.push(b'.');
+			}
+			dotted.extend_from_slice(&label.0);
+		}
+
+		dotted
+	}
 }
 
 /// A domain label.
@@ -158,7 +199,7 @@ impl<'de> Deserialize<'de> for Label {
 
 impl Label {
 	/// Get the label hash of a string.
-	pub fn hash(&self) -> H256 {
+	pub fn hash(&self) -> LabelHash {
 		H256::from_slice(blake2b(32, &[], &self.0).as_bytes())
 	}
 }
@@ -174,3 +215,55 @@ impl TryFrom<RawLabel> for Label {
 		Ok(Label(value))
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn label(raw: &str) -> Label {
+		Label::try_from(raw.as_bytes().to_vec()).unwrap()
+	}
+
+	#[test]
+	fn dotted_names_round_trip() {
+		let name = Name::from_dotted(b"www.example.neatuser").unwrap();
+		assert_eq!(
+			name,
+			Name(vec![label("neatuser"), label("example"), label("www")])
+		);
+		assert_eq!(name.to_dotted(), b"www.example.neatuser".to_vec());
+		assert_eq!(Name::from_dotted(&name.to_dotted()), Some(name.clone()));
+		assert_eq!(Name::from_dotted(b"www.example.neatuser."), Some(name));
+
+		assert_eq!(Name::from_dotted(b""), Some(Name::default()));
+		assert_eq!(Name::from_dotted(b"."), Some(Name::default()));
+		assert_eq!(Name::default().to_dotted(), b".".to_vec());
+		assert_eq!(
+			Name::from_dotted(&Name::default().to_dotted()),
+			Some(Name::default())
+		);
+	}
+
+	#[test]
+	fn rejects_invalid_labels() {
+		for raw in [
+			&b"Example.neatuser"[..],
+			b"example..neatuser",
+			b".example.neatuser",
+			b"example.neatuser..",
+			b"-example.neatuser",
+			b"example-.neatuser",
+			b"1example.neatuser",
+			b"ex_ample.neatuser",
+			"exämple.neatuser".as_bytes(),
+		] {
+			assert_eq!(Name::from_dotted(raw), None, "{:?}", raw);
+		}
+
+		assert!(Label::decode(&mut &b"-".to_vec().encode()[..]).is_err());
+		assert_eq!(
+			Label::decode(&mut &b"x-1".to_vec().encode()[..]).ok(),
+			Some(label("x-1"))
+		);
+	}
+}
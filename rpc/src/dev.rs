@@ -0,0 +1,110 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// This file is part of Neatcoin.
+//
+// Copyright (c) 2021 Wei Tang.
+//
+// Neatcoin is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Neatcoin is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Neatcoin. If not, see <http://www.gnu.org/licenses/>.
+
+//! RPC methods of manual-seal development nodes.
+
+use jsonrpc_core::{Error as RpcError, ErrorCode, Result};
+use jsonrpc_derive::rpc;
+use std::sync::{
+	atomic::{AtomicU64, Ordering},
+	Arc,
+};
+
+/// Clock offset applied to the timestamps of sealed blocks.
+#[derive(Clone, Default, Debug)]
+pub struct TimeWarp(Arc<AtomicU64>);
+
+impl TimeWarp {
+	/// Current offset in milliseconds.
+	pub fn offset(&self) -> u64 {
+		self.0.load(Ordering::SeqCst)
+	}
+
+	/// Move the clock forward, returning the new offset. Returns `None` on
+	/// overflow.
+	pub fn advance(&self, millis: u64) -> Option<u64> {
+		self.0
+			.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |offset| {
+				offset.checked_add(millis)
+			})
+			.ok()
+			.map(|offset| offset + millis)
+	}
+}
+
+/// Development RPC methods.
+#[rpc]
+pub trait DevApi {
+	/// Move the clock of blocks sealed from now on forward by `millis`
+	/// milliseconds. Returns the total offset. Block numbers are unaffected,
+	/// so periods counted in blocks only advance by sealing blocks.
+	///
+	/// Warps of one epoch or more are rejected, as the next sealed block
+	/// would skip an epoch, which BABE does not allow. Warp in smaller steps
+	/// and seal a block in between instead.
+	#[rpc(name = "engine_warp")]
+	fn warp(&self, millis: u64) -> Result<u64>;
+
+	/// Total clock offset in milliseconds.
+	#[rpc(name = "engine_warpOffset")]
+	fn warp_offset(&self) -> Result<u64>;
+}
+
+/// An implementation of development RPC methods.
+pub struct Dev {
+	warp: TimeWarp,
+	epoch_millis: u64,
+}
+
+impl Dev {
+	/// Create new `Dev` controlling the given clock offset, with epochs of
+	/// `epoch_millis` milliseconds.
+	pub fn new(warp: TimeWarp, epoch_millis: u64) -> Self {
+		Dev { warp, epoch_millis }
+	}
+}
+
+/// Error code of a clock offset overflow.
+const WARP_OVERFLOW: i64 = 1;
+/// Error code of a warp of one epoch or more.
+const WARP_TOO_LONG: i64 = 2;
+
+impl DevApi for Dev {
+	fn warp(&self, millis: u64) -> Result<u64> {
+		if millis >= self.epoch_millis {
+			return Err(RpcError {
+				code: ErrorCode::ServerError(WARP_TOO_LONG),
+				message: format!(
+					"Warp must be shorter than one epoch ({} ms). Warp in smaller steps and seal a block in between.",
+					self.epoch_millis,
+				),
+				data: None,
+			})
+		}
+
+		self.warp.advance(millis).ok_or_else(|| RpcError {
+			code: ErrorCode::ServerError(WARP_OVERFLOW),
+			message: "Clock offset overflows.".into(),
+			data: None,
+		})
+	}
+
+	fn warp_offset(&self) -> Result<u64> {
+		Ok(self.warp.offset())
+	}
+}
@@ -16,13 +16,18 @@
 // You should have received a copy of the GNU General Public License
 // along with Neatcoin. If not, see <http://www.gnu.org/licenses/>.
 
+pub mod dev;
+pub mod nomo;
+
 use std::sync::Arc;
 
+use futures::channel::mpsc::Sender;
 use np_opaque::{AccountId, Balance, Block, BlockNumber, Hash, Nonce};
-use sc_client_api::AuxStore;
+use sc_client_api::{AuxStore, BlockchainEvents, StorageProvider};
 use sc_consensus_babe::{Config, Epoch};
 use sc_consensus_babe_rpc::BabeRpcHandler;
 use sc_consensus_epochs::SharedEpochChanges;
+use sc_consensus_manual_seal::EngineCommand;
 use sc_finality_grandpa::{
 	FinalityProofProvider, GrandpaJustificationStream, SharedAuthoritySet, SharedVoterState,
 };
@@ -93,6 +98,28 @@ pub struct FullDeps<C, P, SC, B> {
 	pub babe: BabeDeps,
 	/// GRANDPA specific dependencies.
 	pub grandpa: GrandpaDeps<B>,
+	/// Decoder of name changes from the events of the runtime.
+	pub name_events: nomo::NameEvents,
+}
+
+/// Manual-seal development node dependencies.
+pub struct DevDeps<C, P> {
+	/// The client instance to use.
+	pub client: Arc<C>,
+	/// Transaction pool instance.
+	pub pool: Arc<P>,
+	/// Whether to deny unsafe calls
+	pub deny_unsafe: DenyUnsafe,
+	/// Channel for sending commands to the manual-seal authorship task.
+	pub command_sink: Sender<EngineCommand<Hash>>,
+	/// Clock offset of sealed blocks.
+	pub time_warp: dev::TimeWarp,
+	/// Epoch duration in milliseconds, bounding each clock warp.
+	pub epoch_millis: u64,
+	/// Executor to drive the subscription manager in the Nomo RPC handler.
+	pub subscription_executor: SubscriptionTaskExecutor,
+	/// Decoder of name changes from the events of the runtime.
+	pub name_events: nomo::NameEvents,
 }
 
 /// A IO handler that uses all Full RPC extensions.
@@ -107,11 +134,14 @@ where
 		+ HeaderBackend<Block>
 		+ AuxStore
 		+ HeaderMetadata<Block, Error = BlockChainError>
+		+ StorageProvider<Block, B>
+		+ BlockchainEvents<Block>
 		+ Sync
 		+ Send
 		+ 'static,
 	C::Api: substrate_frame_rpc_system::AccountNonceApi<Block, AccountId, Nonce>,
 	C::Api: pallet_contracts_rpc::ContractsRuntimeApi<Block, AccountId, Balance, BlockNumber, Hash>,
+	C::Api: pallet_eons_rpc::EonsRuntimeApi<Block, Hash>,
 	C::Api: pallet_transaction_payment_rpc::TransactionPaymentRuntimeApi<Block, Balance>,
 	C::Api: BabeApi<Block>,
 	C::Api: BlockBuilder<Block>,
@@ -120,7 +150,9 @@ where
 	B: sc_client_api::Backend<Block> + Send + Sync + 'static,
 	B::State: sc_client_api::backend::StateBackend<sp_runtime::traits::HashFor<Block>>,
 {
+	use self::nomo::{Nomo, NomoApi};
 	use pallet_contracts_rpc::{Contracts, ContractsApi};
+	use pallet_eons_rpc::{Eons, EonsApi};
 	use pallet_transaction_payment_rpc::{TransactionPayment, TransactionPaymentApi};
 	use substrate_frame_rpc_system::{FullSystem, SystemApi};
 
@@ -133,6 +165,7 @@ where
 		deny_unsafe,
 		babe,
 		grandpa,
+		name_events,
 	} = deps;
 
 	let BabeDeps {
@@ -157,6 +190,13 @@ where
 	// more context: https://github.com/paritytech/substrate/pull/3480
 	// These RPCs should use an asynchronous caller instead.
 	io.extend_with(ContractsApi::to_delegate(Contracts::new(client.clone())));
+	io.extend_with(EonsApi::to_delegate(Eons::new(client.clone())));
+	io.extend_with(NomoApi::to_delegate(Nomo::<_, B>::new(
+		client.clone(),
+		subscription_executor.clone(),
+		name_events,
+		deny_unsafe,
+	)));
 	io.extend_with(TransactionPaymentApi::to_delegate(TransactionPayment::new(
 		client.clone(),
 	)));
@@ -193,6 +233,69 @@ where
 	Ok(io)
 }
 
+/// Instantiate all RPC extensions of manual-seal development nodes.
+pub fn create_dev<C, P, B>(deps: DevDeps<C, P>) -> RpcExtension
+where
+	C: ProvideRuntimeApi<Block>
+		+ HeaderBackend<Block>
+		+ HeaderMetadata<Block, Error = BlockChainError>
+		+ StorageProvider<Block, B>
+		+ BlockchainEvents<Block>
+		+ Sync
+		+ Send
+		+ 'static,
+	C::Api: substrate_frame_rpc_system::AccountNonceApi<Block, AccountId, Nonce>,
+	C::Api: pallet_contracts_rpc::ContractsRuntimeApi<Block, AccountId, Balance, BlockNumber, Hash>,
+	C::Api: pallet_eons_rpc::EonsRuntimeApi<Block, Hash>,
+	C::Api: pallet_transaction_payment_rpc::TransactionPaymentRuntimeApi<Block, Balance>,
+	C::Api: BlockBuilder<Block>,
+	P: TransactionPool + 'static,
+	B: sc_client_api::Backend<Block> + Send + Sync + 'static,
+{
+	use self::{
+		dev::{Dev, DevApi},
+		nomo::{Nomo, NomoApi},
+	};
+	use pallet_contracts_rpc::{Contracts, ContractsApi};
+	use pallet_eons_rpc::{Eons, EonsApi};
+	use pallet_transaction_payment_rpc::{TransactionPayment, TransactionPaymentApi};
+	use sc_consensus_manual_seal::rpc::{ManualSeal, ManualSealApi};
+	use substrate_frame_rpc_system::{FullSystem, SystemApi};
+
+	let mut io = jsonrpc_core::IoHandler::default();
+	let DevDeps {
+		client,
+		pool,
+		deny_unsafe,
+		command_sink,
+		time_warp,
+		epoch_millis,
+		subscription_executor,
+		name_events,
+	} = deps;
+
+	io.extend_with(SystemApi::to_delegate(FullSystem::new(
+		client.clone(),
+		pool,
+		deny_unsafe,
+	)));
+	io.extend_with(ContractsApi::to_delegate(Contracts::new(client.clone())));
+	io.extend_with(EonsApi::to_delegate(Eons::new(client.clone())));
+	io.extend_with(NomoApi::to_delegate(Nomo::<_, B>::new(
+		client.clone(),
+		subscription_executor,
+		name_events,
+		deny_unsafe,
+	)));
+	io.extend_with(TransactionPaymentApi::to_delegate(TransactionPayment::new(
+		client,
+	)));
+	io.extend_with(ManualSealApi::to_delegate(ManualSeal::new(command_sink)));
+	io.extend_with(DevApi::to_delegate(Dev::new(time_warp, epoch_millis)));
+
+	io
+}
+
 /// Instantiate all Light RPC extensions.
 pub fn create_light<C, P, F>(deps: LightDeps<C, F, P>) -> RpcExtension
 where
@@ -0,0 +1,425 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// This file is part of Neatcoin.
+//
+// Copyright (c) 2021 Wei Tang.
+//
+// Neatcoin is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Neatcoin is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Neatcoin. If not, see <http://www.gnu.org/licenses/>.
+
+//! RPC methods reading Nomo names and zones, and subscriptions to changes
+//! of names.
+
+use futures::{future, stream, stream::BoxStream, FutureExt, SinkExt, StreamExt};
+use jsonrpc_core::{Error as RpcError, ErrorCode, Result};
+use jsonrpc_derive::rpc;
+use jsonrpc_pubsub::{manager::SubscriptionManager, typed::Subscriber, SubscriptionId};
+use neatcoin_nomo::{
+	is_under, name_order,
+	zonefile::{presentation, Zone},
+	ClientStorage, NomoReader, Ownership, Records,
+};
+use np_domain::Name;
+use np_opaque::{Balance, Block, BlockNumber, Hash};
+use pallet_fcfs::RenewalInfo;
+use pallet_zone::Dnssec;
+use sc_client_api::{Backend, BlockchainEvents, StorageProvider};
+use sc_rpc::SubscriptionTaskExecutor;
+use sc_rpc_api::DenyUnsafe;
+use serde::{Deserialize, Serialize};
+use sp_blockchain::HeaderBackend;
+use sp_runtime::{generic::BlockId, traits::Header as _};
+use std::{marker::PhantomData, sync::Arc};
+
+/// Nomo RPC methods.
+#[rpc]
+pub trait NomoApi<BlockHash> {
+	/// RPC metadata
+	type Metadata;
+
+	/// RFC 1035 master file of the zone of a dotted apex name and all names
+	/// beneath it. Unsafe, as it scans all names in state.
+	#[rpc(name = "nomo_zoneFile")]
+	fn zone_file(&self, apex: String, at: Option<BlockHash>) -> Result<String>;
+
+	/// Subscribe to ownership, renewal and record changes of a dotted name.
+	/// Changes are pushed when their block is imported as the best block and
+	/// again when it is finalized, or only when finalized if `finalized` is
+	/// set.
+	#[pubsub(subscription = "nomo_name", subscribe, name = "nomo_subscribeName")]
+	fn subscribe_name(
+		&self,
+		metadata: Self::Metadata,
+		subscriber: Subscriber<NameChange<BlockHash>>,
+		name: String,
+		finalized: Option<bool>,
+	);
+
+	/// Unsubscribe from changes of a name.
+	#[pubsub(subscription = "nomo_name", unsubscribe, name = "nomo_unsubscribeName")]
+	fn unsubscribe_name(
+		&self,
+		metadata: Option<Self::Metadata>,
+		id: SubscriptionId,
+	) -> Result<bool>;
+
+	/// Subscribe to changes of a dotted name and all names beneath it, like
+	/// `nomo_subscribeName`.
+	#[pubsub(
+		subscription = "nomo_subtree",
+		subscribe,
+		name = "nomo_subscribeSubtree"
+	)]
+	fn subscribe_subtree(
+		&self,
+		metadata: Self::Metadata,
+		subscriber: Subscriber<NameChange<BlockHash>>,
+		name: String,
+		finalized: Option<bool>,
+	);
+
+	/// Unsubscribe from changes of a subtree.
+	#[pubsub(
+		subscription = "nomo_subtree",
+		unsubscribe,
+		name = "nomo_unsubscribeSubtree"
+	)]
+	fn unsubscribe_subtree(
+		&self,
+		metadata: Option<Self::Metadata>,
+		id: SubscriptionId,
+	) -> Result<bool>;
+}
+
+/// Kind of change an event makes to a name.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd)]
+pub enum NameChangeKind {
+	/// Explicit ownership, set in the registry.
+	Ownership,
+	/// FCFS registration, renewal or expiry.
+	Renewal,
+	/// Zone records, external namespace flags or DNSSEC data.
+	Records,
+}
+
+/// Names changed by a block and how, decoded from the raw `System::Events`
+/// storage value with the event type of a runtime.
+pub type NameEvents =
+	fn(&[u8]) -> std::result::Result<Vec<(Name, NameChangeKind)>, parity_scale_codec::Error>;
+
+/// Change of a name pushed to subscribers.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NameChange<Hash> {
+	/// Hash of the block the change is in.
+	pub block: Hash,
+	/// Number of the block the change is in.
+	pub number: BlockNumber,
+	/// Whether the block is finalized.
+	pub finalized: bool,
+	/// Dotted name that changed.
+	pub name: String,
+	/// Value of the name after the change.
+	#[serde(flatten)]
+	pub value: ChangedValue,
+}
+
+/// Value of a changed name, tagged with the kind of change.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum ChangedValue {
+	/// Explicit owner of the name.
+	Ownership { owner: Option<Ownership> },
+	/// FCFS renewal info of the name.
+	Renewal {
+		renewal: Option<RenewalInfo<BlockNumber, Balance>>,
+	},
+	/// Zone records of the name in presentation format, the external
+	/// namespaces it is resolved through, and how it is signed.
+	Records {
+		records: Vec<String>,
+		external: Vec<String>,
+		dnssec: Option<String>,
+	},
+}
+
+impl From<&Records> for ChangedValue {
+	fn from(records: &Records) -> Self {
+		ChangedValue::Records {
+			records: presentation(records)
+				.into_iter()
+				.map(|(rtype, rdata)| format!("{} {}", rtype, rdata))
+				.collect(),
+			external: [
+				(records.icann, "ICANN"),
+				(records.opennic, "OpenNIC"),
+				(records.handshake, "Handshake"),
+			]
+			.iter()
+			.filter(|(set, _)| *set)
+			.map(|(_, external)| external.to_string())
+			.collect(),
+			dnssec: records.dnssec.as_ref().map(|dnssec| {
+				match dnssec {
+					Dnssec::Keystore => "keystore",
+					Dnssec::Presigned => "presigned",
+				}
+				.to_string()
+			}),
+		}
+	}
+}
+
+/// An implementation of Nomo RPC methods.
+pub struct Nomo<C, B> {
+	client: Arc<C>,
+	manager: SubscriptionManager,
+	events: NameEvents,
+	deny_unsafe: DenyUnsafe,
+	_marker: PhantomData<B>,
+}
+
+impl<C, B> Nomo<C, B> {
+	/// Create new `Nomo` with the given reference to the client, the
+	/// executor of subscriptions, the event decoder of the runtime and
+	/// whether unsafe methods are denied.
+	pub fn new(
+		client: Arc<C>,
+		executor: SubscriptionTaskExecutor,
+		events: NameEvents,
+		deny_unsafe: DenyUnsafe,
+	) -> Self {
+		Nomo {
+			client,
+			manager: SubscriptionManager::new(Arc::new(executor)),
+			events,
+			deny_unsafe,
+			_marker: PhantomData,
+		}
+	}
+}
+
+/// Error code of a failed storage read.
+const STORAGE_ERROR: i64 = 1;
+
+fn storage_error<E: std::fmt::Debug>(e: E) -> RpcError {
+	RpcError {
+		code: ErrorCode::ServerError(STORAGE_ERROR),
+		message: "Unable to read Nomo storage.".into(),
+		data: Some(format!("{:?}", e).into()),
+	}
+}
+
+impl<C, B> Nomo<C, B>
+where
+	C: StorageProvider<Block, B> + HeaderBackend<Block> + BlockchainEvents<Block>,
+	C: Send + Sync + 'static,
+	B: Backend<Block> + Send + Sync + 'static,
+{
+	/// Blocks imported as the best block and finalized blocks, or only the
+	/// finalized ones, with whether they are finalized.
+	fn blocks(&self, finalized_only: bool) -> BoxStream<'static, (Hash, BlockNumber, bool)> {
+		let client = self.client.clone();
+		let mut last = self.client.info().finalized_number;
+		// Blocks finalized together only have one notification.
+		let finalized = self
+			.client
+			.finality_notification_stream()
+			.flat_map(move |notification| {
+				let to = *notification.header.number();
+				let blocks = (last + 1..=to)
+					.filter_map(|number| {
+						let hash = client.hash(number).ok().flatten()?;
+						Some((hash, number, true))
+					})
+					.collect::<Vec<_>>();
+				last = last.max(to);
+				stream::iter(blocks)
+			});
+		if finalized_only {
+			return finalized.boxed()
+		}
+
+		let imported = self
+			.client
+			.import_notification_stream()
+			.filter(|notification| future::ready(notification.is_new_best))
+			.map(|notification| (notification.hash, *notification.header.number(), false));
+		stream::select(imported, finalized).boxed()
+	}
+
+	fn subscribe(
+		&self,
+		subscriber: Subscriber<NameChange<Hash>>,
+		name: String,
+		finalized: Option<bool>,
+		subtree: bool,
+	) {
+		let target = match Name::from_dotted(name.as_bytes()) {
+			Some(target) => target,
+			None => {
+				let _ = subscriber.reject(RpcError::invalid_params("Invalid name"));
+				return
+			}
+		};
+
+		let client = self.client.clone();
+		let events = self.events;
+		let changes = self
+			.blocks(finalized.unwrap_or(false))
+			.flat_map(move |block| {
+				let changes = block_changes::<_, B>(&*client, events, &target, subtree, block)
+					.unwrap_or_else(|e| {
+						log::warn!(
+							target: "rpc",
+							"Unable to read Nomo changes of block #{}: {}",
+							block.1,
+							e,
+						);
+						Vec::new()
+					});
+				stream::iter(changes)
+			});
+
+		self.manager.add(subscriber, |sink| {
+			changes
+				.map(|change| Ok::<_, ()>(Ok(change)))
+				.forward(sink.sink_map_err(
+					|e| log::warn!(target: "rpc", "Error sending notifications: {:?}", e),
+				))
+				.map(|_| ())
+		});
+	}
+}
+
+/// Changes of `target`, or of it and the names beneath it for subtrees, in
+/// a block.
+fn block_changes<C, B>(
+	client: &C,
+	events: NameEvents,
+	target: &Name,
+	subtree: bool,
+	(hash, number, finalized): (Hash, BlockNumber, bool),
+) -> std::result::Result<Vec<NameChange<Hash>>, neatcoin_nomo::Error>
+where
+	C: StorageProvider<Block, B>,
+	B: Backend<Block>,
+{
+	let reader = NomoReader::new(ClientStorage::<_, B>::new(client, BlockId::Hash(hash)));
+	let mut changed = match reader.events()? {
+		Some(raw) => events(&raw)
+			.map_err(|error| neatcoin_nomo::Error::Decode {
+				item: "Events",
+				error,
+			})?
+			.into_iter()
+			.filter(|(name, _)| {
+				if subtree {
+					is_under(name, target)
+				} else {
+					name == target
+				}
+			})
+			.collect::<Vec<_>>(),
+		None => Vec::new(),
+	};
+	changed.sort_by(|(a, a_kind), (b, b_kind)| name_order(a, b).then(a_kind.cmp(b_kind)));
+	changed.dedup();
+
+	let mut changes = Vec::new();
+	for (name, kind) in changed {
+		let value = match kind {
+			NameChangeKind::Ownership => ChangedValue::Ownership {
+				owner: reader.owner(&name)?,
+			},
+			NameChangeKind::Renewal => ChangedValue::Renewal {
+				renewal: reader.renewal(&name)?,
+			},
+			NameChangeKind::Records => ChangedValue::from(&reader.records(&name)?),
+		};
+
+		changes.push(NameChange {
+			block: hash,
+			number,
+			finalized,
+			name: String::from_utf8_lossy(&name.to_dotted()).into_owned(),
+			value,
+		});
+	}
+	Ok(changes)
+}
+
+impl<C, B> NomoApi<Hash> for Nomo<C, B>
+where
+	C: StorageProvider<Block, B> + HeaderBackend<Block> + BlockchainEvents<Block>,
+	C: Send + Sync + 'static,
+	B: Backend<Block> + Send + Sync + 'static,
+{
+	type Metadata = sc_rpc::Metadata;
+
+	fn zone_file(&self, apex: String, at: Option<Hash>) -> Result<String> {
+		self.deny_unsafe.check_if_safe()?;
+
+		let apex = Name::from_dotted(apex.as_bytes())
+			.ok_or_else(|| RpcError::invalid_params("Invalid apex name"))?;
+		let hash = at.unwrap_or_else(|| self.client.info().best_hash);
+		let number = self
+			.client
+			.number(hash)
+			.map_err(storage_error)?
+			.ok_or_else(|| RpcError::invalid_params("Unknown block"))?;
+
+		let reader = NomoReader::new(ClientStorage::<_, B>::new(
+			&*self.client,
+			BlockId::Hash(hash),
+		));
+		let zone = Zone::read(&reader, &apex, number, hash).map_err(storage_error)?;
+
+		Ok(zone.render())
+	}
+
+	fn subscribe_name(
+		&self,
+		_metadata: Self::Metadata,
+		subscriber: Subscriber<NameChange<Hash>>,
+		name: String,
+		finalized: Option<bool>,
+	) {
+		self.subscribe(subscriber, name, finalized, false)
+	}
+
+	fn unsubscribe_name(
+		&self,
+		_metadata: Option<Self::Metadata>,
+		id: SubscriptionId,
+	) -> Result<bool> {
+		Ok(self.manager.cancel(id))
+	}
+
+	fn subscribe_subtree(
+		&self,
+		_metadata: Self::Metadata,
+		subscriber: Subscriber<NameChange<Hash>>,
+		name: String,
+		finalized: Option<bool>,
+	) {
+		self.subscribe(subscriber, name, finalized, true)
+	}
+
+	fn unsubscribe_subtree(
+		&self,
+		_metadata: Option<Self::Metadata>,
+		id: SubscriptionId,
+	) -> Result<bool> {
+		Ok(self.manager.cancel(id))
+	}
+}
@@ -21,10 +21,11 @@ use crate::{
 		AccountId, AuthorityDiscoveryId, Balance, Block, EpochDuration, GrandpaId, Nonce,
 		BABE_GENESIS_EPOCH_CONFIG,
 	},
-	AuthorityDiscovery, Babe, BlockNumber, Contracts, Executive, Grandpa, Hash, Historical,
-	InherentDataExt, Runtime, SessionKeys, System, TransactionPayment, VERSION,
+	AuthorityDiscovery, Babe, BlockNumber, Contracts, Eons, Executive, Grandpa, Hash, Historical,
+	InherentDataExt, Ownership, Registry, Runtime, SessionKeys, System, TransactionPayment, VERSION,
 };
 use frame_support::traits::KeyOwnerProofSystem;
+use np_domain::{Label, Name};
 use pallet_grandpa::fg_primitives;
 use pallet_transaction_payment::{FeeDetails, RuntimeDispatchInfo};
 use sp_api::ApisVec;
@@ -260,6 +261,18 @@ sp_api::impl_runtime_apis! {
 		}
 	}
 
+	impl pallet_eons_rpc_runtime_api::EonsApi<Block, Hash> for Runtime {
+		fn past_eons() -> Vec<(u32, pallet_eons::Eon<Hash>)> {
+			Eons::indexed_past_eons()
+		}
+	}
+
+	impl pallet_registry_rpc_runtime_api::RegistryApi<Block, Ownership> for Runtime {
+		fn children(parent: Name, start_after: Option<Label>, limit: u32) -> Vec<(Name, Ownership)> {
+			Registry::children(&parent, start_after.as_ref(), limit)
+		}
+	}
+
 	#[cfg(feature = "try-runtime")]
 	impl frame_try_runtime::TryRuntime<Block> for Runtime {
 		fn on_runtime_upgrade() -> (frame_support::weights::Weight, frame_support::weights::Weight) {
@@ -318,6 +331,8 @@ sp_api::impl_runtime_apis! {
 			list_benchmark!(list, extra, pallet_registry, crate::Registry);
 			list_benchmark!(list, extra, pallet_fcfs, crate::FCFS);
 			list_benchmark!(list, extra, pallet_zone, crate::Zone);
+			list_benchmark!(list, extra, pallet_variables, crate::Variables);
+			list_benchmark!(list, extra, pallet_eons, crate::Eons);
 
 			let storage_info = crate::AllPalletsWithSystem::storage_info();
 
@@ -381,6 +396,8 @@ sp_api::impl_runtime_apis! {
 			add_benchmark!(params, batches, pallet_registry, crate::Registry);
 			add_benchmark!(params, batches, pallet_fcfs, crate::FCFS);
 			add_benchmark!(params, batches, pallet_zone, crate::Zone);
+			add_benchmark!(params, batches, pallet_variables, crate::Variables);
+			add_benchmark!(params, batches, pallet_eons, crate::Eons);
 
 			if batches.is_empty() { return Err("Benchmark not found for this pallet.".into()) }
 			Ok(batches)
@@ -21,46 +21,22 @@ use crate::{
 	types::Balance,
 	AccountId, Balances, BlockNumber, Event, Registry, Runtime, Treasury,
 };
-use codec::{Decode, Encode};
 use frame_support::parameter_types;
-use scale_info::TypeInfo;
-#[cfg(feature = "std")]
-use serde::{Deserialize, Serialize};
-use sp_core::RuntimeDebug;
+use pallet_variables::Variable;
 
-#[derive(Encode, Decode, Clone, RuntimeDebug, PartialEq, Eq, TypeInfo)]
-#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
-pub enum Ownership {
-	None,
-	Root,
-	FCFS,
-	Account(AccountId),
-}
-
-impl Default for Ownership {
-	fn default() -> Self {
-		Self::None
-	}
-}
+pub use pallet_zone::{MaxDnsKeyLength, MaxDsDigestLength, MaxRrsigLength};
 
-impl pallet_registry::Ownership for Ownership {
-	type AccountId = AccountId;
-
-	fn root() -> Self {
-		Self::Root
-	}
-	fn account(account: AccountId) -> Self {
-		Self::Account(account)
-	}
-}
+pub type Ownership = pallet_registry::NomoOwnership<AccountId>;
 
 impl pallet_registry::Config for Runtime {
 	type Ownership = Ownership;
 	type Event = Event;
-	type WeightInfo = ();
+	type WeightInfo = crate::weights::pallet_registry::WeightInfo<Runtime>;
 }
 
 parameter_types! {
+	// Not backed by a variable, as lowering it would make existing records
+	// fail to decode.
 	pub const RecordLimit: u32 = 16;
 }
 
@@ -69,16 +45,26 @@ impl pallet_zone::Config for Runtime {
 	type Registry = Registry;
 	type Event = Event;
 	type RecordLimit = RecordLimit;
-	type WeightInfo = ();
+	type MaxKeyLength = MaxDnsKeyLength;
+	type MaxDigestLength = MaxDsDigestLength;
+	type MaxSignatureLength = MaxRrsigLength;
+	type WeightInfo = crate::weights::pallet_zone::WeightInfo<Runtime>;
 }
 
 parameter_types! {
-	pub const DefaultFee: Balance = 500 * UNITS;
-	pub const Period: BlockNumber = 52 * 7 * DAYS;
-	pub const CanRenewAfter: BlockNumber = 52 * 7 * DAYS;
+	pub const DefaultFeeKey: &'static [u8] = b"nomo.fcfs.default_fee";
+	pub const BaseDefaultFee: Balance = 500 * UNITS;
+	pub const PeriodKey: &'static [u8] = b"nomo.fcfs.period";
+	pub const BasePeriod: BlockNumber = 52 * 7 * DAYS;
+	pub const CanRenewAfterKey: &'static [u8] = b"nomo.fcfs.can_renew_after";
+	pub const BaseCanRenewAfter: BlockNumber = 52 * 7 * DAYS;
 	pub const FCFSOwnership: Ownership = Ownership::FCFS;
 }
 
+pub type DefaultFee = Variable<Runtime, DefaultFeeKey, BaseDefaultFee>;
+pub type Period = Variable<Runtime, PeriodKey, BasePeriod>;
+pub type CanRenewAfter = Variable<Runtime, CanRenewAfterKey, BaseCanRenewAfter>;
+
 impl pallet_fcfs::Config for Runtime {
 	type Ownership = Ownership;
 	type FCFSOwnership = FCFSOwnership;
@@ -88,6 +74,6 @@ impl pallet_fcfs::Config for Runtime {
 	type Period = Period;
 	type CanRenewAfter = CanRenewAfter;
 	type ChargeFee = Treasury;
-	type WeightInfo = ();
+	type WeightInfo = crate::weights::pallet_fcfs::WeightInfo<Runtime>;
 	type Event = Event;
 }
@@ -57,6 +57,7 @@ pub enum ProxyType {
 	Staking = 3,
 	IdentityJudgement = 5,
 	CancelProxy = 6,
+	Nomo = 7,
 }
 
 impl Default for ProxyType {
@@ -98,7 +99,15 @@ impl InstanceFilter<Call> for ProxyType {
 				Call::Utility(..) |
 				Call::Identity(..) |
 				Call::Proxy(..) |
-				Call::Multisig(..)
+				Call::Multisig(..) |
+				Call::Eons(pallet_eons::Call::claim_balance { .. }) |
+				Call::Eons(pallet_eons::Call::claim_name { .. }) |
+				Call::Zone(..) |
+				Call::FCFS(pallet_fcfs::Call::renew { .. }) |
+				Call::FCFS(pallet_fcfs::Call::release_expired { .. })
+				// Specifically omitting FCFS `register`, which spends funds on
+				// a new name
+				// Specifically omitting Registry `force_set_ownership`
 			),
 			ProxyType::Governance => matches!(
 				c,
@@ -119,6 +128,20 @@ impl InstanceFilter<Call> for ProxyType {
 				c,
 				Call::Proxy(pallet_proxy::Call::reject_announcement { .. })
 			),
+			ProxyType::Nomo => matches!(
+				c,
+				Call::Zone(pallet_zone::Call::set_a { .. }) |
+				Call::Zone(pallet_zone::Call::set_aaaa { .. }) |
+				Call::Zone(pallet_zone::Call::set_ns { .. }) |
+				Call::Zone(pallet_zone::Call::set_cname { .. }) |
+				Call::Zone(pallet_zone::Call::set_mx { .. }) |
+				Call::Zone(pallet_zone::Call::set_dnskey { .. }) |
+				Call::Zone(pallet_zone::Call::set_ds { .. }) |
+				Call::Zone(pallet_zone::Call::set_rrsig { .. }) |
+				Call::Zone(pallet_zone::Call::set_dnssec { .. }) |
+				Call::FCFS(pallet_fcfs::Call::renew { .. }) |
+				Call::Utility(..)
+			),
 		}
 	}
 	fn is_superset(&self, o: &Self) -> bool {
@@ -146,3 +169,56 @@ impl pallet_proxy::Config for Runtime {
 	type AnnouncementDepositBase = AnnouncementDepositBase;
 	type AnnouncementDepositFactor = AnnouncementDepositFactor;
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn nomo_proxy_filters_zone_and_fcfs_calls() {
+		let allowed = [
+			Call::Zone(pallet_zone::Call::set_a {
+				name: Default::default(),
+				record: Vec::new(),
+			}),
+			Call::Zone(pallet_zone::Call::set_mx {
+				name: Default::default(),
+				record: Vec::new(),
+			}),
+			Call::Zone(pallet_zone::Call::set_dnskey {
+				name: Default::default(),
+				record: Vec::new(),
+			}),
+			Call::Zone(pallet_zone::Call::set_ds {
+				name: Default::default(),
+				record: Vec::new(),
+			}),
+			Call::Zone(pallet_zone::Call::set_rrsig {
+				name: Default::default(),
+				record: Vec::new(),
+			}),
+			Call::Zone(pallet_zone::Call::set_dnssec {
+				name: Default::default(),
+				mode: None,
+			}),
+			Call::FCFS(pallet_fcfs::Call::renew {
+				name: Default::default(),
+			}),
+		];
+		for call in &allowed {
+			assert!(ProxyType::Nomo.filter(call), "{:?} filtered out", call);
+		}
+
+		let denied = [
+			Call::Zone(pallet_zone::Call::set_icann {
+				name: Default::default(),
+			}),
+			Call::FCFS(pallet_fcfs::Call::register {
+				name: Default::default(),
+			}),
+		];
+		for call in &denied {
+			assert!(!ProxyType::Nomo.filter(call), "{:?} allowed", call);
+		}
+	}
+}
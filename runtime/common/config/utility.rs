@@ -18,11 +18,18 @@
 
 use crate::{
 	constants::currency::{deposit, DOLLARS},
-	types::Balance,
-	Balances, Call, Event, Runtime,
+	types::{AccountId, Balance, MoreThanHalfCouncil},
+	Balances, Call, Event, FCFS, Origin, Registry, Runtime,
 };
-use frame_support::parameter_types;
+use frame_support::{
+	dispatch::DispatchResult,
+	parameter_types,
+	traits::{EnsureOrigin, Get},
+};
+use frame_system::EnsureRoot;
+use pallet_variables::ValueKind;
 use sp_runtime::traits::ConvertInto;
+use sp_std::prelude::*;
 
 impl pallet_utility::Config for Runtime {
 	type Event = Event;
@@ -61,10 +68,61 @@ impl pallet_vesting::Config for Runtime {
 	const MAX_VESTING_SCHEDULES: u32 = 28;
 }
 
-impl pallet_eons::Config for Runtime {}
+impl pallet_eons::Config for Runtime {
+	type Event = Event;
+	type EonOrigin = MoreThanHalfCouncil;
+	type Currency = Balances;
+	type Ownership = crate::config::Ownership;
+	type Registry = Registry;
+	type Registrar = FCFS;
+	type WeightInfo = crate::weights::pallet_eons::WeightInfo<Runtime>;
+}
+
+/// Variables that can be set, with the kind of value they hold.
+pub struct VariableKeys;
+
+impl pallet_variables::VariableKeys for VariableKeys {
+	fn keys() -> Vec<(&'static [u8], ValueKind)> {
+		use super::nomo::{CanRenewAfterKey, DefaultFeeKey, PeriodKey};
+
+		vec![
+			(DefaultFeeKey::get(), ValueKind::U128),
+			(PeriodKey::get(), ValueKind::U32),
+			(CanRenewAfterKey::get(), ValueKind::U32),
+		]
+	}
+}
+
+/// Origins allowed to change variables. Keys under `nomo.` can be changed by
+/// the council; all other keys, such as `fees.`, by root only.
+pub struct VariableOrigins;
+
+impl pallet_variables::KeyOrigin<Origin> for VariableOrigins {
+	fn ensure_origin(key: &[u8], origin: Origin) -> DispatchResult {
+		if key.starts_with(b"nomo.") {
+			MoreThanHalfCouncil::ensure_origin(origin)?;
+		} else {
+			EnsureRoot::<AccountId>::ensure_origin(origin)?;
+		}
+
+		Ok(())
+	}
+}
+
+parameter_types! {
+	pub const MaxVariableKeyLength: u32 = 64;
+	pub const MaxVariableBytesLength: u32 = 128;
+	pub const MaxVariablesScheduledPerBlock: u32 = 16;
+}
 
 impl pallet_variables::Config for Runtime {
 	type Event = Event;
+	type Keys = VariableKeys;
+	type KeyOrigin = VariableOrigins;
+	type MaxScheduledPerBlock = MaxVariablesScheduledPerBlock;
+	type MaxKeyLength = MaxVariableKeyLength;
+	type MaxBytesLength = MaxVariableBytesLength;
+	type WeightInfo = crate::weights::pallet_variables::WeightInfo<Runtime>;
 }
 
 parameter_types! {
@@ -77,4 +135,6 @@ impl pallet_atomic_swap::Config for Runtime {
 	type ProofLimit = ProofLimit;
 }
 
-impl pallet_bootstrap::Config for Runtime {}
+impl pallet_bootstrap::Config for Runtime {
+	type Event = Event;
+}
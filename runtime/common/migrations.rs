@@ -16,4 +16,83 @@
 // You should have received a copy of the GNU General Public License
 // along with Neatcoin. If not, see <http://www.gnu.org/licenses/>.
 
-pub type AllMigrations = ();
+//! Runtime storage migrations.
+//!
+//! Every custom pallet declares its current `STORAGE_VERSION`. Format changes
+//! bump that version and add a migration to `AllMigrations`, guarded on the
+//! on-chain version so that each one only ever runs once.
+
+use crate::{Bootstrap, Eons, Registry, Runtime, Variables, Zone, FCFS};
+use frame_support::{
+	parameter_types,
+	traits::{Get, OnRuntimeUpgrade, PalletInfoAccess, StorageVersion},
+	weights::Weight,
+};
+use sp_std::marker::PhantomData;
+
+parameter_types! {
+	pub const BootstrapStorageVersion: StorageVersion = pallet_bootstrap::STORAGE_VERSION;
+	pub const EonsStorageVersion: StorageVersion = pallet_eons::STORAGE_VERSION;
+	pub const VariablesStorageVersion: StorageVersion = pallet_variables::STORAGE_VERSION;
+	pub const RegistryStorageVersion: StorageVersion = pallet_registry::STORAGE_VERSION;
+	pub const ZoneStorageVersion: StorageVersion = pallet_zone::STORAGE_VERSION;
+	pub const FCFSStorageVersion: StorageVersion = pallet_fcfs::STORAGE_VERSION;
+}
+
+/// Record the initial storage version `V` of pallet `P`, if none was recorded
+/// yet. The storage format is left untouched.
+pub struct InitStorageVersion<P, V>(PhantomData<(P, V)>);
+
+impl<P: PalletInfoAccess, V: Get<StorageVersion>> OnRuntimeUpgrade for InitStorageVersion<P, V> {
+	fn on_runtime_upgrade() -> Weight {
+		let db_weight = <Runtime as frame_system::Config>::DbWeight::get();
+
+		if StorageVersion::get::<P>() == StorageVersion::new(0) {
+			V::get().put::<P>();
+			db_weight.reads_writes(1, 1)
+		} else {
+			db_weight.reads(1)
+		}
+	}
+
+	#[cfg(feature = "try-runtime")]
+	fn pre_upgrade() -> Result<(), &'static str> {
+		let on_chain = StorageVersion::get::<P>();
+		frame_support::ensure!(
+			on_chain == StorageVersion::new(0) || on_chain == V::get(),
+			"unexpected on-chain storage version",
+		);
+
+		log::info!(
+			target: "runtime::migrations",
+			"{}: on-chain storage version {:?}, current {:?}",
+			P::name(),
+			on_chain,
+			V::get(),
+		);
+
+		Ok(())
+	}
+
+	#[cfg(feature = "try-runtime")]
+	fn post_upgrade() -> Result<(), &'static str> {
+		frame_support::ensure!(
+			StorageVersion::get::<P>() == V::get(),
+			"storage version not updated",
+		);
+
+		Ok(())
+	}
+}
+
+pub type AllMigrations = (
+	pallet_bootstrap::migrations::ReleaseEndoweds<Runtime>,
+	InitStorageVersion<Bootstrap, BootstrapStorageVersion>,
+	InitStorageVersion<Eons, EonsStorageVersion>,
+	InitStorageVersion<Variables, VariablesStorageVersion>,
+	pallet_registry::migrations::BuildChildren<Runtime>,
+	InitStorageVersion<Registry, RegistryStorageVersion>,
+	pallet_zone::migrations::BuildSubnames<Runtime>,
+	InitStorageVersion<Zone, ZoneStorageVersion>,
+	InitStorageVersion<FCFS, FCFSStorageVersion>,
+);
@@ -32,3 +32,7 @@ mod offchain;
 mod proxy;
 #[path = "../../common/config/utility.rs"]
 mod utility;
+
+pub use self::nomo::{
+	CanRenewAfterKey, MaxDnsKeyLength, MaxDsDigestLength, MaxRrsigLength, Ownership, PeriodKey,
+};
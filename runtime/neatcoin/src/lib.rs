@@ -25,13 +25,14 @@ mod config;
 #[path = "../../common/api.rs"]
 mod api;
 #[path = "../../common/constants.rs"]
-mod constants;
+pub mod constants;
 #[path = "../../common/impls.rs"]
 mod impls;
 #[path = "../../common/migrations.rs"]
 mod migrations;
 #[path = "../../common/types.rs"]
 mod types;
+mod weights;
 
 use frame_support::construct_runtime;
 use pallet_session::historical as session_historical;
@@ -48,6 +49,9 @@ pub use crate::types::{
 	CheckedExtrinsic, Hash, Header, Moment, Nonce, Signature, SignedBlock, SignedExtra,
 	SignedPayload, UncheckedExtrinsic, BABE_GENESIS_EPOCH_CONFIG,
 };
+pub use crate::config::{
+	CanRenewAfterKey, MaxDnsKeyLength, MaxDsDigestLength, MaxRrsigLength, Ownership, PeriodKey,
+};
 pub use pallet_balances::Call as BalancesCall;
 #[cfg(feature = "std")]
 pub use pallet_staking::StakerStatus;
@@ -67,7 +71,7 @@ pub const VERSION: RuntimeVersion = RuntimeVersion {
 	spec_name: create_runtime_str!("neatcoin"),
 	impl_name: create_runtime_str!("neatcoin"),
 	authoring_version: 0,
-	spec_version: 5,
+	spec_version: 7,
 	impl_version: 0,
 	apis: crate::api::PRUNTIME_API_VERSIONS,
 	transaction_version: 1,
@@ -107,7 +111,7 @@ construct_runtime! {
 		Scheduler: pallet_scheduler::{Pallet, Call, Storage, Event<T>} = 2,
 
 		// Bootstrap
-		Bootstrap: pallet_bootstrap::{Pallet, Storage, Config<T>} = 100,
+		Bootstrap: pallet_bootstrap::{Pallet, Storage, Config<T>, Event<T>} = 100,
 
 		// Must be before session.
 		Babe: pallet_babe::{Pallet, Call, Storage, Config, ValidateUnsigned} = 3,
@@ -143,17 +147,17 @@ construct_runtime! {
 		Multisig: pallet_multisig::{Pallet, Call, Storage, Event<T>} = 27,
 		Bounties: pallet_bounties::{Pallet, Call, Storage, Event<T>} = 28,
 		Tips: pallet_tips::{Pallet, Call, Storage, Event<T>} = 29,
-		Eons: pallet_eons::{Pallet, Call, Storage, Config<T>} = 30,
-		Variables: pallet_variables::{Pallet, Call, Storage, Event} = 31,
+		Eons: pallet_eons::{Pallet, Call, Storage, Config<T>, Event<T>} = 30,
+		Variables: pallet_variables::{Pallet, Call, Storage, Config<T>, Event<T>} = 31,
 		AtomicSwap: pallet_atomic_swap::{Pallet, Call, Storage, Event<T>} = 32,
 
 		// Contracts
 		Contracts: pallet_contracts::{Pallet, Call, Storage, Event<T>} = 33,
 
 		// Nomo
-		Registry: pallet_registry::{Pallet, Call, Storage, Event<T>} = 34,
-		Zone: pallet_zone::{Pallet, Call, Storage, Event} = 35,
-		FCFS: pallet_fcfs::{Pallet, Call, Storage, Event<T>} = 36,
+		Registry: pallet_registry::{Pallet, Call, Storage, Config<T>, Event<T>} = 34,
+		Zone: pallet_zone::{Pallet, Call, Storage, Config, Event<T>} = 35,
+		FCFS: pallet_fcfs::{Pallet, Call, Storage, Config<T>, Event<T>} = 36,
 	}
 }
 
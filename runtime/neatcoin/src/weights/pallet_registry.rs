@@ -0,0 +1,23 @@
+//! Weights for `pallet_registry` in the neatcoin runtime
+//!
+//! NOT YET MEASURED. THESE ARE PLACEHOLDERS FOLLOWING THE STORAGE FOOTPRINT
+//! OF EACH CALL, UNTIL REPLACED BY THE OUTPUT OF `scripts/benchmark.sh`.
+
+
+#![cfg_attr(rustfmt, rustfmt_skip)]
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+
+use frame_support::{traits::Get, weights::Weight};
+use sp_std::marker::PhantomData;
+
+/// Weight functions for `pallet_registry`.
+pub struct WeightInfo<T>(PhantomData<T>);
+impl<T: frame_system::Config> pallet_registry::WeightInfo for WeightInfo<T> {
+	// Storage: Registry Ownerships (r:0 w:1)
+	// Storage: Registry Children (r:0 w:1)
+	fn force_set_ownership() -> Weight {
+		(20_400_000 as Weight)
+			.saturating_add(T::DbWeight::get().writes(2 as Weight))
+	}
+}
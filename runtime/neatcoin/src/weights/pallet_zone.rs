@@ -0,0 +1,154 @@
+//! Weights for `pallet_zone` in the neatcoin runtime
+//!
+//! NOT YET MEASURED. THESE ARE PLACEHOLDERS FOLLOWING THE STORAGE FOOTPRINT
+//! OF EACH CALL, UNTIL REPLACED BY THE OUTPUT OF `scripts/benchmark.sh`.
+
+
+#![cfg_attr(rustfmt, rustfmt_skip)]
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+
+use frame_support::{traits::Get, weights::Weight};
+use sp_std::marker::PhantomData;
+
+/// Weight functions for `pallet_zone`.
+pub struct WeightInfo<T>(PhantomData<T>);
+impl<T: frame_system::Config> pallet_zone::WeightInfo for WeightInfo<T> {
+	// Storage: Registry Ownerships (r:1 w:0)
+	// Storage: Zone As (r:1 w:1)
+	// Storage: Zone Subnames (r:1 w:1)
+	fn set_a(n: u32, r: u32, ) -> Weight {
+		(25_300_000 as Weight)
+			.saturating_add((1_100_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add((70_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().reads((2 as Weight).saturating_mul(n as Weight)))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes((1 as Weight).saturating_mul(n as Weight)))
+	}
+	// Storage: Registry Ownerships (r:1 w:0)
+	// Storage: Zone AAAAs (r:1 w:1)
+	// Storage: Zone Subnames (r:1 w:1)
+	fn set_aaaa(n: u32, r: u32, ) -> Weight {
+		(25_900_000 as Weight)
+			.saturating_add((1_100_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add((110_000 as Weight).saturating_mul(r as Weight))
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().reads((2 as Weight).saturating_mul(n as Weight)))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes((1 as Weight).saturating_mul(n as Weight)))
+	}
+	// Storage: Registry Ownerships (r:1 w:0)
+	// Storage: Zone NSs (r:1 w:1)
+	// Storage: Zone Subnames (r:1 w:1)
+	fn set_ns(n: u32, l: u32, ) -> Weight {
+		(27_600_000 as Weight)
+			.saturating_add((1_100_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add((4_000 as Weight).saturating_mul(l as Weight))
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().reads((2 as Weight).saturating_mul(n as Weight)))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes((1 as Weight).saturating_mul(n as Weight)))
+	}
+	// Storage: Registry Ownerships (r:1 w:0)
+	// Storage: Zone CNAMEs (r:1 w:1)
+	// Storage: Zone Subnames (r:1 w:1)
+	fn set_cname(n: u32, l: u32, ) -> Weight {
+		(27_700_000 as Weight)
+			.saturating_add((1_100_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add((4_000 as Weight).saturating_mul(l as Weight))
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().reads((2 as Weight).saturating_mul(n as Weight)))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes((1 as Weight).saturating_mul(n as Weight)))
+	}
+	// Storage: Registry Ownerships (r:1 w:0)
+	// Storage: Zone MXs (r:1 w:1)
+	// Storage: Zone Subnames (r:1 w:1)
+	fn set_mx(n: u32, l: u32, ) -> Weight {
+		(27_300_000 as Weight)
+			.saturating_add((1_100_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add((4_000 as Weight).saturating_mul(l as Weight))
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().reads((2 as Weight).saturating_mul(n as Weight)))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes((1 as Weight).saturating_mul(n as Weight)))
+	}
+	// Storage: Registry Ownerships (r:1 w:0)
+	// Storage: Zone ICANNs (r:0 w:1)
+	fn set_icann() -> Weight {
+		(22_700_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	// Storage: Registry Ownerships (r:1 w:0)
+	// Storage: Zone OpenNICs (r:0 w:1)
+	fn set_opennic() -> Weight {
+		(22_600_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	// Storage: Registry Ownerships (r:1 w:0)
+	// Storage: Zone Handshakes (r:0 w:1)
+	fn set_handshake() -> Weight {
+		(22_600_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	// Storage: Registry Ownerships (r:1 w:0)
+	// Storage: Zone Handshakes (r:0 w:1)
+	// Storage: Zone ICANNs (r:0 w:1)
+	// Storage: Zone OpenNICs (r:0 w:1)
+	fn reset_extern() -> Weight {
+		(26_400_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	// Storage: Registry Ownerships (r:1 w:0)
+	// Storage: Zone DNSKEYs (r:1 w:1)
+	// Storage: Zone Subnames (r:1 w:1)
+	fn set_dnskey(n: u32, l: u32, ) -> Weight {
+		(27_800_000 as Weight)
+			.saturating_add((1_100_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add((4_000 as Weight).saturating_mul(l as Weight))
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().reads((2 as Weight).saturating_mul(n as Weight)))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes((1 as Weight).saturating_mul(n as Weight)))
+	}
+	// Storage: Registry Ownerships (r:1 w:0)
+	// Storage: Zone DSs (r:1 w:1)
+	// Storage: Zone Subnames (r:1 w:1)
+	fn set_ds(n: u32, l: u32, ) -> Weight {
+		(27_600_000 as Weight)
+			.saturating_add((1_100_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add((4_000 as Weight).saturating_mul(l as Weight))
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().reads((2 as Weight).saturating_mul(n as Weight)))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes((1 as Weight).saturating_mul(n as Weight)))
+	}
+	// Storage: Registry Ownerships (r:1 w:0)
+	// Storage: Zone RRSIGs (r:1 w:1)
+	// Storage: Zone Subnames (r:1 w:1)
+	fn set_rrsig(n: u32, l: u32, ) -> Weight {
+		(28_100_000 as Weight)
+			.saturating_add((1_100_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add((4_000 as Weight).saturating_mul(l as Weight))
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().reads((2 as Weight).saturating_mul(n as Weight)))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes((1 as Weight).saturating_mul(n as Weight)))
+	}
+	// Storage: Registry Ownerships (r:1 w:0)
+	// Storage: Zone DNSSECs (r:1 w:1)
+	// Storage: Zone Subnames (r:1 w:1)
+	fn set_dnssec(n: u32, ) -> Weight {
+		(26_900_000 as Weight)
+			.saturating_add((1_100_000 as Weight).saturating_mul(n as Weight))
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().reads((2 as Weight).saturating_mul(n as Weight)))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes((1 as Weight).saturating_mul(n as Weight)))
+	}
+}
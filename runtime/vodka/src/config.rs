@@ -33,6 +33,10 @@ mod proxy;
 #[path = "../../common/config/utility.rs"]
 mod utility;
 
+pub use self::nomo::{
+	CanRenewAfterKey, MaxDnsKeyLength, MaxDsDigestLength, MaxRrsigLength, Ownership, PeriodKey,
+};
+
 mod sudo {
 	use crate::{Call, Event, Runtime};
 
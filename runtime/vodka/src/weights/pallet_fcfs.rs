@@ -0,0 +1,49 @@
+//! Weights for `pallet_fcfs` in the vodka runtime
+//!
+//! NOT YET MEASURED. THESE ARE PLACEHOLDERS FOLLOWING THE STORAGE FOOTPRINT
+//! OF EACH CALL, UNTIL REPLACED BY THE OUTPUT OF `scripts/benchmark.sh`.
+
+
+#![cfg_attr(rustfmt, rustfmt_skip)]
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+
+use frame_support::{traits::Get, weights::Weight};
+use sp_std::marker::PhantomData;
+
+/// Weight functions for `pallet_fcfs`.
+pub struct WeightInfo<T>(PhantomData<T>);
+impl<T: frame_system::Config> pallet_fcfs::WeightInfo for WeightInfo<T> {
+	// Storage: Registry Ownerships (r:2 w:1)
+	// Storage: FCFS Fee (r:1 w:0)
+	// Storage: Variables Values (r:2 w:0)
+	// Storage: Registry Children (r:0 w:1)
+	// Storage: FCFS Renewals (r:0 w:1)
+	fn register() -> Weight {
+		(82_700_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(5 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	// Storage: Registry Ownerships (r:2 w:0)
+	// Storage: FCFS Renewals (r:1 w:1)
+	// Storage: Variables Values (r:3 w:0)
+	// Storage: FCFS Fee (r:1 w:0)
+	fn renew() -> Weight {
+		(78_200_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(7 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	// Storage: Registry Ownerships (r:1 w:1)
+	// Storage: FCFS Renewals (r:1 w:1)
+	// Storage: Registry Children (r:0 w:1)
+	fn release_expired() -> Weight {
+		(45_200_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(2 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	// Storage: FCFS Fee (r:0 w:1)
+	fn set_fee() -> Weight {
+		(2_300_000 as Weight)
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+}
@@ -0,0 +1,37 @@
+#!/usr/bin/env bash
+
+# Benchmark the Nomo pallets and write the weights of both runtimes to
+# `runtime/<runtime>/src/weights`.
+#
+# Usage: scripts/benchmark.sh [PALLET...]
+
+set -e
+
+cd "$(dirname "$0")/.."
+
+PALLETS=${@:-pallet_registry pallet_zone pallet_fcfs pallet_eons pallet_variables}
+
+echo "*** Building node with runtime benchmarks enabled"
+cargo build --release --features runtime-benchmarks
+
+for runtime in neatcoin vodka; do
+	case $runtime in
+		neatcoin) chain=dev ;;
+		vodka) chain=vodka ;;
+	esac
+
+	for pallet in $PALLETS; do
+		echo "*** Benchmarking $pallet for the $runtime runtime"
+		./target/release/neatcoin benchmark \
+			--chain=$chain \
+			--execution=wasm \
+			--wasm-execution=compiled \
+			--pallet=$pallet \
+			--extrinsic='*' \
+			--steps=50 \
+			--repeat=20 \
+			--heap-pages=4096 \
+			--output=runtime/$runtime/src/weights/$pallet.rs \
+			--template=res/runtime_weights.hbs
+	done
+done
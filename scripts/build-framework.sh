@@ -0,0 +1,26 @@
+#!/usr/bin/env bash
+
+# Compile the Move framework modules declaring the Neatcoin natives to
+# `frame/outmove/framework/build`. The compiled modules are published at
+# address 0x1, either through the `modules` of the outmove genesis config or
+# with `force_publish`.
+#
+# Requires `move-build` from the Move compiler matching the VM revision of
+# `omv`, either on the PATH or given with MOVE_BUILD.
+#
+# Usage: scripts/build-framework.sh
+
+set -e
+
+cd "$(dirname "$0")/.."
+
+MOVE_BUILD=${MOVE_BUILD:-move-build}
+FRAMEWORK=frame/outmove/framework
+OUT=$FRAMEWORK/build
+
+echo "*** Compiling the Move framework to $OUT"
+rm -rf "$OUT"
+"$MOVE_BUILD" --out-dir "$OUT" "$FRAMEWORK"/*.move
+
+echo "*** Compiled modules"
+find "$OUT" -name '*.mv' | sort
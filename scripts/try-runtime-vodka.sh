@@ -0,0 +1,37 @@
+#!/usr/bin/env bash
+
+# Run the runtime upgrade of the Vodka runtime built from this tree against
+# live or snapshotted Vodka state, including all `pre_upgrade`/`post_upgrade`
+# checks of `AllMigrations`.
+#
+# Usage:
+#   scripts/try-runtime-vodka.sh snap [WS_URI]    Take a state snapshot.
+#   scripts/try-runtime-vodka.sh live [WS_URI]    Run against a live node.
+#   scripts/try-runtime-vodka.sh [SNAPSHOT]       Run against a snapshot.
+
+set -e
+
+cd "$(dirname "$0")/.."
+
+WS_URI=${2:-ws://localhost:9944}
+SNAPSHOT=${SNAPSHOT:-vodka.snap}
+
+echo "*** Building node with try-runtime enabled"
+cargo build --release --features try-runtime
+
+NODE=./target/release/neatcoin
+ARGS="--chain=vodka --execution=Native -lruntime=debug"
+
+case "$1" in
+	snap)
+		$NODE try-runtime $ARGS on-runtime-upgrade live \
+			--uri "$WS_URI" --snapshot-path "$SNAPSHOT"
+		;;
+	live)
+		$NODE try-runtime $ARGS on-runtime-upgrade live --uri "$WS_URI"
+		;;
+	*)
+		$NODE try-runtime $ARGS on-runtime-upgrade snap \
+			--snapshot-path "${1:-$SNAPSHOT}"
+		;;
+esac
@@ -18,12 +18,19 @@
 
 use codec::Decode;
 use indexmap::IndexMap;
+use np_domain::Name;
 use np_opaque::{AccountId, Balance};
+use pallet_im_online::sr25519::AuthorityId as ImOnlineId;
 use sc_chain_spec::{ChainSpecExtension, ChainType};
 use serde::{Deserialize, Serialize};
-use sp_core::crypto::{Ss58AddressFormatRegistry, Ss58Codec};
-use sp_runtime::Perbill;
-use std::convert::TryFrom;
+use sp_authority_discovery::AuthorityId as AuthorityDiscoveryId;
+use sp_consensus_babe::AuthorityId as BabeId;
+use sp_core::{crypto::Ss58Codec, sr25519, Pair, Public};
+use sp_finality_grandpa::AuthorityId as GrandpaId;
+use sp_runtime::{
+	traits::{Get, IdentifyAccount},
+	MultiSigner, Perbill,
+};
 use std::marker::PhantomData;
 
 /// Node `ChainSpec` extensions.
@@ -43,27 +50,44 @@ pub type NeatcoinChainSpec =
 	sc_service::GenericChainSpec<neatcoin_runtime::GenesisConfig, Extensions>;
 pub type VodkaChainSpec = sc_service::GenericChainSpec<vodka_runtime::GenesisConfig, Extensions>;
 
+/// Initial Nomo state of a chain.
+pub struct NomoGenesis<Ownership> {
+	/// Names assigned at genesis, usually top-level names with the
+	/// registrar owning them.
+	pub ownerships: Vec<(Name, Ownership)>,
+	/// Initial zone records.
+	pub zone: pallet_zone::GenesisConfig,
+	/// FCFS registration fee. Zero uses the runtime default.
+	pub fcfs_fee: Balance,
+}
+
+impl<Ownership> Default for NomoGenesis<Ownership> {
+	fn default() -> Self {
+		Self {
+			ownerships: Vec::new(),
+			zone: Default::default(),
+			fcfs_fee: 0,
+		}
+	}
+}
+
+/// Top-level name registered through FCFS on development chains.
+fn neatuser() -> Name {
+	Name::from_dotted(b"neatuser").expect("valid name; qed")
+}
+
 pub fn build_genesis_allocations() -> IndexMap<AccountId, Balance> {
-	let raw: IndexMap<String, String> =
-		serde_json::from_slice(include_bytes!("../res/genesis.json"))
-			.expect("parse genesis.json failed");
-	raw.into_iter()
-		.map(|(key, value)| {
-			let (address, version) =
-				AccountId::from_ss58check_with_version(&key).expect("parse address failed");
-			assert_eq!(
-				Ss58AddressFormatRegistry::try_from(version),
-				Ok(Ss58AddressFormatRegistry::KulupuAccount)
-			);
-			let balance = u128::from_str_radix(&value, 10).expect("parse balance failed");
-			(address, balance)
-		})
+	crate::genesis::parse_allocations(crate::genesis::GENESIS_ALLOCATIONS)
+		.unwrap_or_else(|e| panic!("invalid genesis.json: {}", e))
+		.0
+		.into_iter()
 		.collect()
 }
 
 pub fn build_neatcoin_genesis(
 	wasm_binary: &[u8],
 	genesis_keys: Vec<(AccountId, neatcoin_runtime::SessionKeys)>,
+	nomo: NomoGenesis<neatcoin_runtime::Ownership>,
 ) -> neatcoin_runtime::GenesisConfig {
 	neatcoin_runtime::GenesisConfig {
 		system: neatcoin_runtime::SystemConfig {
@@ -125,6 +149,12 @@ pub fn build_neatcoin_genesis(
 		},
 		treasury: neatcoin_runtime::TreasuryConfig {},
 		vesting: neatcoin_runtime::VestingConfig { vesting: vec![] },
+		variables: neatcoin_runtime::VariablesConfig::default(),
+		registry: neatcoin_runtime::RegistryConfig {
+			ownerships: nomo.ownerships,
+		},
+		zone: nomo.zone,
+		fcfs: neatcoin_runtime::FCFSConfig { fee: nomo.fcfs_fee },
 	}
 }
 
@@ -156,7 +186,11 @@ pub fn build_neatcoin_config() -> Result<NeatcoinChainSpec, String> {
 					.collect()
 			};
 
-			build_neatcoin_genesis(include_bytes!("../res/neatcoin-0.wasm"), init_vals)
+			build_neatcoin_genesis(
+				include_bytes!("../res/neatcoin-0.wasm"),
+				init_vals,
+				Default::default(),
+			)
 		},
 		boot_nodes,
 		None,
@@ -183,6 +217,7 @@ pub fn build_vodka_genesis(
 	wasm_binary: &[u8],
 	genesis_keys: Vec<(AccountId, vodka_runtime::SessionKeys)>,
 	sudo_key: AccountId,
+	nomo: NomoGenesis<vodka_runtime::Ownership>,
 ) -> vodka_runtime::GenesisConfig {
 	vodka_runtime::GenesisConfig {
 		system: vodka_runtime::SystemConfig {
@@ -245,6 +280,12 @@ pub fn build_vodka_genesis(
 		sudo: vodka_runtime::SudoConfig { key: sudo_key },
 		treasury: vodka_runtime::TreasuryConfig {},
 		vesting: vodka_runtime::VestingConfig { vesting: vec![] },
+		variables: vodka_runtime::VariablesConfig::default(),
+		registry: vodka_runtime::RegistryConfig {
+			ownerships: nomo.ownerships,
+		},
+		zone: nomo.zone,
+		fcfs: vodka_runtime::FCFSConfig { fee: nomo.fcfs_fee },
 	}
 }
 
@@ -281,6 +322,7 @@ pub fn build_vodka_config() -> Result<VodkaChainSpec, String> {
 				init_vals,
 				AccountId::from_ss58check("5DjqKKzLzYHTzgMgG2mxtZaSojShWwp9N3qPhnWuRoL3sFeD")
 					.expect("parse address failed"),
+				Default::default(),
 			)
 		},
 		boot_nodes,
@@ -304,7 +346,21 @@ pub fn vodka_config() -> Result<VodkaChainSpec, String> {
 	VodkaChainSpec::from_json_bytes(&include_bytes!("../res/vodka-spec.json")[..])
 }
 
+/// FCFS registration period of the manual-seal development chain, in
+/// blocks.
+const DEV_FCFS_PERIOD: u32 = 20;
+
 pub fn development_config() -> Result<NeatcoinChainSpec, String> {
+	build_development_config(false)
+}
+
+/// Development chain for manual-seal nodes, with FCFS periods short enough
+/// for expiry to be reached by sealing blocks.
+pub fn manual_seal_development_config() -> Result<NeatcoinChainSpec, String> {
+	build_development_config(true)
+}
+
+fn build_development_config(short_fcfs_periods: bool) -> Result<NeatcoinChainSpec, String> {
 	let wasm_binary =
 		neatcoin_runtime::WASM_BINARY.ok_or("Development wasm binary not available".to_string())?;
 
@@ -313,10 +369,28 @@ pub fn development_config() -> Result<NeatcoinChainSpec, String> {
 		"dev",
 		ChainType::Development,
 		move || {
-			build_neatcoin_genesis(
+			let mut genesis = build_neatcoin_genesis(
 				wasm_binary,
 				vec![(Default::default(), neatcoin_runtime::SessionKeys::default())],
-			)
+				NomoGenesis {
+					ownerships: vec![(neatuser(), neatcoin_runtime::Ownership::FCFS)],
+					fcfs_fee: neatcoin_runtime::constants::currency::UNITS,
+					..Default::default()
+				},
+			);
+			if short_fcfs_periods {
+				genesis.variables.values = vec![
+					(
+						neatcoin_runtime::PeriodKey::get().to_vec(),
+						pallet_variables::Value::U32(DEV_FCFS_PERIOD),
+					),
+					(
+						neatcoin_runtime::CanRenewAfterKey::get().to_vec(),
+						pallet_variables::Value::U32(DEV_FCFS_PERIOD / 2),
+					),
+				];
+			}
+			genesis
 		},
 		vec![],
 		None,
@@ -325,3 +399,216 @@ pub fn development_config() -> Result<NeatcoinChainSpec, String> {
 		Default::default(),
 	))
 }
+
+/// Balance of each endowed account of local testnets.
+const LOCAL_ENDOWMENT: Balance = 10_000_000 * neatcoin_runtime::constants::currency::UNITS;
+/// Stake bonded by each authority of local testnets.
+const LOCAL_STASH: Balance = LOCAL_ENDOWMENT / 10;
+
+/// Generate a crypto pair from a dev seed, such as `Alice`.
+pub fn get_from_seed<TPublic: Public>(seed: &str) -> <TPublic::Pair as Pair>::Public {
+	TPublic::Pair::from_string(&format!("//{}", seed), None)
+		.expect("static values are valid; qed")
+		.public()
+}
+
+/// Generate an account id from a dev seed.
+pub fn get_account_id_from_seed<TPublic: Public>(seed: &str) -> AccountId
+where
+	MultiSigner: From<<TPublic::Pair as Pair>::Public>,
+{
+	MultiSigner::from(get_from_seed::<TPublic>(seed)).into_account()
+}
+
+/// Dev authority keys of local testnets.
+pub struct AuthorityKeys {
+	pub stash: AccountId,
+	pub controller: AccountId,
+	pub grandpa: GrandpaId,
+	pub babe: BabeId,
+	pub im_online: ImOnlineId,
+	pub authority_discovery: AuthorityDiscoveryId,
+}
+
+/// Generate authority keys from a dev seed.
+pub fn authority_keys_from_seed(seed: &str) -> AuthorityKeys {
+	AuthorityKeys {
+		stash: get_account_id_from_seed::<sr25519::Public>(&format!("{}//stash", seed)),
+		controller: get_account_id_from_seed::<sr25519::Public>(seed),
+		grandpa: get_from_seed::<GrandpaId>(seed),
+		babe: get_from_seed::<BabeId>(seed),
+		im_online: get_from_seed::<ImOnlineId>(seed),
+		authority_discovery: get_from_seed::<AuthorityDiscoveryId>(seed),
+	}
+}
+
+/// Authorities of local testnets.
+fn local_authorities() -> Vec<AuthorityKeys> {
+	["Alice", "Bob", "Charlie"]
+		.iter()
+		.map(|seed| authority_keys_from_seed(seed))
+		.collect()
+}
+
+/// Dev accounts endowed on local testnets, including authority stashes.
+fn local_endowed_accounts() -> Vec<AccountId> {
+	let seeds = ["Alice", "Bob", "Charlie", "Dave", "Eve", "Ferdie"];
+
+	seeds
+		.iter()
+		.map(|seed| get_account_id_from_seed::<sr25519::Public>(seed))
+		.chain(
+			seeds
+				.iter()
+				.map(|seed| get_account_id_from_seed::<sr25519::Public>(&format!("{}//stash", seed))),
+		)
+		.collect()
+}
+
+/// Endow dev accounts and bond authority stashes as validators.
+fn add_local_stakers(
+	authorities: &[AuthorityKeys],
+	balances: &mut Vec<(AccountId, Balance)>,
+	stakers: &mut Vec<(AccountId, AccountId, Balance, pallet_staking::StakerStatus<AccountId>)>,
+	invulnerables: &mut Vec<AccountId>,
+) {
+	balances.extend(
+		local_endowed_accounts()
+			.into_iter()
+			.map(|account| (account, LOCAL_ENDOWMENT)),
+	);
+	stakers.extend(authorities.iter().map(|keys| {
+		(
+			keys.stash.clone(),
+			keys.controller.clone(),
+			LOCAL_STASH,
+			pallet_staking::StakerStatus::Validator,
+		)
+	}));
+	invulnerables.extend(authorities.iter().map(|keys| keys.stash.clone()));
+}
+
+pub fn neatcoin_local_testnet_config() -> Result<NeatcoinChainSpec, String> {
+	let wasm_binary = neatcoin_runtime::WASM_BINARY
+		.ok_or("Local testnet wasm binary not available".to_string())?;
+
+	Ok(NeatcoinChainSpec::from_genesis(
+		"Neatcoin Local Testnet",
+		"neatcoin_local",
+		ChainType::Local,
+		move || {
+			let authorities = local_authorities();
+			let session_keys = authorities
+				.iter()
+				.map(|keys| {
+					(
+						keys.stash.clone(),
+						neatcoin_runtime::SessionKeys {
+							grandpa: keys.grandpa.clone(),
+							babe: keys.babe.clone(),
+							im_online: keys.im_online.clone(),
+							authority_discovery: keys.authority_discovery.clone(),
+						},
+					)
+				})
+				.collect();
+
+			let mut genesis = build_neatcoin_genesis(
+				wasm_binary,
+				session_keys,
+				NomoGenesis {
+					ownerships: vec![(neatuser(), neatcoin_runtime::Ownership::FCFS)],
+					fcfs_fee: neatcoin_runtime::constants::currency::UNITS,
+					..Default::default()
+				},
+			);
+			add_local_stakers(
+				&authorities,
+				&mut genesis.balances.balances,
+				&mut genesis.staking.stakers,
+				&mut genesis.staking.invulnerables,
+			);
+			genesis.staking.validator_count = authorities.len() as u32;
+			genesis.staking.minimum_validator_count = 1;
+			genesis.staking.force_era = pallet_staking::Forcing::NotForcing;
+			genesis
+		},
+		vec![],
+		None,
+		Some("neatcoin"),
+		Some(
+			serde_json::json!({
+				"ss58Format": 48,
+				"tokenDecimals": 12,
+				"tokenSymbol": "NEAT"
+			})
+			.as_object()
+			.expect("Created an object")
+			.clone(),
+		),
+		Default::default(),
+	))
+}
+
+pub fn vodka_local_testnet_config() -> Result<VodkaChainSpec, String> {
+	let wasm_binary = vodka_runtime::WASM_BINARY
+		.ok_or("Local testnet wasm binary not available".to_string())?;
+
+	Ok(VodkaChainSpec::from_genesis(
+		"Vodka Local Testnet",
+		"vodka_local",
+		ChainType::Local,
+		move || {
+			let authorities = local_authorities();
+			let session_keys = authorities
+				.iter()
+				.map(|keys| {
+					(
+						keys.stash.clone(),
+						vodka_runtime::SessionKeys {
+							grandpa: keys.grandpa.clone(),
+							babe: keys.babe.clone(),
+							im_online: keys.im_online.clone(),
+							authority_discovery: keys.authority_discovery.clone(),
+						},
+					)
+				})
+				.collect();
+
+			let mut genesis = build_vodka_genesis(
+				wasm_binary,
+				session_keys,
+				get_account_id_from_seed::<sr25519::Public>("Alice"),
+				NomoGenesis {
+					ownerships: vec![(neatuser(), vodka_runtime::Ownership::FCFS)],
+					fcfs_fee: vodka_runtime::constants::currency::UNITS,
+					..Default::default()
+				},
+			);
+			add_local_stakers(
+				&authorities,
+				&mut genesis.balances.balances,
+				&mut genesis.staking.stakers,
+				&mut genesis.staking.invulnerables,
+			);
+			genesis.staking.validator_count = authorities.len() as u32;
+			genesis.staking.minimum_validator_count = 1;
+			genesis.staking.force_era = pallet_staking::Forcing::NotForcing;
+			genesis
+		},
+		vec![],
+		None,
+		Some("vodka"),
+		Some(
+			serde_json::json!({
+				"ss58Format": 42,
+				"tokenDecimals": 12,
+				"tokenSymbol": "VODKA"
+			})
+			.as_object()
+			.expect("Created an object")
+			.clone(),
+		),
+		Default::default(),
+	))
+}
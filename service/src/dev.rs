@@ -0,0 +1,358 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// This file is part of Neatcoin.
+//
+// Copyright (c) 2021 Wei Tang.
+//
+// Neatcoin is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Neatcoin is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Neatcoin. If not, see <http://www.gnu.org/licenses/>.
+
+//! Development nodes sealing blocks with manual-seal instead of running
+//! BABE and GRANDPA.
+//!
+//! Blocks still carry BABE pre-digests, so the runtime sees the same
+//! consensus data as on a regular network. Each block is one slot after its
+//! parent, or later if the clock has been warped through `engine_warp`.
+//!
+//! Warping only moves timestamps. Periods counted in blocks, such as FCFS
+//! registration periods, only advance by sealing blocks, which is why the
+//! `dev` chain of manual-seal nodes uses short FCFS periods.
+
+use crate::{
+	dns, names, set_prometheus_registry, ChainVariant, Client, Error, FullBackend, FullClient,
+	IdentifyVariant, NeatcoinExecutorDispatch, NewFull, RuntimeApiCollection,
+	VodkaExecutorDispatch,
+};
+use futures::{channel::mpsc, prelude::*};
+use neatcoin_rpc::dev::TimeWarp;
+use np_opaque::Block;
+use sc_basic_authorship::ProposerFactory;
+use sc_consensus_manual_seal::{
+	consensus::babe::BabeConsensusDataProvider, EngineCommand, ManualSealParams,
+};
+use sc_executor::NativeElseWasmExecutor;
+use sc_service::{ChainType, Configuration, NativeExecutionDispatch};
+use sc_telemetry::{Telemetry, TelemetryWorker};
+use sc_transaction_pool_api::TransactionPool;
+use sp_api::ConstructRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_runtime::{
+	generic::BlockId,
+	traits::{Header as HeaderT, Zero},
+};
+use std::{str::FromStr, sync::Arc, time::SystemTime};
+
+/// How a development node seals blocks.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Sealing {
+	/// Seal and finalize a block as soon as a transaction enters the pool.
+	/// Blocks can also be sealed through RPC.
+	Instant,
+	/// Seal and finalize blocks only through `engine_createBlock` and
+	/// `engine_finalizeBlock`.
+	Manual,
+}
+
+impl FromStr for Sealing {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		match s {
+			"instant" => Ok(Self::Instant),
+			"manual" => Ok(Self::Manual),
+			_ => Err(format!("unknown sealing mode {}", s)),
+		}
+	}
+}
+
+/// Milliseconds since the unix epoch.
+fn now_millis() -> u64 {
+	SystemTime::now()
+		.duration_since(SystemTime::UNIX_EPOCH)
+		.map(|duration| duration.as_millis() as u64)
+		.unwrap_or_default()
+}
+
+pub fn new_dev<RuntimeApi, ExecutorDispatch>(
+	mut config: Configuration,
+	sealing: Sealing,
+) -> Result<NewFull<Arc<FullClient<RuntimeApi, ExecutorDispatch>>>, Error>
+where
+	RuntimeApi: ConstructRuntimeApi<Block, FullClient<RuntimeApi, ExecutorDispatch>>
+		+ Send
+		+ Sync
+		+ 'static,
+	RuntimeApi::RuntimeApi:
+		RuntimeApiCollection<StateBackend = sc_client_api::StateBackendFor<crate::FullBackend, Block>>,
+	ExecutorDispatch: NativeExecutionDispatch + 'static,
+{
+	match config.chain_spec.chain_type() {
+		ChainType::Development | ChainType::Local => (),
+		_ => {
+			return Err(sc_service::Error::Other(
+				"Manual sealing is only available on development and local chains".into(),
+			)
+			.into())
+		}
+	}
+
+	set_prometheus_registry(&mut config)?;
+
+	let telemetry = config
+		.telemetry_endpoints
+		.clone()
+		.filter(|x| !x.is_empty())
+		.map(|endpoints| -> Result<_, sc_telemetry::Error> {
+			let worker = TelemetryWorker::new(16)?;
+			let telemetry = worker.handle().new_telemetry(endpoints);
+			Ok((worker, telemetry))
+		})
+		.transpose()?;
+
+	let executor = NativeElseWasmExecutor::<ExecutorDispatch>::new(
+		config.wasm_method,
+		config.default_heap_pages,
+		config.max_runtime_instances,
+	);
+
+	let (client, backend, keystore_container, mut task_manager) =
+		sc_service::new_full_parts::<Block, RuntimeApi, _>(
+			&config,
+			telemetry.as_ref().map(|(_, telemetry)| telemetry.handle()),
+			executor,
+		)?;
+	let client = Arc::new(client);
+
+	let mut telemetry: Option<Telemetry> = telemetry.map(|(worker, telemetry)| {
+		task_manager.spawn_handle().spawn("telemetry", worker.run());
+		telemetry
+	});
+
+	let select_chain = sc_consensus::LongestChain::new(backend.clone());
+
+	let transaction_pool = sc_transaction_pool::BasicPool::new_full(
+		config.transaction_pool.clone(),
+		config.role.is_authority().into(),
+		config.prometheus_registry(),
+		task_manager.spawn_essential_handle(),
+		client.clone(),
+	);
+
+	let babe_config = sc_consensus_babe::Config::get_or_compute(&*client)?;
+	let (block_import, babe_link) =
+		sc_consensus_babe::block_import(babe_config.clone(), client.clone(), client.clone())?;
+
+	let import_queue = sc_consensus_manual_seal::import_queue(
+		Box::new(block_import.clone()),
+		&task_manager.spawn_essential_handle(),
+		config.prometheus_registry(),
+	);
+
+	let (network, system_rpc_tx, network_starter) =
+		sc_service::build_network(sc_service::BuildNetworkParams {
+			config: &config,
+			client: client.clone(),
+			transaction_pool: transaction_pool.clone(),
+			spawn_handle: task_manager.spawn_handle(),
+			import_queue,
+			on_demand: None,
+			block_announce_validator_builder: None,
+			warp_sync: None,
+		})?;
+
+	if config.offchain_worker.enabled {
+		let _ = sc_service::build_offchain_workers(
+			&config,
+			task_manager.spawn_handle(),
+			client.clone(),
+			network.clone(),
+		);
+	}
+
+	let prometheus_registry = config.prometheus_registry().cloned();
+	let (command_sink, commands_stream) = mpsc::channel(1024);
+	let time_warp = TimeWarp::default();
+	let epoch_millis = babe_config
+		.epoch_length
+		.saturating_mul(babe_config.slot_duration().as_millis() as u64);
+
+	let rpc_extensions_builder = {
+		let client = client.clone();
+		let transaction_pool = transaction_pool.clone();
+		let command_sink = command_sink.clone();
+		let time_warp = time_warp.clone();
+		let name_events = names::decoder(config.chain_spec.identify_variant());
+
+		move |deny_unsafe, subscription_executor| -> Result<neatcoin_rpc::RpcExtension, _> {
+			let deps = neatcoin_rpc::DevDeps {
+				client: client.clone(),
+				pool: transaction_pool.clone(),
+				deny_unsafe,
+				command_sink: command_sink.clone(),
+				time_warp: time_warp.clone(),
+				epoch_millis,
+				subscription_executor,
+				name_events,
+			};
+
+			Ok(neatcoin_rpc::create_dev::<_, _, crate::FullBackend>(deps))
+		}
+	};
+
+	let rpc_handlers = sc_service::spawn_tasks(sc_service::SpawnTasksParams {
+		config,
+		backend,
+		client: client.clone(),
+		keystore: keystore_container.sync_keystore(),
+		network: network.clone(),
+		rpc_extensions_builder: Box::new(rpc_extensions_builder),
+		transaction_pool: transaction_pool.clone(),
+		task_manager: &mut task_manager,
+		on_demand: None,
+		remote_blockchain: None,
+		system_rpc_tx,
+		telemetry: telemetry.as_mut(),
+	})?;
+
+	let proposer = ProposerFactory::new(
+		task_manager.spawn_handle(),
+		client.clone(),
+		transaction_pool.clone(),
+		prometheus_registry.as_ref(),
+		telemetry.as_ref().map(|x| x.handle()),
+	);
+
+	let consensus_data_provider = BabeConsensusDataProvider::new(
+		client.clone(),
+		keystore_container.sync_keystore(),
+		babe_link.epoch_changes().clone(),
+		babe_config.authorities.clone(),
+	)?;
+
+	let commands_stream: Box<dyn Stream<Item = EngineCommand<_>> + Send + Unpin> =
+		match sealing {
+			Sealing::Instant => Box::new(stream::select(
+				commands_stream,
+				transaction_pool
+					.import_notification_stream()
+					.map(|_| EngineCommand::SealNewBlock {
+						create_empty: false,
+						finalize: true,
+						parent_hash: None,
+						sender: None,
+					}),
+			)),
+			Sealing::Manual => Box::new(commands_stream),
+		};
+
+	let client_clone = client.clone();
+	let slot_duration = babe_link.config().slot_duration();
+	let authorship = sc_consensus_manual_seal::run_manual_seal(ManualSealParams {
+		block_import,
+		env: proposer,
+		client: client.clone(),
+		pool: transaction_pool,
+		commands_stream,
+		select_chain,
+		consensus_data_provider: Some(Box::new(consensus_data_provider)),
+		create_inherent_data_providers: move |parent, ()| {
+			let client_clone = client_clone.clone();
+			let time_warp = time_warp.clone();
+			async move {
+				let header = client_clone
+					.header(BlockId::Hash(parent))?
+					.ok_or_else(|| format!("Parent header {} not found", parent))?;
+				let parent_slot = if header.number().is_zero() {
+					0
+				} else {
+					*sc_consensus_babe::find_pre_digest::<Block>(&header)
+						.map_err(|e| format!("{:?}", e))?
+						.slot()
+				};
+
+				// Never reuse a slot, even when sealing faster than the slot
+				// duration.
+				let slot_millis = slot_duration.as_millis() as u64;
+				let next_slot_millis = parent_slot.saturating_add(1).saturating_mul(slot_millis);
+				let timestamp = sp_timestamp::InherentDataProvider::new(
+					now_millis()
+						.saturating_add(time_warp.offset())
+						.max(next_slot_millis)
+						.into(),
+				);
+
+				let slot =
+					sp_consensus_babe::inherents::InherentDataProvider::from_timestamp_and_duration(
+						*timestamp,
+						slot_duration,
+					);
+
+				Ok::<_, Box<dyn std::error::Error + Send + Sync>>((timestamp, slot))
+			}
+		},
+	});
+
+	task_manager
+		.spawn_essential_handle()
+		.spawn_blocking("manual-seal", authorship);
+
+	network_starter.start_network();
+
+	Ok(NewFull {
+		task_manager,
+		client,
+		network,
+		rpc_handlers,
+		keystore: keystore_container.sync_keystore(),
+	})
+}
+
+/// Build a development node sealing blocks with manual-seal.
+pub fn build_dev(
+	config: Configuration,
+	sealing: Sealing,
+	dns: Option<dns::Config>,
+) -> Result<NewFull<Client>, Error> {
+	let dns = dns.map(|dns| dns::with_changelog(dns, &config));
+	match config.chain_spec.identify_variant() {
+		ChainVariant::Neatcoin => {
+			new_dev::<neatcoin_runtime::RuntimeApi, NeatcoinExecutorDispatch>(config, sealing).map(
+				|full| {
+					if let Some(dns) = dns {
+						dns::spawn::<dns::NeatcoinZoneEvents, _, FullBackend>(
+							&full.task_manager,
+							full.client.clone(),
+							full.keystore.clone(),
+							dns,
+						);
+					}
+					full.with_client(Client::Neatcoin)
+				},
+			)
+		}
+		ChainVariant::Vodka => {
+			new_dev::<vodka_runtime::RuntimeApi, VodkaExecutorDispatch>(config, sealing).map(
+				|full| {
+					if let Some(dns) = dns {
+						dns::spawn::<dns::VodkaZoneEvents, _, FullBackend>(
+							&full.task_manager,
+							full.client.clone(),
+							full.keystore.clone(),
+							dns,
+						);
+					}
+					full.with_client(Client::Vodka)
+				},
+			)
+		}
+	}
+}
@@ -0,0 +1,94 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// This file is part of Neatcoin.
+//
+// Copyright (c) 2021 Wei Tang.
+//
+// Neatcoin is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Neatcoin is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Neatcoin. If not, see <http://www.gnu.org/licenses/>.
+
+//! DNS frontend of full nodes.
+
+use codec::Decode;
+use np_domain::Name;
+use np_opaque::{Block, Hash};
+use sc_client_api::{Backend, BlockchainEvents, StorageProvider};
+use sc_service::{Configuration, TaskManager};
+use sp_blockchain::HeaderBackend;
+use sp_keystore::SyncCryptoStorePtr;
+use std::sync::Arc;
+
+pub use neatcoin_dns::{Config, IpNet, Upstreams, ZoneEvents};
+
+macro_rules! zone_events {
+	($name:ident, $runtime:ident) => {
+		/// Zone changes decoded from the events of the runtime.
+		pub struct $name;
+
+		impl ZoneEvents for $name {
+			fn changed_names(events: &[u8]) -> Result<Vec<Name>, codec::Error> {
+				use $runtime::Event;
+
+				let records =
+					Vec::<frame_system::EventRecord<Event, Hash>>::decode(&mut &events[..])?;
+				Ok(records
+					.into_iter()
+					.filter_map(|record| match record.event {
+						Event::Zone(pallet_zone::Event::SetA(name, _))
+						| Event::Zone(pallet_zone::Event::SetAAAA(name, _))
+						| Event::Zone(pallet_zone::Event::SetNS(name, _))
+						| Event::Zone(pallet_zone::Event::SetCNAME(name, _))
+						| Event::Zone(pallet_zone::Event::SetMX(name, _))
+						| Event::Zone(pallet_zone::Event::SetDNSKEY(name, _))
+						| Event::Zone(pallet_zone::Event::SetDS(name, _))
+						| Event::Zone(pallet_zone::Event::SetRRSIG(name, _)) => Some(name),
+						_ => None,
+					})
+					.collect())
+			}
+		}
+	};
+}
+
+zone_events!(NeatcoinZoneEvents, neatcoin_runtime);
+zone_events!(VodkaZoneEvents, vodka_runtime);
+
+/// Fill in the changelog directory under the chain's config directory,
+/// unless one is set.
+pub(crate) fn with_changelog(mut dns: Config, config: &Configuration) -> Config {
+	if dns.changelog.is_none() {
+		dns.changelog = config
+			.base_path
+			.as_ref()
+			.map(|base| base.config_dir(config.chain_spec.id()).join("dns"));
+	}
+	dns
+}
+
+/// Spawn the DNS frontend of a full node, decoding events with `E` and
+/// signing zones with the node keystore.
+pub(crate) fn spawn<E, C, B>(
+	task_manager: &TaskManager,
+	client: Arc<C>,
+	keystore: SyncCryptoStorePtr,
+	dns: Config,
+) where
+	E: ZoneEvents,
+	C: StorageProvider<Block, B> + HeaderBackend<Block> + BlockchainEvents<Block>,
+	C: Send + Sync + 'static,
+	B: Backend<Block> + 'static,
+{
+	task_manager.spawn_handle().spawn(
+		"dns",
+		neatcoin_dns::run::<E, _, _>(client, Some(keystore), dns),
+	);
+}
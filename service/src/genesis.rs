@@ -0,0 +1,406 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// This file is part of Neatcoin.
+//
+// Copyright (c) 2021 Wei Tang.
+//
+// Neatcoin is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Neatcoin is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Neatcoin. If not, see <http://www.gnu.org/licenses/>.
+
+//! Genesis allocations carried over from Kulupu.
+//!
+//! Allocations are stored in `res/genesis.json` as an object mapping Kulupu
+//! SS58 addresses to decimal balance strings.
+
+use codec::Encode;
+use indexmap::IndexMap;
+use np_opaque::{AccountId, Balance};
+use serde::de::{Deserialize, Deserializer, MapAccess, Visitor};
+use sp_core::{
+	crypto::{Ss58AddressFormat, Ss58AddressFormatRegistry, Ss58Codec},
+	hashing::{blake2_256, twox_128},
+	H256,
+};
+use std::{
+	collections::{BTreeMap, HashSet},
+	convert::TryFrom,
+	fmt,
+};
+
+/// Bundled genesis allocations.
+pub const GENESIS_ALLOCATIONS: &[u8] = include_bytes!("../res/genesis.json");
+
+#[derive(thiserror::Error, Debug)]
+pub enum Error {
+	#[error("invalid json: {0}")]
+	Json(#[from] serde_json::Error),
+
+	#[error("invalid address {address}: {reason}")]
+	InvalidAddress { address: String, reason: String },
+
+	#[error("address {address} is not a Kulupu address (format {format})")]
+	WrongAddressFormat { address: String, format: u16 },
+
+	#[error("address {address} is listed more than once")]
+	DuplicateAddress { address: String },
+
+	#[error("balance {value:?} of {address} is not a decimal number")]
+	InvalidBalance { address: String, value: String },
+
+	#[error("balance of {address} is zero")]
+	ZeroBalance { address: String },
+
+	#[error("total allocation overflows")]
+	TotalOverflow,
+
+	#[error("total allocation is zero")]
+	ZeroTotal,
+
+	#[error("total allocation is {actual}, expected {expected}")]
+	TotalMismatch { expected: Balance, actual: Balance },
+
+	#[error("chain spec has no raw genesis storage")]
+	MissingRawStorage,
+
+	#[error("invalid raw storage entry {key}: {reason}")]
+	InvalidStorage { key: String, reason: String },
+
+	#[error("balance of {address} in chain spec is {actual}, expected {expected}")]
+	AccountMismatch {
+		address: String,
+		expected: Balance,
+		actual: Balance,
+	},
+
+	#[error("total issuance in chain spec is {actual}, expected {expected}")]
+	IssuanceMismatch { expected: Balance, actual: Balance },
+}
+
+/// Verified genesis allocations, in file order.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Allocations(pub Vec<(AccountId, Balance)>);
+
+impl Allocations {
+	/// Total allocated balance.
+	pub fn total(&self) -> Balance {
+		self.0.iter().map(|(_, balance)| balance).sum()
+	}
+
+	/// Blake2-256 hash of the SCALE-encoded allocations.
+	pub fn checksum(&self) -> H256 {
+		H256::from(blake2_256(&self.0.encode()))
+	}
+
+	/// Render allocations as `genesis.json`.
+	pub fn to_json(&self) -> String {
+		let raw = self
+			.0
+			.iter()
+			.map(|(account, balance)| {
+				(
+					account.to_ss58check_with_version(kulupu_format()),
+					balance.to_string(),
+				)
+			})
+			.collect::<IndexMap<_, _>>();
+
+		serde_json::to_string_pretty(&raw).expect("string map always serializes; qed")
+	}
+}
+
+/// Object entries in document order, keeping duplicate keys.
+struct RawEntries(Vec<(String, String)>);
+
+impl<'de> Deserialize<'de> for RawEntries {
+	fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+		struct EntriesVisitor;
+
+		impl<'de> Visitor<'de> for EntriesVisitor {
+			type Value = RawEntries;
+
+			fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+				write!(f, "an object of addresses to balance strings")
+			}
+
+			fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<RawEntries, A::Error> {
+				let mut entries = Vec::new();
+				while let Some(entry) = map.next_entry()? {
+					entries.push(entry);
+				}
+				Ok(RawEntries(entries))
+			}
+		}
+
+		deserializer.deserialize_map(EntriesVisitor)
+	}
+}
+
+fn kulupu_format() -> Ss58AddressFormat {
+	Ss58AddressFormatRegistry::KulupuAccount.into()
+}
+
+/// Parse and verify genesis allocations. Every address must be a unique
+/// Kulupu address with a non-zero balance, and the total must not be zero.
+pub fn parse_allocations(json: &[u8]) -> Result<Allocations, Error> {
+	let RawEntries(entries) = serde_json::from_slice(json)?;
+
+	let mut seen = HashSet::new();
+	let mut allocations = Vec::with_capacity(entries.len());
+	let mut total: Balance = 0;
+	for (address, value) in entries {
+		let (account, format) =
+			AccountId::from_ss58check_with_version(&address).map_err(|e| {
+				Error::InvalidAddress {
+					address: address.clone(),
+					reason: format!("{:?}", e),
+				}
+			})?;
+		if Ss58AddressFormatRegistry::try_from(format) !=
+			Ok(Ss58AddressFormatRegistry::KulupuAccount)
+		{
+			return Err(Error::WrongAddressFormat { address, format: format.prefix() })
+		}
+		if !seen.insert(account.clone()) {
+			return Err(Error::DuplicateAddress { address })
+		}
+
+		let balance = value
+			.parse::<Balance>()
+			.map_err(|_| Error::InvalidBalance { address: address.clone(), value })?;
+		if balance == 0 {
+			return Err(Error::ZeroBalance { address })
+		}
+		total = total.checked_add(balance).ok_or(Error::TotalOverflow)?;
+
+		allocations.push((account, balance));
+	}
+
+	if total == 0 {
+		return Err(Error::ZeroTotal)
+	}
+
+	Ok(Allocations(allocations))
+}
+
+/// Check the allocation total against an expected supply.
+pub fn verify_total(allocations: &Allocations, expected: Balance) -> Result<(), Error> {
+	let actual = allocations.total();
+	if actual != expected {
+		return Err(Error::TotalMismatch { expected, actual })
+	}
+	Ok(())
+}
+
+/// Raw top-level genesis storage of a chain spec, such as one built by
+/// `build-spec --raw` or `export-state`.
+pub fn raw_storage(spec_json: &[u8]) -> Result<BTreeMap<Vec<u8>, Vec<u8>>, Error> {
+	let spec: serde_json::Value = serde_json::from_slice(spec_json)?;
+	let top = spec
+		.pointer("/genesis/raw/top")
+		.and_then(|top| top.as_object())
+		.ok_or(Error::MissingRawStorage)?;
+
+	let decode = |key: &str, value: &str| {
+		hex::decode(value.trim_start_matches("0x")).map_err(|e| Error::InvalidStorage {
+			key: key.to_string(),
+			reason: e.to_string(),
+		})
+	};
+
+	top.iter()
+		.map(|(key, value)| {
+			let value = value.as_str().ok_or_else(|| Error::InvalidStorage {
+				key: key.clone(),
+				reason: "value is not a string".to_string(),
+			})?;
+			Ok((decode(key, key)?, decode(key, value)?))
+		})
+		.collect()
+}
+
+fn storage_prefix(pallet: &[u8], item: &[u8]) -> Vec<u8> {
+	let mut prefix = twox_128(pallet).to_vec();
+	prefix.extend_from_slice(&twox_128(item));
+	prefix
+}
+
+/// Free and reserved balances of all `System::Account` entries. Only the
+/// trailing `AccountData` is decoded, so it works across changes of the
+/// reference count fields.
+pub fn account_balances(
+	storage: &BTreeMap<Vec<u8>, Vec<u8>>,
+) -> Result<BTreeMap<AccountId, (Balance, Balance)>, Error> {
+	// Blake2_128Concat key hasher.
+	const HASH_LEN: usize = 16;
+	// Free, reserved, misc frozen and fee frozen.
+	const DATA_LEN: usize = 4 * 16;
+
+	let prefix = storage_prefix(b"System", b"Account");
+	let balance_at = |value: &[u8], offset: usize| {
+		let mut raw = [0u8; 16];
+		raw.copy_from_slice(&value[offset..offset + 16]);
+		Balance::from_le_bytes(raw)
+	};
+
+	storage
+		.iter()
+		.filter(|(key, _)| key.starts_with(&prefix))
+		.map(|(key, value)| {
+			let invalid = |reason: &str| Error::InvalidStorage {
+				key: format!("0x{}", hex::encode(key)),
+				reason: reason.to_string(),
+			};
+
+			let raw_account = &key[prefix.len()..];
+			if raw_account.len() != HASH_LEN + 32 {
+				return Err(invalid("unexpected account key length"))
+			}
+			let account = AccountId::try_from(&raw_account[HASH_LEN..])
+				.map_err(|_| invalid("invalid account id"))?;
+			if value.len() < DATA_LEN {
+				return Err(invalid("account info too short"))
+			}
+			let data = value.len() - DATA_LEN;
+
+			Ok((account, (balance_at(value, data), balance_at(value, data + 16))))
+		})
+		.collect()
+}
+
+/// Summary of a chain spec checked against allocations.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SpecReport {
+	/// Total issuance recorded in the chain spec.
+	pub total_issuance: Balance,
+	/// Funded accounts in the chain spec that are not allocations, such as
+	/// bootstrap validators.
+	pub extra: Vec<(AccountId, Balance)>,
+}
+
+/// Check that a built chain spec funds every allocation with exactly its
+/// balance, and that its total issuance is consistent with its accounts.
+pub fn verify_spec(allocations: &Allocations, spec_json: &[u8]) -> Result<SpecReport, Error> {
+	let storage = raw_storage(spec_json)?;
+	let mut balances = account_balances(&storage)?;
+
+	for (account, expected) in &allocations.0 {
+		let actual = balances
+			.remove(account)
+			.map(|(free, reserved)| free.saturating_add(reserved))
+			.unwrap_or_default();
+		if actual != *expected {
+			return Err(Error::AccountMismatch {
+				address: account.to_ss58check_with_version(kulupu_format()),
+				expected: *expected,
+				actual,
+			})
+		}
+	}
+
+	let extra = balances
+		.into_iter()
+		.map(|(account, (free, reserved))| (account, free.saturating_add(reserved)))
+		.filter(|(_, balance)| *balance != 0)
+		.collect::<Vec<_>>();
+	let expected = extra
+		.iter()
+		.map(|(_, balance)| balance)
+		.fold(allocations.total(), |acc, balance| acc.saturating_add(*balance));
+
+	let issuance_key = storage_prefix(b"Balances", b"TotalIssuance");
+	let total_issuance = storage
+		.get(&issuance_key)
+		.map(|value| {
+			<Balance as codec::Decode>::decode(&mut &value[..]).map_err(|_| Error::InvalidStorage {
+				key: format!("0x{}", hex::encode(&issuance_key)),
+				reason: "invalid balance".to_string(),
+			})
+		})
+		.transpose()?
+		.unwrap_or_default();
+	if total_issuance != expected {
+		return Err(Error::IssuanceMismatch { expected, actual: total_issuance })
+	}
+
+	Ok(SpecReport { total_issuance, extra })
+}
+
+/// Build allocations from a Kulupu state export. Free and reserved balances
+/// are both allocated, and empty accounts are skipped. Allocations are
+/// ordered by balance, largest first.
+pub fn allocations_from_state(state_json: &[u8]) -> Result<Allocations, Error> {
+	let storage = raw_storage(state_json)?;
+	let mut allocations = account_balances(&storage)?
+		.into_iter()
+		.map(|(account, (free, reserved))| (account, free.saturating_add(reserved)))
+		.filter(|(_, balance)| *balance != 0)
+		.collect::<Vec<_>>();
+	allocations.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+	let allocations = Allocations(allocations);
+	if allocations.total() == 0 {
+		return Err(Error::ZeroTotal)
+	}
+	Ok(allocations)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const ADDRESS: &str = "2gG8yKQinbw315xw7ASkj9J5f2diKmiqNcDeTb1CbGwB9jvq";
+
+	#[test]
+	fn bundled_allocations_verify() {
+		let allocations = parse_allocations(GENESIS_ALLOCATIONS).unwrap();
+		assert_eq!(allocations.0.len(), 270);
+		assert_eq!(allocations.total(), 32_721_493_670_883_249_638);
+	}
+
+	#[test]
+	fn bundled_spec_matches_allocations() {
+		let allocations = parse_allocations(GENESIS_ALLOCATIONS).unwrap();
+		let report =
+			verify_spec(&allocations, include_bytes!("../res/neatcoin-spec.json")).unwrap();
+		assert_eq!(report.total_issuance, 32_721_493_680_883_249_638);
+	}
+
+	#[test]
+	fn rejects_duplicates() {
+		let json = format!(r#"{{"{0}": "1", "{0}": "2"}}"#, ADDRESS);
+		assert!(matches!(
+			parse_allocations(json.as_bytes()),
+			Err(Error::DuplicateAddress { .. })
+		));
+	}
+
+	#[test]
+	fn rejects_non_kulupu_address() {
+		let json = r#"{"5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY": "1"}"#;
+		assert!(matches!(
+			parse_allocations(json.as_bytes()),
+			Err(Error::WrongAddressFormat { format: 42, .. })
+		));
+	}
+
+	#[test]
+	fn rejects_zero_total() {
+		assert!(matches!(parse_allocations(b"{}"), Err(Error::ZeroTotal)));
+	}
+
+	#[test]
+	fn json_roundtrips() {
+		let allocations = parse_allocations(GENESIS_ALLOCATIONS).unwrap();
+		let rendered = allocations.to_json();
+		assert_eq!(parse_allocations(rendered.as_bytes()).unwrap(), allocations);
+	}
+}
@@ -18,6 +18,11 @@
 
 pub mod chain_spec;
 mod client;
+mod dev;
+pub mod dns;
+pub mod genesis;
+mod names;
+pub mod transaction;
 
 use np_opaque::Block;
 use sc_basic_authorship::ProposerFactory;
@@ -29,6 +34,7 @@ use sc_service::{
 };
 use sc_telemetry::{Telemetry, TelemetryWorker};
 use sp_api::ConstructRuntimeApi;
+use sp_keystore::SyncCryptoStorePtr;
 use sp_runtime::traits::Block as BlockT;
 use std::{sync::Arc, time::Duration};
 use substrate_prometheus_endpoint::Registry;
@@ -36,6 +42,8 @@ use substrate_prometheus_endpoint::Registry;
 pub use crate::client::{
 	AbstractClient, Client, ClientHandle, ExecuteWithClient, RuntimeApiCollection,
 };
+pub use crate::dev::{build_dev, Sealing};
+pub use neatcoin_nomo as nomo;
 pub use neatcoin_runtime;
 pub use vodka_runtime;
 
@@ -91,6 +99,9 @@ pub enum Error {
 
 	#[error(transparent)]
 	Telemetry(#[from] sc_telemetry::Error),
+
+	#[error(transparent)]
+	ManualSeal(#[from] sc_consensus_manual_seal::Error),
 }
 
 #[derive(Copy, Clone, Eq, PartialEq, Debug)]
@@ -298,6 +309,7 @@ where
 		let transaction_pool = transaction_pool.clone();
 		let select_chain = select_chain.clone();
 		let chain_spec = config.chain_spec.cloned_box();
+		let name_events = names::decoder(config.chain_spec.identify_variant());
 
 		move |deny_unsafe, subscription_executor| -> Result<neatcoin_rpc::RpcExtension, _> {
 			let deps = neatcoin_rpc::FullDeps {
@@ -318,6 +330,7 @@ where
 					subscription_executor,
 					finality_provider: finality_proof_provider.clone(),
 				},
+				name_events,
 			};
 
 			Ok(neatcoin_rpc::create_full(deps)?)
@@ -347,6 +360,7 @@ pub struct NewFull<C> {
 	pub client: C,
 	pub network: Arc<sc_network::NetworkService<Block, <Block as BlockT>::Hash>>,
 	pub rpc_handlers: RpcHandlers,
+	pub keystore: SyncCryptoStorePtr,
 }
 
 impl<C> NewFull<C> {
@@ -357,6 +371,7 @@ impl<C> NewFull<C> {
 			task_manager: self.task_manager,
 			network: self.network,
 			rpc_handlers: self.rpc_handlers,
+			keystore: self.keystore,
 		}
 	}
 }
@@ -601,17 +616,42 @@ where
 		client,
 		network,
 		rpc_handlers,
+		keystore: keystore_container.sync_keystore(),
 	})
 }
 
-pub fn build_full(config: Configuration) -> Result<NewFull<Client>, Error> {
+/// Builds a new full node, serving DNS if `dns` is given.
+pub fn build_full(
+	config: Configuration,
+	dns: Option<dns::Config>,
+) -> Result<NewFull<Client>, Error> {
+	let dns = dns.map(|dns| dns::with_changelog(dns, &config));
 	match config.chain_spec.identify_variant() {
 		ChainVariant::Neatcoin => {
-			new_full::<neatcoin_runtime::RuntimeApi, NeatcoinExecutorDispatch>(config)
-				.map(|full| full.with_client(Client::Neatcoin))
+			new_full::<neatcoin_runtime::RuntimeApi, NeatcoinExecutorDispatch>(config).map(|full| {
+				if let Some(dns) = dns {
+					dns::spawn::<dns::NeatcoinZoneEvents, _, FullBackend>(
+						&full.task_manager,
+						full.client.clone(),
+						full.keystore.clone(),
+						dns,
+					);
+				}
+				full.with_client(Client::Neatcoin)
+			})
 		}
 		ChainVariant::Vodka => new_full::<vodka_runtime::RuntimeApi, VodkaExecutorDispatch>(config)
-			.map(|full| full.with_client(Client::Vodka)),
+			.map(|full| {
+				if let Some(dns) = dns {
+					dns::spawn::<dns::VodkaZoneEvents, _, FullBackend>(
+						&full.task_manager,
+						full.client.clone(),
+						full.keystore.clone(),
+						dns,
+					);
+				}
+				full.with_client(Client::Vodka)
+			}),
 	}
 }
 
@@ -0,0 +1,76 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// This file is part of Neatcoin.
+//
+// Copyright (c) 2021 Wei Tang.
+//
+// Neatcoin is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Neatcoin is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Neatcoin. If not, see <http://www.gnu.org/licenses/>.
+
+//! Name changes decoded from runtime events, for RPC subscriptions.
+
+use crate::ChainVariant;
+use codec::Decode;
+use neatcoin_rpc::nomo::{NameChangeKind, NameEvents};
+use np_domain::Name;
+use np_opaque::Hash;
+
+macro_rules! name_events {
+	($name:ident, $runtime:ident) => {
+		/// Name changes decoded from the events of the runtime.
+		fn $name(events: &[u8]) -> Result<Vec<(Name, NameChangeKind)>, codec::Error> {
+			use $runtime::Event;
+
+			let records = Vec::<frame_system::EventRecord<Event, Hash>>::decode(&mut &events[..])?;
+			Ok(records
+				.into_iter()
+				.filter_map(|record| match record.event {
+					Event::Registry(pallet_registry::Event::OwnershipSet(name, _)) => {
+						Some((name, NameChangeKind::Ownership))
+					}
+					Event::FCFS(pallet_fcfs::Event::Registered(name, _))
+					| Event::FCFS(pallet_fcfs::Event::Renewed(name, _))
+					| Event::FCFS(pallet_fcfs::Event::Expired(name)) => {
+						Some((name, NameChangeKind::Renewal))
+					}
+					Event::Zone(pallet_zone::Event::SetA(name, _))
+					| Event::Zone(pallet_zone::Event::SetAAAA(name, _))
+					| Event::Zone(pallet_zone::Event::SetNS(name, _))
+					| Event::Zone(pallet_zone::Event::SetCNAME(name, _))
+					| Event::Zone(pallet_zone::Event::SetMX(name, _))
+					| Event::Zone(pallet_zone::Event::SetICANN(name))
+					| Event::Zone(pallet_zone::Event::SetOpenNIC(name))
+					| Event::Zone(pallet_zone::Event::SetHandshake(name))
+					| Event::Zone(pallet_zone::Event::ResetExtern(name))
+					| Event::Zone(pallet_zone::Event::SetDNSKEY(name, _))
+					| Event::Zone(pallet_zone::Event::SetDS(name, _))
+					| Event::Zone(pallet_zone::Event::SetRRSIG(name, _))
+					| Event::Zone(pallet_zone::Event::SetDNSSEC(name, _)) => {
+						Some((name, NameChangeKind::Records))
+					}
+					_ => None,
+				})
+				.collect())
+		}
+	};
+}
+
+name_events!(neatcoin_name_events, neatcoin_runtime);
+name_events!(vodka_name_events, vodka_runtime);
+
+/// Decoder of name changes from the events of the runtime of a chain.
+pub(crate) fn decoder(variant: ChainVariant) -> NameEvents {
+	match variant {
+		ChainVariant::Neatcoin => neatcoin_name_events,
+		ChainVariant::Vodka => vodka_name_events,
+	}
+}
@@ -0,0 +1,98 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// This file is part of Neatcoin.
+//
+// Copyright (c) 2021 Wei Tang.
+//
+// Neatcoin is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Neatcoin is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Neatcoin. If not, see <http://www.gnu.org/licenses/>.
+
+//! Signing of transactions outside of a running node.
+
+use crate::ChainVariant;
+use codec::Encode;
+use neatcoin_nomo::import::ZoneCall;
+use np_opaque::{Hash, Nonce};
+use sp_core::{sr25519, Pair};
+use sp_runtime::{
+	generic::Era, traits::IdentifyAccount, MultiAddress, MultiSignature, MultiSigner,
+};
+
+/// Chain state a transaction is signed against.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SigningInfo {
+	pub nonce: Nonce,
+	pub genesis_hash: Hash,
+	pub spec_version: u32,
+	pub transaction_version: u32,
+}
+
+/// Sign zone calls wrapped in a single `batch_all`, returning the encoded
+/// extrinsic. The transaction is immortal and pays no tip.
+pub fn sign_zone_batch(
+	variant: ChainVariant,
+	calls: Vec<ZoneCall>,
+	signer: &sr25519::Pair,
+	info: &SigningInfo,
+) -> Vec<u8> {
+	macro_rules! sign {
+		($runtime:ident) => {{
+			use $runtime::{Call, Runtime, SignedPayload, UncheckedExtrinsic};
+
+			let call = Call::Utility(pallet_utility::Call::batch_all {
+				calls: calls
+					.into_iter()
+					.map(|call| Call::Zone(call.into_call::<Runtime>()))
+					.collect(),
+			});
+			let extra = (
+				frame_system::CheckSpecVersion::<Runtime>::new(),
+				frame_system::CheckTxVersion::<Runtime>::new(),
+				frame_system::CheckGenesis::<Runtime>::new(),
+				frame_system::CheckMortality::<Runtime>::from(Era::Immortal),
+				frame_system::CheckNonce::<Runtime>::from(info.nonce),
+				frame_system::CheckWeight::<Runtime>::new(),
+				pallet_transaction_payment::ChargeTransactionPayment::<Runtime>::from(0),
+			);
+			let payload = SignedPayload::from_raw(
+				call,
+				extra,
+				(
+					info.spec_version,
+					info.transaction_version,
+					info.genesis_hash,
+					info.genesis_hash,
+					(),
+					(),
+					(),
+				),
+			);
+
+			let signature = payload.using_encoded(|payload| signer.sign(payload));
+			let account = MultiSigner::from(signer.public()).into_account();
+			let (call, extra, _) = payload.deconstruct();
+
+			UncheckedExtrinsic::new_signed(
+				call,
+				MultiAddress::Id(account),
+				MultiSignature::from(signature),
+				extra,
+			)
+			.encode()
+		}};
+	}
+
+	match variant {
+		ChainVariant::Neatcoin => sign!(neatcoin_runtime),
+		ChainVariant::Vodka => sign!(vodka_runtime),
+	}
+}
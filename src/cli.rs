@@ -16,7 +16,14 @@
 // You should have received a copy of the GNU General Public License
 // along with Neatcoin. If not, see <http://www.gnu.org/licenses/>.
 
-use sc_cli::RunCmd;
+use neatcoin_service::dns::{Config as DnsConfig, IpNet, Upstreams};
+use sc_cli::{
+	BlockNumberOrHash, CliConfiguration, DatabaseParams, PruningParams, RunCmd, SharedParams,
+};
+use std::{
+	net::{IpAddr, SocketAddr},
+	path::PathBuf,
+};
 use structopt::StructOpt;
 
 #[derive(Debug, StructOpt)]
@@ -26,6 +33,93 @@ pub struct Cli {
 
 	#[structopt(flatten)]
 	pub run: RunCmd,
+
+	/// Seal blocks with manual-seal instead of BABE and GRANDPA. Only
+	/// available on development and local chains. The development chain then
+	/// starts with an FCFS registration period of 20 blocks, so names expire
+	/// by sealing blocks. `engine_warp` only moves timestamps, by less than
+	/// one epoch per call, and does not bring expiry closer.
+	#[structopt(long, possible_values = &["instant", "manual"])]
+	pub sealing: Option<neatcoin_service::Sealing>,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub dns: DnsParams,
+}
+
+#[derive(Debug, StructOpt)]
+pub struct DnsParams {
+	/// Serve Nomo zones over DNS on this address, over both UDP and TCP.
+	/// Answers are read from finalized state.
+	#[structopt(long = "dns-listen", value_name = "ADDR")]
+	pub listen: Option<SocketAddr>,
+
+	/// Serve DNS over HTTPS (RFC 8484) and its JSON API on this address, at
+	/// `/dns-query`. The endpoint speaks plain HTTP, and must sit behind a
+	/// reverse proxy terminating TLS.
+	#[structopt(long = "doh-listen", value_name = "ADDR")]
+	pub doh: Option<SocketAddr>,
+
+	/// Longest time in seconds DNS over HTTPS responses may be cached for,
+	/// whatever the TTL of their records.
+	#[structopt(long = "doh-cache-limit", value_name = "SECONDS", default_value = "60")]
+	pub doh_cache_limit: u32,
+
+	/// Address or network allowed to transfer zones with AXFR and IXFR,
+	/// such as `192.0.2.0/24`. Can be given multiple times.
+	#[structopt(long = "dns-allow-transfer", value_name = "NET", parse(try_from_str = parse_net))]
+	pub allow_transfer: Vec<IpNet>,
+
+	/// Secondary server sent DNS NOTIFY when a finalized block changes its
+	/// zones, such as `192.0.2.1:53`. Can be given multiple times.
+	#[structopt(long = "dns-notify", value_name = "ADDR")]
+	pub notify: Vec<SocketAddr>,
+
+	/// Resolver answering names root flagged as ICANN, such as
+	/// `192.0.2.53:53`. Can be given multiple times, and resolvers are tried
+	/// in order. Names owned on chain below a flagged name are still
+	/// answered from chain.
+	#[structopt(long = "dns-upstream-icann", value_name = "ADDR")]
+	pub upstream_icann: Vec<SocketAddr>,
+
+	/// Resolver answering names root flagged as OpenNIC. Can be given
+	/// multiple times.
+	#[structopt(long = "dns-upstream-opennic", value_name = "ADDR")]
+	pub upstream_opennic: Vec<SocketAddr>,
+
+	/// Resolver answering names root flagged as Handshake. Can be given
+	/// multiple times.
+	#[structopt(long = "dns-upstream-handshake", value_name = "ADDR")]
+	pub upstream_handshake: Vec<SocketAddr>,
+}
+
+impl DnsParams {
+	/// Configuration of the DNS frontend, if enabled.
+	pub fn config(&self) -> Option<DnsConfig> {
+		if self.listen.is_none() && self.doh.is_none() {
+			return None
+		}
+
+		Some(DnsConfig {
+			listen: self.listen,
+			doh: self.doh,
+			doh_cache_limit: self.doh_cache_limit,
+			allow_transfer: self.allow_transfer.clone(),
+			notify: self.notify.clone(),
+			upstreams: Upstreams {
+				icann: self.upstream_icann.clone(),
+				opennic: self.upstream_opennic.clone(),
+				handshake: self.upstream_handshake.clone(),
+			},
+			changelog: None,
+		})
+	}
+}
+
+fn parse_net(s: &str) -> Result<IpNet, String> {
+	s.parse::<IpNet>()
+		.or_else(|_| s.parse::<IpAddr>().map(IpNet::from))
+		.map_err(|_| format!("invalid address or network {}", s))
 }
 
 #[derive(Debug, StructOpt)]
@@ -53,12 +147,21 @@ pub enum Subcommand {
 	/// Revert the chain to a previous state.
 	Revert(sc_cli::RevertCmd),
 
+	/// Verify or regenerate genesis allocations.
+	Genesis(GenesisCmd),
+
+	/// Inspect Nomo names and zone records in the local database.
+	Nomo(NomoCmd),
+
+	/// Import a zone master file as a signed batch of Nomo zone calls.
+	ImportZone(ImportZoneCmd),
+
 	#[structopt(name = "export-builtin-wasm", setting = structopt::clap::AppSettings::Hidden)]
 	ExportBuiltinWasm(ExportBuiltinWasmCommand),
 
 	/// Try some command against runtime state.
 	#[cfg(feature = "try-runtime")]
-	#[structopt(name = "benchmark", about = "Benchmark runtime pallets.")]
+	#[structopt(name = "try-runtime", about = "Try some command against runtime state.")]
 	TryRuntime(try_runtime_cli::TryRuntimeCmd),
 
 	/// The custom benchmark subcommmand benchmarking runtime pallets.
@@ -72,3 +175,119 @@ pub struct ExportBuiltinWasmCommand {
 	#[structopt()]
 	pub folder: String,
 }
+
+#[derive(Debug, StructOpt)]
+pub enum GenesisCmd {
+	/// Verify genesis allocations and the chain spec built from them.
+	Verify {
+		/// Allocation file. Defaults to the bundled `genesis.json`.
+		#[structopt(long, parse(from_os_str))]
+		allocations: Option<PathBuf>,
+
+		/// Raw chain spec to check balances against. Defaults to the bundled
+		/// Neatcoin chain spec.
+		#[structopt(long, parse(from_os_str))]
+		spec: Option<PathBuf>,
+
+		/// Expected total supply of the allocations.
+		#[structopt(long)]
+		total: Option<u128>,
+	},
+	/// Regenerate genesis allocations from a Kulupu state export, as written
+	/// by `kulupu export-state <block>`.
+	Regenerate {
+		/// Kulupu state export.
+		#[structopt(parse(from_os_str))]
+		state: PathBuf,
+
+		/// Output file. Defaults to stdout.
+		#[structopt(long, short, parse(from_os_str))]
+		output: Option<PathBuf>,
+	},
+}
+
+#[derive(Debug, StructOpt)]
+pub struct NomoCmd {
+	#[structopt(subcommand)]
+	pub subcommand: NomoSubcommand,
+
+	/// Block hash or number to read at. Defaults to the best block.
+	#[structopt(long, value_name = "HASH or NUMBER")]
+	pub at: Option<BlockNumberOrHash>,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub shared_params: SharedParams,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub pruning_params: PruningParams,
+
+	#[allow(missing_docs)]
+	#[structopt(flatten)]
+	pub database_params: DatabaseParams,
+}
+
+#[derive(Debug, StructOpt)]
+pub enum NomoSubcommand {
+	/// Show owners, renewal info and zone records of a dotted name.
+	Show {
+		/// Dotted name, such as `example.neatuser`.
+		name: String,
+	},
+	/// List all names under a dotted parent name.
+	List {
+		/// Dotted parent name. `.` lists all names.
+		parent: String,
+	},
+	/// Export the zone of a dotted apex name and all names beneath it as an
+	/// RFC 1035 master file.
+	ZoneFile {
+		/// Dotted apex name.
+		apex: String,
+
+		/// Output file. Defaults to stdout.
+		#[structopt(long, short, parse(from_os_str))]
+		output: Option<PathBuf>,
+	},
+}
+
+#[derive(Debug, StructOpt)]
+pub struct ImportZoneCmd {
+	/// Zone master file.
+	#[structopt(parse(from_os_str))]
+	pub file: PathBuf,
+
+	/// Dotted apex name of the zone. The signer must own it and every name
+	/// changed under it.
+	#[structopt(long)]
+	pub apex: String,
+
+	/// Secret URI of the sr25519 signer, such as a mnemonic or `//Alice`.
+	#[structopt(long)]
+	pub suri: String,
+
+	/// HTTP RPC endpoint of the node to read chain state from. Zone names are
+	/// listed with the unsafe `state_getPairs` method, so the node must allow
+	/// unsafe RPC methods, as nodes listening on localhost do by default.
+	#[structopt(long, default_value = "http://localhost:9933")]
+	pub url: String,
+
+	/// Submit the transaction to the node instead of printing it.
+	#[structopt(long)]
+	pub submit: bool,
+}
+
+impl CliConfiguration for NomoCmd {
+	fn shared_params(&self) -> &SharedParams {
+		&self.shared_params
+	}
+
+	fn pruning_params(&self) -> Option<&PruningParams> {
+		Some(&self.pruning_params)
+	}
+
+	fn database_params(&self) -> Option<&DatabaseParams> {
+		Some(&self.database_params)
+	}
+}
@@ -16,11 +16,28 @@
 // You should have received a copy of the GNU General Public License
 // along with Neatcoin. If not, see <http://www.gnu.org/licenses/>.
 
-use crate::cli::{Cli, Subcommand};
+use crate::{
+	cli::{Cli, GenesisCmd, ImportZoneCmd, NomoCmd, NomoSubcommand, Subcommand},
+	remote::Remote,
+};
 use futures::future::TryFutureExt;
 use log::info;
-use neatcoin_service::{chain_spec, ChainVariant, IdentifyVariant};
+use neatcoin_service::{
+	chain_spec, genesis,
+	nomo::{
+		import,
+		zonefile::{self, Zone},
+		ClientStorage, NomoReader, Ownership,
+	},
+	transaction::{self, SigningInfo},
+	ChainVariant, IdentifyVariant,
+};
+use np_domain::Name;
+use np_opaque::AccountId;
 use sc_cli::{ChainSpec, Role, RuntimeVersion, SubstrateCli};
+use sp_blockchain::HeaderBackend;
+use sp_core::{sr25519, Pair};
+use sp_runtime::{generic::BlockId, traits::IdentifyAccount, MultiSigner};
 use std::{fs::File, io::Write, path::PathBuf, sync::Arc};
 
 #[derive(thiserror::Error, Debug)]
@@ -37,6 +54,30 @@ pub enum Error {
 	#[error(transparent)]
 	Io(#[from] std::io::Error),
 
+	#[error("Genesis allocations: {0}")]
+	Genesis(#[from] genesis::Error),
+
+	#[error("Chain spec: {0}")]
+	ChainSpec(String),
+
+	#[error("Nomo storage: {0}")]
+	Nomo(#[from] neatcoin_service::nomo::Error),
+
+	#[error("Invalid block: {0}")]
+	InvalidBlock(String),
+
+	#[error("Invalid name: {0}")]
+	InvalidName(String),
+
+	#[error("Zone file: {0}")]
+	ZoneFile(#[from] zonefile::ParseError),
+
+	#[error("RPC: {0}")]
+	Rpc(#[from] jsonrpc_core_client::RpcError),
+
+	#[error("Zone import: {0}")]
+	ImportZone(String),
+
 	#[error("Wasm binary is not available")]
 	UnavailableWasmBinary,
 }
@@ -70,7 +111,12 @@ impl SubstrateCli for Cli {
 		Ok(match id {
 			"vodka" | "testnet" => Box::new(chain_spec::vodka_config()?),
 			"" | "neatcoin" | "mainnet" => Box::new(chain_spec::neatcoin_config()?),
+			"dev" if self.sealing.is_some() => {
+				Box::new(chain_spec::manual_seal_development_config()?)
+			}
 			"dev" => Box::new(chain_spec::development_config()?),
+			"local" | "neatcoin-local" => Box::new(chain_spec::neatcoin_local_testnet_config()?),
+			"vodka-local" => Box::new(chain_spec::vodka_local_testnet_config()?),
 			_path => return Err("Custom chain spec is not supported".into()),
 		})
 	}
@@ -84,9 +130,13 @@ impl SubstrateCli for Cli {
 }
 
 fn set_default_ss58_version(spec: &Box<dyn sc_service::ChainSpec>) {
+	set_variant_ss58_version(spec.identify_variant())
+}
+
+fn set_variant_ss58_version(variant: ChainVariant) {
 	use sp_core::crypto::Ss58AddressFormatRegistry;
 
-	let ss58_version = match spec.identify_variant() {
+	let ss58_version = match variant {
 		ChainVariant::Neatcoin => Ss58AddressFormatRegistry::NeatcoinAccount,
 		ChainVariant::Vodka => Ss58AddressFormatRegistry::SubstrateAccount,
 	};
@@ -94,6 +144,290 @@ fn set_default_ss58_version(spec: &Box<dyn sc_service::ChainSpec>) {
 	sp_core::crypto::set_default_ss58_version(ss58_version.into());
 }
 
+fn run_genesis(cmd: &GenesisCmd) -> Result<(), Error> {
+	match cmd {
+		GenesisCmd::Verify {
+			allocations,
+			spec,
+			total,
+		} => {
+			let allocations = match allocations {
+				Some(path) => genesis::parse_allocations(&std::fs::read(path)?)?,
+				None => genesis::parse_allocations(genesis::GENESIS_ALLOCATIONS)?,
+			};
+			if let Some(total) = total {
+				genesis::verify_total(&allocations, *total)?;
+			}
+			let spec = match spec {
+				Some(path) => std::fs::read(path)?,
+				None => chain_spec::neatcoin_config()
+					.and_then(|spec| spec.as_json(true))
+					.map_err(Error::ChainSpec)?
+					.into_bytes(),
+			};
+			let report = genesis::verify_spec(&allocations, &spec)?;
+
+			println!("Allocations: {}", allocations.0.len());
+			println!("Total: {}", allocations.total());
+			println!("Checksum: {:?}", allocations.checksum());
+			println!("Total issuance: {}", report.total_issuance);
+			for (account, balance) in report.extra {
+				println!("Additional account: {} {}", account, balance);
+			}
+
+			Ok(())
+		}
+		GenesisCmd::Regenerate { state, output } => {
+			let allocations = genesis::allocations_from_state(&std::fs::read(state)?)?;
+			let json = allocations.to_json();
+
+			match output {
+				Some(path) => {
+					let mut file = File::create(path)?;
+					file.write_all(json.as_bytes())?;
+					file.write_all(b"\n")?;
+					file.flush()?;
+				}
+				None => println!("{}", json),
+			}
+
+			eprintln!(
+				"Regenerated {} allocations, total {}, checksum {:?}",
+				allocations.0.len(),
+				allocations.total(),
+				allocations.checksum()
+			);
+
+			Ok(())
+		}
+	}
+}
+
+fn parse_name(raw: &str) -> Result<Name, Error> {
+	Name::from_dotted(raw.as_bytes()).ok_or_else(|| Error::InvalidName(raw.to_string()))
+}
+
+fn display_name(name: &Name) -> String {
+	String::from_utf8_lossy(&name.to_dotted()).into_owned()
+}
+
+fn display_ownership(ownership: &Ownership) -> String {
+	match ownership {
+		Ownership::None => "none".to_string(),
+		Ownership::Root => "root".to_string(),
+		Ownership::FCFS => "fcfs".to_string(),
+		Ownership::Account(account) => account.to_string(),
+	}
+}
+
+fn run_nomo(cmd: &NomoCmd, client: &neatcoin_service::Client) -> Result<(), Error> {
+	let at = match &cmd.at {
+		Some(at) => at.parse::<np_opaque::Block>().map_err(Error::InvalidBlock)?,
+		None => BlockId::Hash(client.info().best_hash),
+	};
+	let reader = NomoReader::new(ClientStorage::<_, neatcoin_service::FullBackend>::new(
+		client,
+		at.clone(),
+	));
+
+	match &cmd.subcommand {
+		NomoSubcommand::Show { name } => {
+			let name = parse_name(name)?;
+
+			println!("Name: {}", display_name(&name));
+			println!("Owners:");
+			for (name, owner) in reader.owner_chain(&name)? {
+				println!(
+					"  {}: {}",
+					display_name(&name),
+					owner.as_ref().map(display_ownership).unwrap_or_else(|| "-".to_string())
+				);
+			}
+			match reader.effective_owner(&name)? {
+				Some((from, owner)) => println!(
+					"Effective owner: {} (from {})",
+					display_ownership(&owner),
+					display_name(&from)
+				),
+				None => println!("Effective owner: -"),
+			}
+			match reader.renewal(&name)? {
+				Some(info) => println!(
+					"Renewal: expires at block {}, fee {}",
+					info.expire_at, info.fee
+				),
+				None => println!("Renewal: -"),
+			}
+
+			let records = reader.records(&name)?;
+			println!("Records:");
+			for addr in &records.a {
+				println!("  A {}", addr);
+			}
+			for addr in &records.aaaa {
+				println!("  AAAA {}", addr);
+			}
+			for server in &records.ns {
+				println!("  NS {}", display_name(server));
+			}
+			if let Some(target) = &records.cname {
+				println!("  CNAME {}", display_name(target));
+			}
+			if let Some((preference, exchange)) = &records.mx {
+				println!("  MX {} {}", preference, display_name(exchange));
+			}
+			for key in &records.dnskey {
+				println!(
+					"  DNSKEY {} 3 {} ({} byte key)",
+					key.flags,
+					key.algorithm,
+					key.public_key.len()
+				);
+			}
+			for ds in &records.ds {
+				println!("  DS {} {} {}", ds.key_tag, ds.algorithm, ds.digest_type);
+			}
+			if !records.rrsig.is_empty() {
+				println!("  RRSIG ({} pre-computed)", records.rrsig.len());
+			}
+			if let Some(mode) = &records.dnssec {
+				println!("  DNSSEC {:?}", mode);
+			}
+			for (set, external) in [
+				(records.icann, "ICANN"),
+				(records.opennic, "OpenNIC"),
+				(records.handshake, "Handshake"),
+			] {
+				if set {
+					println!("  EXTERN {}", external);
+				}
+			}
+		}
+		NomoSubcommand::List { parent } => {
+			let parent = parse_name(parent)?;
+
+			for (name, owner) in reader.descendants(&parent)? {
+				println!("{} {}", display_name(&name), display_ownership(&owner));
+			}
+		}
+		NomoSubcommand::ZoneFile { apex, output } => {
+			let apex = parse_name(apex)?;
+			let hash = client
+				.expect_block_hash_from_id(&at)
+				.map_err(neatcoin_service::nomo::Error::from)?;
+			let number = client
+				.expect_block_number_from_id(&at)
+				.map_err(neatcoin_service::nomo::Error::from)?;
+			let zone = Zone::read(&reader, &apex, number, hash)?.render();
+
+			match output {
+				Some(path) => {
+					let mut file = File::create(path)?;
+					file.write_all(zone.as_bytes())?;
+					file.flush()?;
+				}
+				None => print!("{}", zone),
+			}
+		}
+	}
+
+	Ok(())
+}
+
+fn run_import_zone(cmd: &ImportZoneCmd) -> Result<(), Error> {
+	let apex = parse_name(&cmd.apex)?;
+	let zone = zonefile::parse(&std::fs::read_to_string(&cmd.file)?, &apex)?;
+	for (line, reason) in &zone.skipped {
+		eprintln!("Skipped line {}: {}", line, reason);
+	}
+
+	let signer = sr25519::Pair::from_string(&cmd.suri, None)
+		.map_err(|e| Error::ImportZone(format!("invalid secret URI: {:?}", e)))?;
+	let account: AccountId = MultiSigner::from(signer.public()).into_account();
+
+	let remote = Remote::connect(&cmd.url)?;
+	let at = remote.best_hash()?;
+	let version = remote.runtime_version(at)?;
+	let (variant, native) = match &*version.spec_name {
+		"neatcoin" => (ChainVariant::Neatcoin, &neatcoin_service::neatcoin_runtime::VERSION),
+		"vodka" => (ChainVariant::Vodka, &neatcoin_service::vodka_runtime::VERSION),
+		other => return Err(Error::ImportZone(format!("unsupported runtime {}", other))),
+	};
+	// Calls are encoded with the native runtime, so they are only valid if
+	// the transaction version matches.
+	if version.transaction_version != native.transaction_version {
+		return Err(Error::ImportZone(format!(
+			"node runtime has transaction version {}, but this binary encodes version {}",
+			version.transaction_version, native.transaction_version
+		)))
+	}
+	set_variant_ss58_version(variant);
+
+	let reader = NomoReader::new(remote.storage(at));
+	if reader.owner(&apex)?.is_none() {
+		return Err(Error::ImportZone(format!(
+			"{} has no owner, so it is not the apex of a zone",
+			display_name(&apex)
+		)))
+	}
+	let mut entries = Vec::new();
+	for (name, records) in zone.entries {
+		match reader.effective_owner(&name)? {
+			Some((owned, _)) if owned == apex => entries.push((name, records)),
+			_ => eprintln!(
+				"Skipped {}: it is not in the zone of {}",
+				display_name(&name),
+				display_name(&apex)
+			),
+		}
+	}
+	let calls = import::diff(&reader.owned_records(&apex)?, &entries);
+	if calls.is_empty() {
+		eprintln!("Zone {} is up to date", display_name(&apex));
+		return Ok(())
+	}
+
+	let mut checked = Vec::<&Name>::new();
+	for call in &calls {
+		if checked.contains(&call.name()) {
+			continue
+		}
+		match reader.effective_owner(call.name())? {
+			Some((_, Ownership::Account(owner))) if owner == account => (),
+			_ => {
+				return Err(Error::ImportZone(format!(
+					"{} is not owned by {}",
+					display_name(call.name()),
+					account
+				)))
+			}
+		}
+		checked.push(call.name());
+	}
+
+	for call in &calls {
+		eprintln!("{}", call);
+	}
+	let info = SigningInfo {
+		nonce: remote.next_nonce(&account)?,
+		genesis_hash: remote.genesis_hash()?,
+		spec_version: version.spec_version,
+		transaction_version: version.transaction_version,
+	};
+	let count = calls.len();
+	let extrinsic = transaction::sign_zone_batch(variant, calls, &signer, &info);
+
+	if cmd.submit {
+		let hash = remote.submit(extrinsic)?;
+		eprintln!("Submitted {} zone calls", count);
+		println!("{:?}", hash);
+	} else {
+		println!("0x{}", hex::encode(extrinsic));
+	}
+
+	Ok(())
+}
+
 /// Parses polkadot specific CLI arguments and run the service.
 pub fn run() -> Result<(), Error> {
 	let cli = Cli::from_args();
@@ -102,17 +436,23 @@ pub fn run() -> Result<(), Error> {
 		None => {
 			let runner = cli.create_runner(&cli.run).map_err(Error::from)?;
 			let chain_spec = &runner.config().chain_spec;
+			let sealing = cli.sealing;
+			let dns = cli.dns.config();
 
 			set_default_ss58_version(chain_spec);
 
 			runner.run_node_until_exit(move |config| async move {
 				let role = config.role.clone();
 
-				let task_manager = match role {
-					Role::Light => {
+				let task_manager = match (role, sealing) {
+					(Role::Light, _) => {
 						neatcoin_service::build_light(config).map(|light| light.task_manager)
 					}
-					_ => neatcoin_service::build_full(config).map(|full| full.task_manager),
+					(_, Some(sealing)) => neatcoin_service::build_dev(config, sealing, dns)
+						.map(|full| full.task_manager),
+					(_, None) => {
+						neatcoin_service::build_full(config, dns).map(|full| full.task_manager)
+					}
 				}?;
 				Ok::<_, Error>(task_manager)
 			})
@@ -207,9 +547,13 @@ pub fn run() -> Result<(), Error> {
 
 			set_default_ss58_version(chain_spec);
 
-			Ok(runner.sync_run(|config| {
-				cmd.run::<neatcoin_service::neatcoin_runtime::Block, neatcoin_service::NeatcoinExecutorDispatch>(config)
-					.map_err(|e| Error::SubstrateCli(e))
+			Ok(runner.sync_run(|config| match config.chain_spec.identify_variant() {
+				ChainVariant::Neatcoin => cmd
+					.run::<neatcoin_service::neatcoin_runtime::Block, neatcoin_service::NeatcoinExecutorDispatch>(config)
+					.map_err(|e| Error::SubstrateCli(e)),
+				ChainVariant::Vodka => cmd
+					.run::<neatcoin_service::vodka_runtime::Block, neatcoin_service::VodkaExecutorDispatch>(config)
+					.map_err(|e| Error::SubstrateCli(e)),
 			})?)
 		}
 		Some(Subcommand::ExportBuiltinWasm(cmd)) => {
@@ -274,9 +618,26 @@ pub fn run() -> Result<(), Error> {
 			Ok(())
 		}
 		Some(Subcommand::Key(cmd)) => Ok(cmd.run(&cli)?),
+		Some(Subcommand::Genesis(cmd)) => run_genesis(cmd),
+		Some(Subcommand::ImportZone(cmd)) => run_import_zone(cmd),
+		Some(Subcommand::Nomo(cmd)) => {
+			let runner = cli.create_runner(cmd)?;
+			let chain_spec = &runner.config().chain_spec;
+
+			set_default_ss58_version(chain_spec);
+
+			Ok(runner.async_run(|mut config| {
+				let ops = neatcoin_service::new_chain_ops(&mut config)?;
+				let result = run_nomo(cmd, &ops.client);
+				Ok((futures::future::ready(result), ops.task_manager))
+			})?)
+		}
 		#[cfg(feature = "try-runtime")]
 		Some(Subcommand::TryRuntime(cmd)) => {
 			let runner = cli.create_runner(cmd)?;
+			let chain_spec = &runner.config().chain_spec;
+
+			set_default_ss58_version(chain_spec);
 
 			runner.async_run(|config| {
 				let registry = config.prometheus_config.as_ref().map(|cfg| &cfg.registry);
@@ -284,11 +645,19 @@ pub fn run() -> Result<(), Error> {
 					sc_service::TaskManager::new(config.tokio_handle.clone(), registry)
 						.map_err(|e| sc_cli::Error::Service(sc_service::Error::Prometheus(e)))?;
 
-				Ok((
-					cmd.run::<neatcoin_service::neatcoin_runtime::Block, neatcoin_service::NeatcoinExecutorDispatch>(config)
-						.map_err(Error::SubstrateCli),
-					task_manager,
-				))
+				let future: std::pin::Pin<Box<dyn std::future::Future<Output = _>>> =
+					match config.chain_spec.identify_variant() {
+						ChainVariant::Neatcoin => Box::pin(
+							cmd.run::<neatcoin_service::neatcoin_runtime::Block, neatcoin_service::NeatcoinExecutorDispatch>(config)
+								.map_err(Error::SubstrateCli),
+						),
+						ChainVariant::Vodka => Box::pin(
+							cmd.run::<neatcoin_service::vodka_runtime::Block, neatcoin_service::VodkaExecutorDispatch>(config)
+								.map_err(Error::SubstrateCli),
+						),
+					};
+
+				Ok((future, task_manager))
 			})
 		}
 	}?;
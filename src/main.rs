@@ -20,6 +20,7 @@
 
 mod cli;
 mod command;
+mod remote;
 
 fn main() -> Result<(), command::Error> {
 	command::run()
@@ -0,0 +1,121 @@
+// SPDX-License-Identifier: GPL-3.0-or-later
+// This file is part of Neatcoin.
+//
+// Copyright (c) 2021 Wei Tang.
+//
+// Neatcoin is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// Neatcoin is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with Neatcoin. If not, see <http://www.gnu.org/licenses/>.
+
+//! Chain state of a running node, read over HTTP RPC.
+
+use jsonrpc_core::Params;
+use jsonrpc_core_client::{transports::http, RawClient, RpcError};
+use neatcoin_service::nomo::{self, StorageRead};
+use np_opaque::{AccountId, Hash, Nonce};
+use sc_cli::RuntimeVersion;
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+use sp_core::Bytes;
+
+/// Connection to the RPC endpoint of a node.
+pub struct Remote {
+	client: RawClient,
+	runtime: tokio::runtime::Runtime,
+}
+
+impl Remote {
+	pub fn connect(url: &str) -> Result<Self, RpcError> {
+		let runtime = tokio::runtime::Builder::new_current_thread()
+			.enable_all()
+			.build()
+			.map_err(|e| RpcError::Other(Box::new(e)))?;
+		let client = runtime.block_on(http::connect::<RawClient>(url))?;
+
+		Ok(Self { client, runtime })
+	}
+
+	fn call<T: DeserializeOwned>(&self, method: &str, params: Vec<Value>) -> Result<T, RpcError> {
+		let value = self
+			.runtime
+			.block_on(self.client.call_method(method, Params::Array(params)))?;
+
+		serde_json::from_value(value).map_err(|e| RpcError::ParseError(method.into(), Box::new(e)))
+	}
+
+	/// Hash of the best block.
+	pub fn best_hash(&self) -> Result<Hash, RpcError> {
+		self.call("chain_getBlockHash", vec![])
+	}
+
+	/// Hash of the genesis block.
+	pub fn genesis_hash(&self) -> Result<Hash, RpcError> {
+		self.call("chain_getBlockHash", vec![json!(0)])
+	}
+
+	/// Runtime version at a block.
+	pub fn runtime_version(&self, at: Hash) -> Result<RuntimeVersion, RpcError> {
+		self.call("state_getRuntimeVersion", vec![json!(at)])
+	}
+
+	/// Next nonce of an account, including transactions in the pool.
+	pub fn next_nonce(&self, account: &AccountId) -> Result<Nonce, RpcError> {
+		self.call("system_accountNextIndex", vec![json!(account)])
+	}
+
+	/// Submit an encoded extrinsic, returning its hash.
+	pub fn submit(&self, extrinsic: Vec<u8>) -> Result<Hash, RpcError> {
+		self.call("author_submitExtrinsic", vec![json!(Bytes(extrinsic))])
+	}
+
+	/// Storage at a block.
+	pub fn storage(&self, at: Hash) -> RemoteStorage<'_> {
+		RemoteStorage { remote: self, at }
+	}
+}
+
+/// Storage of a node at a block. Listing pairs uses `state_getPairs`, an
+/// unsafe RPC method, so it only works with nodes that allow unsafe methods.
+/// Nodes allow them by default only when their RPC listens on localhost, and
+/// otherwise only when started with `--rpc-methods unsafe`.
+pub struct RemoteStorage<'a> {
+	remote: &'a Remote,
+	at: Hash,
+}
+
+impl<'a> StorageRead for RemoteStorage<'a> {
+	fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>, nomo::Error> {
+		self.remote
+			.call::<Option<Bytes>>(
+				"state_getStorage",
+				vec![json!(Bytes(key.to_vec())), json!(self.at)],
+			)
+			.map(|data| data.map(|data| data.0))
+			.map_err(|e| nomo::Error::Remote(e.to_string()))
+	}
+
+	/// Pairs under `prefix`, with the unsafe `state_getPairs` method.
+	fn pairs(&self, prefix: &[u8]) -> Result<Vec<(Vec<u8>, Vec<u8>)>, nomo::Error> {
+		self.remote
+			.call::<Vec<(Bytes, Bytes)>>(
+				"state_getPairs",
+				vec![json!(Bytes(prefix.to_vec())), json!(self.at)],
+			)
+			.map(|pairs| {
+				pairs
+					.into_iter()
+					.map(|(key, data)| (key.0, data.0))
+					.collect()
+			})
+			.map_err(|e| nomo::Error::Remote(e.to_string()))
+	}
+}